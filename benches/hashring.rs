@@ -1,6 +1,7 @@
 use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
 use hulahoop::HashRing;
 use rustc_hash::FxHasher;
+use std::collections::hash_map::DefaultHasher;
 use std::hash::BuildHasherDefault;
 
 pub fn criterion_benchmark(c: &mut Criterion) {
@@ -64,6 +65,41 @@ pub fn criterion_benchmark(c: &mut Criterion) {
         group.finish();
     }
 
+    {
+        let mut ring: HashRing<String, _> = HashRing::new();
+        for i in 0..100 {
+            ring.insert(format!("10.0.0.{i}:12345"), 100);
+        }
+        let mut group = c.benchmark_group("Getting nodes for n prehashed keys from a frozen ring");
+        let key_hashes = (0..100_000)
+            .map(|key| ring.hash_key(key))
+            .collect::<Vec<_>>();
+        let ring = ring.freeze();
+        for size in [10, 1000, 100_000].iter() {
+            let key_hashes = &key_hashes[..*size];
+            group.bench_with_input(
+                BenchmarkId::new("loop", size),
+                key_hashes,
+                |b, key_hashes| {
+                    b.iter(|| {
+                        key_hashes
+                            .iter()
+                            .map(|key_hash| ring.get_prehashed(*key_hash))
+                            .collect::<Vec<_>>()
+                    });
+                },
+            );
+            group.bench_with_input(
+                BenchmarkId::new("batched", size),
+                key_hashes,
+                |b, key_hashes| {
+                    b.iter(|| ring.get_many_prehashed(key_hashes));
+                },
+            );
+        }
+        group.finish();
+    }
+
     {
         let mut ring: HashRing<&str, _> = HashRing::new();
         let mut group = c.benchmark_group("Inserting virtual nodes");
@@ -87,6 +123,33 @@ pub fn criterion_benchmark(c: &mut Criterion) {
         group.finish();
     }
 
+    {
+        let mut group = c.benchmark_group("Building a ring with 100 nodes of weight n");
+        for size in [1, 10, 100].iter() {
+            let nodes = (0..100)
+                .map(|i| (format!("10.0.0.{i}:12345"), *size))
+                .collect::<Vec<_>>();
+            group.bench_with_input(BenchmarkId::new("insert", size), &nodes, |b, nodes| {
+                b.iter(|| {
+                    let mut ring: HashRing<String, _> = HashRing::new();
+                    for (node, weight) in nodes {
+                        ring.insert(node.clone(), *weight);
+                    }
+                    ring
+                });
+            });
+            group.bench_with_input(BenchmarkId::new("build_from", size), &nodes, |b, nodes| {
+                b.iter(|| {
+                    HashRing::build_from(
+                        nodes.iter().cloned(),
+                        BuildHasherDefault::<DefaultHasher>::default(),
+                    )
+                });
+            });
+        }
+        group.finish();
+    }
+
     {
         let mut ring: HashRing<&str, _> = HashRing::new();
         let mut group = c.benchmark_group("Removing virtual nodes");
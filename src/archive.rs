@@ -0,0 +1,256 @@
+use crate::frozen::successor_index;
+use crate::{FrozenHashRing, HulahoopError, KeyHash};
+use rkyv::api::high::{HighSerializer, HighValidator};
+use rkyv::bytecheck::CheckBytes;
+use rkyv::rancor;
+use rkyv::ser::allocator::ArenaHandle;
+use rkyv::util::AlignedVec;
+use rkyv::{Archive, Archived, Serialize};
+use std::fmt;
+use std::hash::{BuildHasher, Hash};
+
+/// The layout of an archived [FrozenHashRing](FrozenHashRing), without its hasher.
+#[derive(Debug, Archive, Serialize)]
+#[rkyv(archived = ArchivedFrozenHashRing)]
+pub struct FrozenHashRingArchive<N> {
+    // Every position in ascending order
+    positions: Vec<u64>,
+    // The index into `nodes` of the node of every position
+    owners: Vec<u32>,
+    nodes: Vec<N>,
+    weights: Vec<u64>,
+}
+
+impl<N> fmt::Debug for ArchivedFrozenHashRing<N>
+where
+    N: Archive,
+    Archived<N>: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ArchivedFrozenHashRing")
+            .field("positions", &self.positions)
+            .field("owners", &self.owners)
+            .field("nodes", &self.nodes)
+            .field("weights", &self.weights)
+            .finish()
+    }
+}
+
+impl<N, B> FrozenHashRing<N, B> {
+    /// Archives the ring with [rkyv](https://rkyv.org), so it can be looked up in place with
+    /// [ArchivedFrozenHashRing::access](ArchivedFrozenHashRing::access), e.g. after memory mapping the bytes.
+    ///
+    /// The positions of all virtual nodes, their owners and the nodes are archived. The hasher is not, keys have to
+    /// be hashed like the ring does to be routed to the same nodes. Fails with
+    /// [InvalidSnapshot](HulahoopError::InvalidSnapshot) if a node cannot be archived.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::{ArchivedFrozenHashRing, HashRing};
+    ///
+    /// let mut ring: HashRing<String, _> = HashRing::default();
+    /// ring.insert("10.0.0.1:1234".to_string(), 10);
+    /// ring.insert("10.0.0.2:1234".to_string(), 10);
+    /// let key_hash = ring.hash_key("Some key");
+    /// let frozen = ring.freeze();
+    ///
+    /// let bytes = frozen.to_archive().unwrap();
+    /// let archived = ArchivedFrozenHashRing::<String>::access(&bytes).unwrap();
+    /// assert_eq!(archived.len(), 2);
+    /// assert_eq!(
+    ///     archived.get_prehashed(key_hash).map(|node| node.as_str()),
+    ///     frozen.get_prehashed(key_hash).map(String::as_str)
+    /// );
+    /// ```
+    pub fn to_archive(&self) -> Result<AlignedVec, HulahoopError>
+    where
+        N: Clone,
+        FrozenHashRingArchive<N>:
+            for<'a> Serialize<HighSerializer<AlignedVec, ArenaHandle<'a>, rancor::Error>>,
+    {
+        let (nodes, weights) = self
+            .iter()
+            .map(|(node, weight)| (node.clone(), weight))
+            .unzip();
+        let archive = FrozenHashRingArchive {
+            positions: self.positions.clone(),
+            owners: self.owners.clone(),
+            nodes,
+            weights,
+        };
+        rkyv::to_bytes::<rancor::Error>(&archive).map_err(|_| HulahoopError::InvalidSnapshot)
+    }
+}
+
+impl<N> ArchivedFrozenHashRing<N>
+where
+    N: Archive,
+{
+    /// Accesses a ring archived by [to_archive](FrozenHashRing::to_archive) in place, without deserializing it.
+    ///
+    /// The bytes are validated once, taking time linear in their length, and have to be aligned to 16 bytes like the
+    /// bytes returned by [to_archive](FrozenHashRing::to_archive) or a memory mapped file. Fails with
+    /// [InvalidSnapshot](HulahoopError::InvalidSnapshot) if the bytes are not a valid archive.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::{ArchivedFrozenHashRing, HashRing, HulahoopError};
+    ///
+    /// let mut ring: HashRing<u32, _> = HashRing::default();
+    /// ring.insert(1, 10);
+    ///
+    /// let bytes = ring.freeze().to_archive().unwrap();
+    /// assert!(ArchivedFrozenHashRing::<u32>::access(&bytes).is_ok());
+    ///
+    /// let invalid = ArchivedFrozenHashRing::<u32>::access(&bytes[..bytes.len() - 16]);
+    /// assert_eq!(invalid.unwrap_err(), HulahoopError::InvalidSnapshot);
+    /// ```
+    pub fn access(bytes: &[u8]) -> Result<&Self, HulahoopError>
+    where
+        Self: for<'a> CheckBytes<HighValidator<'a, rancor::Error>>,
+    {
+        let archived = rkyv::access::<Self, rancor::Error>(bytes)
+            .map_err(|_| HulahoopError::InvalidSnapshot)?;
+        let nodes = archived.nodes.len();
+        if archived.owners.len() != archived.positions.len()
+            || archived.weights.len() != nodes
+            || archived
+                .owners
+                .iter()
+                .any(|owner| owner.to_native() as usize >= nodes)
+            || archived
+                .positions
+                .windows(2)
+                .any(|positions| positions[0] >= positions[1])
+        {
+            return Err(HulahoopError::InvalidSnapshot);
+        }
+        Ok(archived)
+    }
+
+    /// Returns the number of nodes.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns `true` if the ring contains no nodes.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Returns the total number of virtual nodes.
+    pub fn total_virtual_nodes(&self) -> usize {
+        self.positions.len()
+    }
+
+    /// An iterator visiting all archived node-weight pairs in arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item = (&Archived<N>, u64)> {
+        self.nodes
+            .iter()
+            .zip(self.weights.iter().map(|weight| weight.to_native()))
+    }
+
+    /// Returns a reference to the archived node of the already hashed key, like
+    /// [FrozenHashRing::get_prehashed](FrozenHashRing::get_prehashed).
+    #[inline]
+    pub fn get_prehashed(&self, key_hash: KeyHash) -> Option<&Archived<N>> {
+        let index = successor_index(
+            self.positions.len(),
+            |index| self.positions[index].to_native(),
+            key_hash.get(),
+        )?;
+        Some(&self.nodes[self.owners[index].to_native() as usize])
+    }
+
+    /// Returns a reference to the archived node of the key, hashing it with `hash_builder`.
+    ///
+    /// To look up the same node as the archived ring, `hash_builder` has to hash like the hasher of the ring.
+    #[inline]
+    pub fn get<K, B>(&self, key: K, hash_builder: &B) -> Option<&Archived<N>>
+    where
+        K: Hash,
+        B: BuildHasher,
+    {
+        self.get_prehashed(KeyHash::new(hash_builder.hash_one(key)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FrozenHashRingArchive;
+    use crate::{ArchivedFrozenHashRing, HashRing, HulahoopError, KeyHash};
+    use rkyv::rancor;
+
+    #[test]
+    fn archived_rings_route_like_the_frozen_ring() {
+        let mut ring: HashRing<u32, _> = HashRing::new();
+        for node in 0..20 {
+            ring.insert(node, u64::from(node % 5 + 1) * 10);
+        }
+        let mut hashes = vec![0, u64::MAX];
+        for position in ring.virtual_nodes.keys() {
+            hashes.extend([
+                position.wrapping_sub(1),
+                *position,
+                position.wrapping_add(1),
+            ]);
+        }
+        let hash_builder = ring.hasher().clone();
+        let frozen = ring.freeze();
+
+        let bytes = frozen.to_archive().unwrap();
+        let archived = ArchivedFrozenHashRing::<u32>::access(&bytes).unwrap();
+        assert_eq!(archived.len(), frozen.len());
+        assert_eq!(archived.total_virtual_nodes(), frozen.total_virtual_nodes());
+        assert!(archived
+            .iter()
+            .map(|(node, weight)| (node.to_native(), weight))
+            .eq(frozen.iter().map(|(node, weight)| (*node, weight))));
+        for hash in hashes {
+            let key_hash = KeyHash::new(hash);
+            assert_eq!(
+                archived
+                    .get_prehashed(key_hash)
+                    .map(|node| node.to_native()),
+                frozen.get_prehashed(key_hash).copied(),
+                "{hash}"
+            );
+        }
+        assert_eq!(
+            archived
+                .get("Some key", &hash_builder)
+                .map(|node| node.to_native()),
+            frozen.get("Some key").copied()
+        );
+    }
+
+    #[test]
+    fn empty_and_invalid_archives() {
+        let bytes = HashRing::<u32, _>::new().freeze().to_archive().unwrap();
+        let archived = ArchivedFrozenHashRing::<u32>::access(&bytes).unwrap();
+        assert!(archived.is_empty());
+        assert_eq!(archived.get_prehashed(KeyHash::new(0)), None);
+
+        let out_of_range = FrozenHashRingArchive {
+            positions: vec![1, 2],
+            owners: vec![0, 1],
+            nodes: vec![1u32],
+            weights: vec![2],
+        };
+        let unsorted = FrozenHashRingArchive {
+            positions: vec![2, 1],
+            owners: vec![0, 0],
+            nodes: vec![1u32],
+            weights: vec![2],
+        };
+        for archive in [out_of_range, unsorted] {
+            let bytes = rkyv::to_bytes::<rancor::Error>(&archive).unwrap();
+            assert_eq!(
+                ArchivedFrozenHashRing::<u32>::access(&bytes).unwrap_err(),
+                HulahoopError::InvalidSnapshot
+            );
+        }
+    }
+}
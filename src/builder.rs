@@ -0,0 +1,278 @@
+//! A builder for configuring a [HashRing](crate::HashRing).
+
+use crate::{
+    CollisionCallback, CollisionPolicy, DefaultBuildHasher, EventCallback, HashRing, RingEvent,
+    VirtualNodeFn,
+};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Debug;
+use std::num::NonZeroUsize;
+use std::sync::Arc;
+
+/// A builder for a [HashRing](HashRing) with non-default settings.
+///
+/// # Examples
+///
+/// ```
+/// use hulahoop::HashRingBuilder;
+///
+/// let mut ring = HashRingBuilder::default()
+///     .total_virtual_node_budget(15)
+///     .build();
+///
+/// ring.insert("10.0.0.1:1234", 10);
+/// ring.insert("10.0.0.2:1234", 10);
+/// assert_eq!(ring.remaining_budget(), 0);
+/// ```
+pub struct HashRingBuilder<N, B> {
+    hash_builder: B,
+    virtual_node_fn: Option<VirtualNodeFn<N, B>>,
+    total_virtual_node_budget: Option<u64>,
+    replication_factor: NonZeroUsize,
+    collision_policy: CollisionPolicy,
+    collision_callback: Option<CollisionCallback<N>>,
+    event_callback: Option<EventCallback<N>>,
+}
+
+impl<N, B> Debug for HashRingBuilder<N, B>
+where
+    B: Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HashRingBuilder")
+            .field("hash_builder", &self.hash_builder)
+            .field("custom_virtual_node_fn", &self.virtual_node_fn.is_some())
+            .field("total_virtual_node_budget", &self.total_virtual_node_budget)
+            .field("replication_factor", &self.replication_factor)
+            .field("collision_policy", &self.collision_policy)
+            .field("collision_callback", &self.collision_callback.is_some())
+            .field("event_callback", &self.event_callback.is_some())
+            .finish()
+    }
+}
+
+impl<N> Default for HashRingBuilder<N, DefaultBuildHasher> {
+    fn default() -> Self {
+        Self::with_hasher(DefaultBuildHasher::default())
+    }
+}
+
+impl<N, B> HashRingBuilder<N, B> {
+    /// Creates a builder for a `HashRing` which will use the given `hash_builder` to hash nodes and keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::hash::BuildHasherDefault;
+    /// use rustc_hash::FxHasher;
+    /// use hulahoop::HashRingBuilder;
+    ///
+    /// let mut ring = HashRingBuilder::with_hasher(BuildHasherDefault::<FxHasher>::default()).build();
+    ///
+    /// ring.insert("10.0.0.1:1234", 1);
+    /// assert_eq!(ring.get("Some key"), Some(&"10.0.0.1:1234"));
+    /// ```
+    pub fn with_hasher(hash_builder: B) -> Self {
+        Self {
+            hash_builder,
+            virtual_node_fn: None,
+            total_virtual_node_budget: None,
+            replication_factor: NonZeroUsize::MIN,
+            collision_policy: CollisionPolicy::Replace,
+            collision_callback: None,
+            event_callback: None,
+        }
+    }
+
+    /// Sets the function computing the position of each virtual node.
+    ///
+    /// See [HashRing::with_virtual_node_fn](HashRing::with_virtual_node_fn) for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRingBuilder;
+    ///
+    /// let mut ring = HashRingBuilder::default()
+    ///     .virtual_node_fn(|_, _, index| index)
+    ///     .build();
+    ///
+    /// ring.insert("10.0.0.1:1234", 1);
+    /// assert_eq!(ring.get("Some key"), Some(&"10.0.0.1:1234"));
+    /// ```
+    pub fn virtual_node_fn<F>(mut self, virtual_node_fn: F) -> Self
+    where
+        F: Fn(&B, &N, u64) -> u64 + Send + Sync + 'static,
+    {
+        self.virtual_node_fn = Some(Arc::new(virtual_node_fn));
+        self
+    }
+
+    /// Sets the maximum number of virtual nodes across all nodes of the ring.
+    ///
+    /// Once the sum of the actual weights of all nodes reaches the budget, nodes inserted afterwards get
+    /// their weight reduced to the remaining budget. A node is not inserted at all if no budget is left.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRingBuilder;
+    ///
+    /// let mut ring = HashRingBuilder::default()
+    ///     .total_virtual_node_budget(15)
+    ///     .build();
+    ///
+    /// ring.insert("10.0.0.1:1234", 10);
+    /// ring.insert("10.0.0.2:1234", 10);
+    /// ring.insert("10.0.0.3:1234", 10);
+    /// assert_eq!(ring.iter().map(|(_, weight)| weight).sum::<u64>(), 15);
+    /// assert!(!ring.contains_node(&"10.0.0.3:1234"));
+    /// ```
+    pub fn total_virtual_node_budget(mut self, budget: u64) -> Self {
+        self.total_virtual_node_budget = Some(budget);
+        self
+    }
+
+    /// Sets the number of replicas returned by [HashRing::get_replicas](HashRing::get_replicas). Defaults to 1.
+    ///
+    /// The ring has no notion of zones, so the replicas are the next distinct nodes clockwise wherever they are
+    /// located, and several of them may share a zone. To spread the replicas over zones, pick them with
+    /// [get_n_filtered](HashRing::get_n_filtered) instead, e.g. skipping nodes in zones already picked.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::num::NonZeroUsize;
+    /// use hulahoop::HashRingBuilder;
+    ///
+    /// let mut ring = HashRingBuilder::default()
+    ///     .replication_factor(NonZeroUsize::new(3).unwrap())
+    ///     .build();
+    ///
+    /// ring.insert("10.0.0.1:1234", 10);
+    /// ring.insert("10.0.0.2:1234", 10);
+    /// ring.insert("10.0.0.3:1234", 10);
+    /// ring.insert("10.0.0.4:1234", 10);
+    /// assert_eq!(ring.get_replicas("Some key").len(), 3);
+    /// ```
+    pub fn replication_factor(mut self, replication_factor: NonZeroUsize) -> Self {
+        self.replication_factor = replication_factor;
+        self
+    }
+
+    /// Sets the [CollisionPolicy](CollisionPolicy) deciding what happens when virtual nodes of different nodes collide.
+    /// Defaults to [Replace](CollisionPolicy::Replace).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::{CollisionPolicy, HashRingBuilder};
+    ///
+    /// let mut ring = HashRingBuilder::default()
+    ///     .virtual_node_fn(|_, node: &&str, index| match (*node, index) {
+    ///         ("b", 0) => 100,
+    ///         _ => index,
+    ///     })
+    ///     .collision_policy(CollisionPolicy::Reassign)
+    ///     .build();
+    ///
+    /// ring.insert("a", 10);
+    /// // All but the first position of "b" collide with positions of "a"
+    /// ring.insert("b", 10);
+    /// assert_eq!(ring.positions_of(&"a").len(), 10);
+    /// assert_eq!(ring.positions_of(&"b").len(), 10);
+    /// ```
+    pub fn collision_policy(mut self, collision_policy: CollisionPolicy) -> Self {
+        self.collision_policy = collision_policy;
+        self
+    }
+
+    /// Sets a callback invoked whenever a node ends up with fewer virtual nodes than it was inserted with.
+    ///
+    /// The callback receives the node, the weight it was inserted with and the number of virtual nodes it actually got.
+    /// Virtual nodes of the same node colliding with each other are only placed once, so a node may get fewer virtual nodes
+    /// than its weight. The weight is the one after a reduction by the
+    /// [total virtual node budget](HashRingBuilder::total_virtual_node_budget), if any.
+    /// Nodes losing virtual nodes to an inserted node are reported as well, with the number of virtual nodes they have
+    /// left, which is 0 if they were replaced entirely.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::atomic::{AtomicU64, Ordering};
+    /// use std::sync::Arc;
+    /// use hulahoop::HashRingBuilder;
+    ///
+    /// let lost_virtual_nodes = Arc::new(AtomicU64::new(0));
+    /// let counter = lost_virtual_nodes.clone();
+    /// let mut ring = HashRingBuilder::default()
+    ///     .virtual_node_fn(|_, _, index| index % 5)
+    ///     .collision_callback(move |_, requested, achieved| {
+    ///         counter.fetch_add(requested - achieved, Ordering::Relaxed);
+    ///     })
+    ///     .build();
+    ///
+    /// ring.insert("10.0.0.1:1234", 10);
+    /// assert_eq!(lost_virtual_nodes.load(Ordering::Relaxed), 5);
+    /// ```
+    pub fn collision_callback<F>(mut self, collision_callback: F) -> Self
+    where
+        F: Fn(&N, u64, u64) + Send + Sync + 'static,
+    {
+        self.collision_callback = Some(Arc::new(collision_callback));
+        self
+    }
+
+    /// Sets a callback invoked with a [RingEvent](RingEvent) whenever a node is added, removed or its weight changes.
+    ///
+    /// The callback is invoked after the change was applied, with the arcs of the hash space routed to a different node,
+    /// which allows to start rebalancing data exactly when the ownership of keys changes.
+    /// Inserting a node which is already part of the ring reports its removal followed by its addition.
+    /// [apply](HashRing::apply), [rebuild_with](HashRing::rebuild_with) and
+    /// [deduplicate_positions](HashRing::deduplicate_positions) report all their changes as a single
+    /// [Batch](RingEvent::Batch).
+    /// [replace](HashRing::replace) and [update_by_id](HashRing::update_by_id) keep all positions and do not report events.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::{Arc, Mutex};
+    /// use hulahoop::{HashRingBuilder, RingEvent};
+    ///
+    /// let moved = Arc::new(Mutex::new(Vec::new()));
+    /// let recorded = moved.clone();
+    /// let mut ring = HashRingBuilder::default()
+    ///     .event_callback(move |event: &RingEvent<'_, &str>| {
+    ///         recorded.lock().unwrap().extend_from_slice(event.ranges());
+    ///     })
+    ///     .build();
+    ///
+    /// ring.insert("10.0.0.1:1234", 10);
+    /// assert_eq!(*moved.lock().unwrap(), vec![(0, u64::MAX)]);
+    /// ```
+    pub fn event_callback<F>(mut self, event_callback: F) -> Self
+    where
+        F: Fn(&RingEvent<'_, N>) + Send + Sync + 'static,
+    {
+        self.event_callback = Some(Arc::new(event_callback));
+        self
+    }
+
+    /// Creates the configured `HashRing`.
+    pub fn build(self) -> HashRing<N, B> {
+        HashRing {
+            virtual_nodes: BTreeMap::new(),
+            master_nodes_by_hash: HashMap::new(),
+            hash_builder: self.hash_builder,
+            virtual_node_fn: self.virtual_node_fn,
+            total_virtual_node_budget: self.total_virtual_node_budget,
+            replication_factor: self.replication_factor,
+            collision_policy: self.collision_policy,
+            collision_callback: self.collision_callback,
+            event_callback: self.event_callback,
+            node_count: 0,
+            generation: 0,
+            version: 0,
+        }
+    }
+}
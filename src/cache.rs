@@ -0,0 +1,201 @@
+use crate::{HashRing, KeyHash, MasterNode};
+use std::hash::{BuildHasher, Hash};
+use std::num::NonZeroUsize;
+use std::sync::Arc;
+
+/// A ring remembering the nodes of the most recently looked up keys.
+///
+/// Up to `capacity` key hashes are cached together with their node, so looking up one of them again skips
+/// the search of the ring. When the cache is full, the oldest resolution is replaced. Keys still have to be hashed,
+/// and the cache is scanned linearly, so it pays off for small capacities and workloads repeatedly looking up
+/// a few hot keys. Any mutable access to the ring through [ring_mut](CachedHashRing::ring_mut) clears the cache.
+///
+/// # Examples
+///
+/// ```
+/// use std::num::NonZeroUsize;
+/// use hulahoop::{CachedHashRing, HashRing};
+///
+/// let mut ring = CachedHashRing::new(HashRing::default(), NonZeroUsize::new(8).unwrap());
+/// ring.ring_mut().insert("10.0.0.1:1234", 10);
+///
+/// assert_eq!(ring.get("Some key"), Some(&"10.0.0.1:1234"));
+/// // Served from the cache
+/// assert_eq!(ring.get("Some key"), Some(&"10.0.0.1:1234"));
+/// ```
+#[derive(Debug)]
+pub struct CachedHashRing<N, B> {
+    ring: HashRing<N, B>,
+    // The cached key hashes with their node, replaced in insertion order
+    entries: Vec<(u64, Arc<MasterNode<N>>)>,
+    // The index of the entry to replace next once the cache is full
+    next: usize,
+    capacity: NonZeroUsize,
+}
+
+/// Clones the ring with an empty cache.
+///
+/// The cached nodes belong to the original ring, so they are not carried over to the clone, whose ring has nodes
+/// of its own.
+impl<N, B> Clone for CachedHashRing<N, B>
+where
+    N: Clone,
+    B: Clone,
+{
+    fn clone(&self) -> Self {
+        Self::new(self.ring.clone(), self.capacity)
+    }
+}
+
+impl<N, B> CachedHashRing<N, B> {
+    /// Creates a `CachedHashRing` caching the nodes of up to `capacity` keys of the ring.
+    pub fn new(ring: HashRing<N, B>, capacity: NonZeroUsize) -> Self {
+        Self {
+            ring,
+            entries: Vec::with_capacity(capacity.get()),
+            next: 0,
+            capacity,
+        }
+    }
+
+    /// Returns the maximum number of cached keys.
+    pub fn capacity(&self) -> NonZeroUsize {
+        self.capacity
+    }
+
+    /// Returns a reference to the ring.
+    pub fn ring(&self) -> &HashRing<N, B> {
+        &self.ring
+    }
+
+    /// Returns a mutable reference to the ring, clearing the cache.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::num::NonZeroUsize;
+    /// use hulahoop::{CachedHashRing, HashRing};
+    ///
+    /// let mut ring = CachedHashRing::new(HashRing::default(), NonZeroUsize::new(8).unwrap());
+    /// ring.ring_mut().insert("10.0.0.1:1234", 10);
+    /// assert_eq!(ring.get("Some key"), Some(&"10.0.0.1:1234"));
+    ///
+    /// ring.ring_mut().remove(&"10.0.0.1:1234");
+    /// assert_eq!(ring.get("Some key"), None);
+    /// ```
+    pub fn ring_mut(&mut self) -> &mut HashRing<N, B> {
+        self.entries.clear();
+        self.next = 0;
+        &mut self.ring
+    }
+
+    /// Takes ownership of the ring.
+    pub fn into_ring(self) -> HashRing<N, B> {
+        self.ring
+    }
+}
+
+impl<N, B> CachedHashRing<N, B>
+where
+    N: Hash,
+    B: BuildHasher,
+{
+    /// Returns a reference to the node of the key, like [HashRing::get](HashRing::get).
+    pub fn get<K>(&mut self, key: K) -> Option<&N>
+    where
+        K: Hash,
+    {
+        let key_hash = self.ring.hash_key(key);
+        self.get_prehashed(key_hash)
+    }
+
+    /// Returns a reference to the node of the already hashed key, like [HashRing::get_prehashed](HashRing::get_prehashed).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::num::NonZeroUsize;
+    /// use hulahoop::{CachedHashRing, HashRing};
+    ///
+    /// let mut ring = CachedHashRing::new(HashRing::default(), NonZeroUsize::new(8).unwrap());
+    /// ring.ring_mut().insert("10.0.0.1:1234", 10);
+    ///
+    /// let key_hash = ring.ring().hash_key("Some key");
+    /// assert_eq!(ring.get_prehashed(key_hash), Some(&"10.0.0.1:1234"));
+    /// ```
+    pub fn get_prehashed(&mut self, key_hash: KeyHash) -> Option<&N> {
+        let key_hash = key_hash.get();
+        let index = match self
+            .entries
+            .iter()
+            .position(|(cached_hash, _)| *cached_hash == key_hash)
+        {
+            Some(index) => index,
+            None => {
+                let master_node = self.ring.get_master_node_for_key_hash(key_hash)?.clone();
+                self.cache(key_hash, master_node)
+            }
+        };
+        Some(&self.entries[index].1.node)
+    }
+
+    fn cache(&mut self, key_hash: u64, master_node: Arc<MasterNode<N>>) -> usize {
+        if self.entries.len() < self.capacity.get() {
+            self.entries.push((key_hash, master_node));
+            self.entries.len() - 1
+        } else {
+            let index = self.next;
+            self.entries[index] = (key_hash, master_node);
+            self.next = (index + 1) % self.capacity.get();
+            index
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{CachedHashRing, HashRing};
+    use std::num::NonZeroUsize;
+
+    #[test]
+    fn cache_replaces_the_oldest_key_and_is_cleared_on_mutation() {
+        let mut ring: HashRing<u32, _> = HashRing::new();
+        for node in 0..10 {
+            ring.insert(node, 10);
+        }
+        let mut cached = CachedHashRing::new(ring.clone(), NonZeroUsize::new(2).unwrap());
+        for key in [1, 2, 1, 3, 4, 2, 1] {
+            assert_eq!(cached.get(key), ring.get(key));
+            assert!(cached.entries.len() <= 2);
+        }
+        let cached_hashes = cached
+            .entries
+            .iter()
+            .map(|(key_hash, _)| *key_hash)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            cached_hashes,
+            vec![ring.hash_key(2).get(), ring.hash_key(1).get()]
+        );
+
+        let node = *cached.get(1).unwrap();
+        cached.ring_mut().remove(&node);
+        assert!(cached.entries.is_empty());
+        assert_ne!(cached.get(1), Some(&node));
+    }
+
+    #[test]
+    fn clones_do_not_share_cached_nodes() {
+        let mut cached = CachedHashRing::new(HashRing::new(), NonZeroUsize::new(2).unwrap());
+        cached.ring_mut().insert("a".to_string(), 10);
+        assert_eq!(cached.get(1), Some(&"a".to_string()));
+
+        let mut cloned = cached.clone();
+        assert!(cloned.entries.is_empty());
+        assert_eq!(cloned.get(1), Some(&"a".to_string()));
+        assert_eq!(
+            cached.ring_mut().remove_entry("a"),
+            Some(("a".to_string(), 10))
+        );
+    }
+}
@@ -0,0 +1,35 @@
+/// Determines what happens when a virtual node of an inserted node lands on a position already owned by another node.
+///
+/// The policy of a ring is set with [HashRingBuilder::collision_policy](crate::HashRingBuilder::collision_policy)
+/// and can be inspected with [HashRing::collision_policy](crate::HashRing::collision_policy).
+///
+/// The first virtual node of a node identifies it within the ring, so a node whose first virtual node lands on the
+/// position of another node always replaces that node, regardless of the policy.
+///
+/// # Examples
+///
+/// ```
+/// use hulahoop::{CollisionPolicy, HashRingBuilder};
+///
+/// let ring = HashRingBuilder::<&str, _>::default()
+///     .collision_policy(CollisionPolicy::Reassign)
+///     .build();
+/// assert_eq!(ring.collision_policy(), CollisionPolicy::Reassign);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum CollisionPolicy {
+    /// The inserted node takes over the position, the other node loses the virtual node.
+    ///
+    /// Positions only depend on the node and the index of the virtual node, but nodes may end up with fewer
+    /// virtual nodes than their weight.
+    #[default]
+    Replace,
+    /// The virtual node is moved to the next free position, probing forward one position at a time.
+    ///
+    /// Every node gets exactly as many virtual nodes as its weight, at positions disjoint from all other nodes.
+    /// Positions depend on the nodes already present, so the same set of nodes inserted in a different order may
+    /// end up at different positions. A moved virtual node is placed right behind the one it collided with and
+    /// therefore receives almost no keys: probing guarantees the number of virtual nodes, not their share of the
+    /// key space. With well distributed hashes collisions are rare and the impact on the distribution is negligible.
+    Reassign,
+}
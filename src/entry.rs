@@ -0,0 +1,339 @@
+use crate::{HashRing, MasterNode};
+use std::fmt::{Debug, Formatter};
+use std::hash::{BuildHasher, Hash};
+use std::num::NonZeroU64;
+use std::sync::Arc;
+
+impl<N, B> HashRing<N, B>
+where
+    N: Hash,
+    B: BuildHasher,
+{
+    /// Gets the entry of the node in the ring for in-place manipulation.
+    ///
+    /// The node is only hashed once to find out whether it is part of the ring, so inserting it if absent or updating
+    /// its weight if present does not look it up again. The entry is only occupied by a node equal to `node`, never by
+    /// another node holding the position of its first virtual node.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::{Entry, HashRing};
+    ///
+    /// let mut ring: HashRing<&str, _> = HashRing::default();
+    ///
+    /// ring.entry("10.0.0.1:1234").or_insert(10);
+    /// assert_eq!(ring.len(), 1);
+    ///
+    /// match ring.entry("10.0.0.1:1234") {
+    ///     Entry::Occupied(entry) => assert_eq!(entry.set_weight(20), 10),
+    ///     Entry::Vacant(_) => unreachable!(),
+    /// }
+    /// assert_eq!(ring.total_weight(), 20);
+    /// ```
+    pub fn entry(&mut self, node: N) -> Entry<'_, N, B>
+    where
+        N: Eq,
+    {
+        let id = self.virtual_node_hash(&node, 0);
+        // The position of the first virtual node may be held by another node, which the node must not be mistaken for
+        let position = self
+            .master_node_with_id(&node, id, |master_node| master_node.node == node)
+            .cloned()
+            .and_then(|master_node| self.owned_position(id, &master_node));
+        match position {
+            Some(position) => Entry::Occupied(OccupiedEntry {
+                ring: self,
+                node,
+                position,
+            }),
+            None => Entry::Vacant(VacantEntry { ring: self, node }),
+        }
+    }
+
+    /// Returns a position owned by the master node, preferring the one of its id.
+    fn owned_position(&self, id: u64, master_node: &Arc<MasterNode<N>>) -> Option<u64> {
+        if self.is_owned_by(&id, master_node) {
+            return Some(id);
+        }
+        self.owned_positions(master_node).into_iter().next()
+    }
+}
+
+/// A view into a single node of a ring, which may either be vacant or occupied.
+///
+/// This `enum` is created by the [entry](HashRing::entry) method on [HashRing](HashRing). See its documentation for more.
+pub enum Entry<'a, N, B> {
+    /// The node is part of the ring.
+    Occupied(OccupiedEntry<'a, N, B>),
+    /// The node is not part of the ring.
+    Vacant(VacantEntry<'a, N, B>),
+}
+
+impl<'a, N, B> Entry<'a, N, B>
+where
+    N: Hash,
+    B: BuildHasher,
+{
+    /// Returns a reference to the node the entry was created for.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut ring: HashRing<&str, _> = HashRing::default();
+    /// assert_eq!(ring.entry("10.0.0.1:1234").node(), &"10.0.0.1:1234");
+    /// ```
+    pub fn node(&self) -> &N {
+        match self {
+            Entry::Occupied(entry) => entry.node(),
+            Entry::Vacant(entry) => entry.node(),
+        }
+    }
+
+    /// Inserts the node with `weight` if it is not part of the ring, returning a reference to the node in the ring.
+    ///
+    /// The weight of a node already part of the ring is left unchanged.
+    /// Returns `None` if the node is absent and `weight` is 0 or the total virtual node budget is exhausted,
+    /// see [VacantEntry::insert](VacantEntry::insert).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut ring: HashRing<&str, _> = HashRing::default();
+    ///
+    /// assert_eq!(ring.entry("10.0.0.1:1234").or_insert(10), Some(&"10.0.0.1:1234"));
+    /// assert_eq!(ring.entry("10.0.0.1:1234").or_insert(20), Some(&"10.0.0.1:1234"));
+    /// assert_eq!(ring.total_weight(), 10);
+    /// ```
+    pub fn or_insert(self, weight: u64) -> Option<&'a N> {
+        match self {
+            Entry::Occupied(entry) => Some(entry.into_ref()),
+            Entry::Vacant(entry) => entry.insert(weight),
+        }
+    }
+}
+
+impl<'a, N, B> Debug for Entry<'a, N, B>
+where
+    N: Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Entry::Occupied(entry) => f.debug_tuple("Entry").field(entry).finish(),
+            Entry::Vacant(entry) => f.debug_tuple("Entry").field(entry).finish(),
+        }
+    }
+}
+
+/// A view into a node which is part of a ring. It is part of the [Entry](Entry) enum.
+pub struct OccupiedEntry<'a, N, B> {
+    ring: &'a mut HashRing<N, B>,
+    node: N,
+    // A position owned by the node, usually the one of its first virtual node
+    position: u64,
+}
+
+impl<'a, N, B> OccupiedEntry<'a, N, B>
+where
+    N: Hash,
+    B: BuildHasher,
+{
+    /// Returns a reference to the node the entry was created for.
+    pub fn node(&self) -> &N {
+        &self.node
+    }
+
+    /// Returns a reference to the node in the ring.
+    pub fn get(&self) -> &N {
+        &self.master_node().node
+    }
+
+    /// Returns the actual weight of the node in the ring.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::{Entry, HashRing};
+    ///
+    /// let mut ring: HashRing<&str, _> = HashRing::default();
+    ///
+    /// ring.insert("10.0.0.1:1234", 10);
+    /// if let Entry::Occupied(entry) = ring.entry("10.0.0.1:1234") {
+    ///     assert_eq!(entry.weight(), 10);
+    /// }
+    /// ```
+    pub fn weight(&self) -> u64 {
+        self.master_node().weight.get()
+    }
+
+    /// Updates the weight of the node like [upsert](HashRing::upsert), returning its previous actual weight.
+    ///
+    /// Only the virtual nodes making up the difference between the old and the new `weight` are added or removed.
+    /// A `weight` of 0 removes the node.
+    pub fn set_weight(self, weight: u64) -> u64 {
+        let master_node = self.master_node().clone();
+        self.ring.update_master_node(master_node, self.node, weight)
+    }
+
+    /// Removes the node from the ring, returning the number of its virtual nodes like [remove](HashRing::remove).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::{Entry, HashRing};
+    ///
+    /// let mut ring: HashRing<&str, _> = HashRing::default();
+    ///
+    /// ring.insert("10.0.0.1:1234", 10);
+    /// if let Entry::Occupied(entry) = ring.entry("10.0.0.1:1234") {
+    ///     assert_eq!(entry.remove(), 10);
+    /// }
+    /// assert!(ring.is_empty());
+    /// ```
+    pub fn remove(self) -> u64 {
+        let master_node = self.master_node().clone();
+        self.ring.remove_master_node(master_node).1
+    }
+
+    fn into_ref(self) -> &'a N {
+        &self.ring.virtual_nodes[&self.position].node
+    }
+
+    fn master_node(&self) -> &Arc<MasterNode<N>> {
+        &self.ring.virtual_nodes[&self.position]
+    }
+}
+
+impl<'a, N, B> Debug for OccupiedEntry<'a, N, B>
+where
+    N: Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OccupiedEntry")
+            .field("node", &self.node)
+            .field("weight", &self.ring.virtual_nodes[&self.position].weight)
+            .finish()
+    }
+}
+
+/// A view into a node which is not part of a ring. It is part of the [Entry](Entry) enum.
+pub struct VacantEntry<'a, N, B> {
+    ring: &'a mut HashRing<N, B>,
+    node: N,
+}
+
+impl<'a, N, B> VacantEntry<'a, N, B>
+where
+    N: Hash,
+    B: BuildHasher,
+{
+    /// Returns a reference to the node the entry was created for.
+    pub fn node(&self) -> &N {
+        &self.node
+    }
+
+    /// Takes ownership of the node.
+    pub fn into_node(self) -> N {
+        self.node
+    }
+
+    /// Inserts the node with `weight`, returning a reference to the node in the ring.
+    ///
+    /// Like [insert](HashRing::insert), the node is not inserted and `None` is returned if `weight` is 0,
+    /// and the weight is reduced to the remaining [total virtual node budget](crate::HashRingBuilder::total_virtual_node_budget).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::{Entry, HashRing};
+    ///
+    /// let mut ring: HashRing<&str, _> = HashRing::default();
+    ///
+    /// if let Entry::Vacant(entry) = ring.entry("10.0.0.1:1234") {
+    ///     assert_eq!(entry.insert(10), Some(&"10.0.0.1:1234"));
+    /// }
+    /// assert_eq!(ring.len(), 1);
+    /// ```
+    pub fn insert(self, weight: u64) -> Option<&'a N> {
+        let weight = NonZeroU64::new(self.ring.budgeted_weight(&self.node, weight))?;
+        let ring = self.ring;
+        let master_node = ring.insert_absent(self.node, weight);
+        let position = ring.owned_position(master_node.id, &master_node)?;
+        let ring: &'a HashRing<N, B> = ring;
+        Some(&ring.virtual_nodes[&position].node)
+    }
+}
+
+impl<'a, N, B> Debug for VacantEntry<'a, N, B>
+where
+    N: Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("VacantEntry")
+            .field("node", &self.node)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{CollisionPolicy, Entry, HashRing, HashRingBuilder};
+    use std::collections::hash_map::DefaultHasher;
+    use std::collections::HashMap;
+    use std::hash::BuildHasherDefault;
+
+    #[test]
+    fn entry_inserts_absent_and_updates_present_nodes() {
+        for collision_policy in [CollisionPolicy::Replace, CollisionPolicy::Reassign] {
+            let mut ring = HashRingBuilder::default()
+                .collision_policy(collision_policy)
+                .build();
+            let mut upserted: HashRing<&str, _> = HashRingBuilder::default()
+                .collision_policy(collision_policy)
+                .build();
+            for (node, weight) in [("a", 10), ("b", 20), ("a", 30), ("b", 0), ("c", 0)] {
+                match ring.entry(node) {
+                    Entry::Occupied(entry) => {
+                        entry.set_weight(weight);
+                    }
+                    Entry::Vacant(entry) => {
+                        entry.insert(weight);
+                    }
+                }
+                upserted.upsert(node, weight);
+                assert!(ring.virtual_nodes.keys().eq(upserted.virtual_nodes.keys()));
+                assert_eq!(ring.len(), upserted.len());
+            }
+            assert_eq!(ring.to_weights_map(), upserted.to_weights_map());
+        }
+    }
+
+    #[test]
+    fn entries_are_not_occupied_by_nodes_holding_the_first_position() {
+        let mut ring = HashRing::with_virtual_node_fn(
+            BuildHasherDefault::<DefaultHasher>::default(),
+            |_, node: &&str, index| match (*node, index) {
+                ("a", _) => 10 + index,
+                ("b", 0) => 20,
+                (_, _) => 10,
+            },
+        );
+        ring.insert("a", 2);
+        // "b" takes over the first position of "a"
+        ring.insert("b", 2);
+        assert!(matches!(ring.entry("c"), Entry::Vacant(_)));
+
+        match ring.entry("a") {
+            Entry::Occupied(entry) => {
+                assert_eq!(entry.get(), &"a");
+                assert_eq!(entry.remove(), 1);
+            }
+            Entry::Vacant(_) => panic!("\"a\" is part of the ring"),
+        }
+        assert_eq!(ring.to_weights_map(), HashMap::from([("b", 2)]));
+    }
+}
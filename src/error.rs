@@ -0,0 +1,105 @@
+//! Errors returned by the fallible methods of a [HashRing](crate::HashRing).
+
+use std::error::Error;
+use std::fmt::{Debug, Display, Formatter};
+
+/// The error type for fallible operations on a [HashRing](crate::HashRing).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum HulahoopError {
+    /// The ring does not contain any nodes.
+    EmptyRing,
+    /// A node was inserted with a weight of 0.
+    ZeroWeight,
+    /// A node was inserted with a weight larger than [MAX_WEIGHT](crate::HashRing::MAX_WEIGHT).
+    WeightTooLarge {
+        /// The weight the node was inserted with.
+        requested: u64,
+        /// The largest supported weight.
+        max: u64,
+    },
+    /// Inserting a node would exceed the [total virtual node budget](crate::HashRingBuilder::total_virtual_node_budget).
+    BudgetExceeded {
+        /// The weight the node was inserted with.
+        requested: u64,
+        /// The number of virtual nodes that could still be added.
+        remaining: u64,
+    },
+    /// The node is not part of the ring.
+    NodeAbsent,
+    /// The first virtual node of an inserted node collides with a virtual node of a different node,
+    /// so inserting it would replace that node.
+    NodeCollision,
+    /// Virtual nodes of an inserted node collide with virtual nodes of other nodes or with each other,
+    /// so the node would get fewer virtual nodes than its weight.
+    VirtualNodeCollision {
+        /// The number of colliding virtual nodes.
+        colliding: u64,
+    },
+    /// A snapshot of a ring could not be encoded or decoded.
+    InvalidSnapshot,
+}
+
+impl Display for HulahoopError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HulahoopError::EmptyRing => write!(f, "the ring does not contain any nodes"),
+            HulahoopError::ZeroWeight => write!(f, "the weight of a node must not be 0"),
+            HulahoopError::WeightTooLarge { requested, max } => write!(
+                f,
+                "a weight of {requested} exceeds the largest supported weight of {max}"
+            ),
+            HulahoopError::BudgetExceeded {
+                requested,
+                remaining,
+            } => write!(
+                f,
+                "a weight of {requested} exceeds the remaining virtual node budget of {remaining}"
+            ),
+            HulahoopError::NodeAbsent => write!(f, "the node is not part of the ring"),
+            HulahoopError::NodeCollision => {
+                write!(f, "the node collides with a different node of the ring")
+            }
+            HulahoopError::VirtualNodeCollision { colliding } => {
+                write!(f, "{colliding} virtual nodes of the node collide")
+            }
+            HulahoopError::InvalidSnapshot => write!(f, "the snapshot of the ring is invalid"),
+        }
+    }
+}
+
+impl Error for HulahoopError {}
+
+/// The error returned by [insert_if_balanced](crate::HashRing::insert_if_balanced) if inserting a node would
+/// worsen the balance of the ring beyond the threshold.
+///
+/// The rejected node can be recovered with [into_node](RejectedInsert::into_node).
+#[derive(Debug, Clone, PartialEq)]
+pub struct RejectedInsert<N> {
+    pub(crate) node: N,
+    pub(crate) balance_factor: f64,
+}
+
+impl<N> RejectedInsert<N> {
+    /// Returns the balance factor the ring would have had with the node inserted.
+    pub fn balance_factor(&self) -> f64 {
+        self.balance_factor
+    }
+
+    /// Returns the rejected node.
+    pub fn into_node(self) -> N {
+        self.node
+    }
+}
+
+impl<N> Display for RejectedInsert<N> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "inserting the node would result in a balance factor of {}",
+            self.balance_factor
+        )
+    }
+}
+
+impl<N: Debug> Error for RejectedInsert<N> {}
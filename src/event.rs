@@ -0,0 +1,359 @@
+use crate::{HashRing, MasterNode};
+use std::collections::HashSet;
+use std::hash::{BuildHasher, Hash};
+use std::sync::Arc;
+
+/// A node before and after a batch of changes, with the positions it owned before.
+pub(crate) type NodeChange<N> = (
+    Option<Arc<MasterNode<N>>>,
+    Option<Arc<MasterNode<N>>>,
+    Vec<u64>,
+);
+
+/// A change of the nodes of a ring, reported to the [event callback](crate::HashRingBuilder::event_callback).
+///
+/// Every event carries the arcs `(start, end)` of the hash space whose keys are routed to a different node than before
+/// the change. Like in [ownership_map](HashRing::ownership_map), both `start` and `end` are inclusive, the arcs are
+/// in ascending order and adjacent arcs are coalesced.
+///
+/// # Examples
+///
+/// ```
+/// use hulahoop::RingEvent;
+///
+/// let node = "10.0.0.1:1234";
+/// let event = RingEvent::NodeAdded { node: &node, weight: 1, ranges: vec![(0, u64::MAX)] };
+/// assert_eq!(event.node(), Some(&"10.0.0.1:1234"));
+/// assert_eq!(event.ranges(), &[(0, u64::MAX)]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RingEvent<'a, N> {
+    /// A node was added to the ring. Keys in `ranges` are now routed to it.
+    NodeAdded {
+        /// The added node.
+        node: &'a N,
+        /// The actual weight of the node.
+        weight: u64,
+        /// The arcs of the hash space now routed to the node.
+        ranges: Vec<(u64, u64)>,
+    },
+    /// A node was removed from the ring. Keys in `ranges` were routed to it before.
+    NodeRemoved {
+        /// The removed node.
+        node: &'a N,
+        /// The actual weight the node had.
+        weight: u64,
+        /// The arcs of the hash space routed to the node before.
+        ranges: Vec<(u64, u64)>,
+    },
+    /// The weight of a node changed. Keys in `ranges` moved to or away from it.
+    WeightChanged {
+        /// The node whose weight changed.
+        node: &'a N,
+        /// The previous actual weight of the node.
+        old_weight: u64,
+        /// The new actual weight of the node.
+        new_weight: u64,
+        /// The arcs of the hash space moved to or away from the node.
+        ranges: Vec<(u64, u64)>,
+    },
+    /// Several changes were applied at once by [apply](HashRing::apply),
+    /// [rebuild_with](HashRing::rebuild_with) or [deduplicate_positions](HashRing::deduplicate_positions).
+    Batch {
+        /// The changes of the individual nodes, in the order of their [ids](crate::NodeId).
+        /// A node inserted again is reported as `WeightChanged`.
+        events: Vec<RingEvent<'a, N>>,
+        /// The arcs of the hash space routed to a different node than before all changes.
+        ranges: Vec<(u64, u64)>,
+    },
+}
+
+impl<'a, N> RingEvent<'a, N> {
+    /// Returns a reference to the node the event is about, or `None` for a [Batch](RingEvent::Batch) of changes.
+    pub fn node(&self) -> Option<&'a N> {
+        match self {
+            RingEvent::NodeAdded { node, .. }
+            | RingEvent::NodeRemoved { node, .. }
+            | RingEvent::WeightChanged { node, .. } => Some(node),
+            RingEvent::Batch { .. } => None,
+        }
+    }
+
+    /// Returns the arcs of the hash space whose keys are routed to a different node than before the event.
+    pub fn ranges(&self) -> &[(u64, u64)] {
+        match self {
+            RingEvent::NodeAdded { ranges, .. }
+            | RingEvent::NodeRemoved { ranges, .. }
+            | RingEvent::WeightChanged { ranges, .. }
+            | RingEvent::Batch { ranges, .. } => ranges,
+        }
+    }
+}
+
+impl<N, B> HashRing<N, B>
+where
+    N: Hash,
+    B: BuildHasher,
+{
+    /// Returns the positions currently owned by the master node.
+    pub(crate) fn owned_positions(&self, master_node: &Arc<MasterNode<N>>) -> Vec<u64> {
+        self.master_node_positions(master_node)
+            .into_iter()
+            .filter(|position| self.is_owned_by(position, master_node))
+            .collect()
+    }
+
+    /// Reports the node as added at `positions`, if an event callback is configured.
+    pub(crate) fn notify_added(&self, master_node: &MasterNode<N>, positions: Vec<u64>) {
+        if let Some(event_callback) = &self.event_callback {
+            event_callback(&RingEvent::NodeAdded {
+                node: &master_node.node,
+                weight: master_node.weight.get(),
+                ranges: self.changed_ranges(positions),
+            });
+        }
+    }
+
+    /// Reports the node as removed from `positions`, if an event callback is configured.
+    pub(crate) fn notify_removed(&self, master_node: &MasterNode<N>, positions: Vec<u64>) {
+        if let Some(event_callback) = &self.event_callback {
+            event_callback(&RingEvent::NodeRemoved {
+                node: &master_node.node,
+                weight: master_node.weight.get(),
+                ranges: self.changed_ranges(positions),
+            });
+        }
+    }
+
+    /// Reports the weight of the node as changed, if an event callback is configured and anything changed.
+    pub(crate) fn notify_weight_changed(
+        &self,
+        master_node: &MasterNode<N>,
+        old_weight: u64,
+        positions: Vec<u64>,
+    ) {
+        let new_weight = master_node.weight.get();
+        if positions.is_empty() && old_weight == new_weight {
+            return;
+        }
+        if let Some(event_callback) = &self.event_callback {
+            event_callback(&RingEvent::WeightChanged {
+                node: &master_node.node,
+                old_weight,
+                new_weight,
+                ranges: self.changed_ranges(positions),
+            });
+        }
+    }
+
+    /// Reports the changes of the individual nodes as a single batch, if an event callback is configured
+    /// and anything changed.
+    pub(crate) fn notify_batch(&self, changes: Vec<NodeChange<N>>) {
+        let Some(event_callback) = &self.event_callback else {
+            return;
+        };
+        let mut events = Vec::new();
+        for (old, new, old_positions) in &changes {
+            let event = match (old, new) {
+                (None, Some(new)) => RingEvent::NodeAdded {
+                    node: &new.node,
+                    weight: new.weight.get(),
+                    ranges: self.changed_ranges(self.owned_positions(new)),
+                },
+                (Some(old), None) => RingEvent::NodeRemoved {
+                    node: &old.node,
+                    weight: old.weight.get(),
+                    ranges: self.changed_ranges(old_positions.clone()),
+                },
+                (Some(old), Some(new)) => {
+                    let old_positions = old_positions.iter().copied().collect::<HashSet<_>>();
+                    let new_positions = self.owned_positions(new).into_iter().collect();
+                    let positions = old_positions
+                        .symmetric_difference(&new_positions)
+                        .copied()
+                        .collect::<Vec<_>>();
+                    if positions.is_empty() && old.weight == new.weight {
+                        continue;
+                    }
+                    RingEvent::WeightChanged {
+                        node: &new.node,
+                        old_weight: old.weight.get(),
+                        new_weight: new.weight.get(),
+                        ranges: self.changed_ranges(positions),
+                    }
+                }
+                (None, None) => continue,
+            };
+            events.push(event);
+        }
+        if events.is_empty() {
+            return;
+        }
+        let ranges = coalesce(
+            events
+                .iter()
+                .flat_map(|event| event.ranges().iter().copied())
+                .collect(),
+        );
+        event_callback(&RingEvent::Batch { events, ranges });
+    }
+
+    /// Returns the arcs whose owner changed because virtual nodes were added or removed at `positions`,
+    /// computed on the ring after the change.
+    fn changed_ranges(&self, mut positions: Vec<u64>) -> Vec<(u64, u64)> {
+        positions.sort_unstable();
+        positions.dedup();
+        let mut ranges = Vec::with_capacity(positions.len());
+        for position in positions {
+            match self.virtual_nodes.range(..position).next_back() {
+                Some((previous, _)) => ranges.push((previous + 1, position)),
+                None => {
+                    // Keys beyond the last virtual node wrap around to the first one
+                    ranges.push((0, position));
+                    let last = self
+                        .virtual_nodes
+                        .keys()
+                        .next_back()
+                        .map_or(position, |last| position.max(*last));
+                    if let Some(start) = last.checked_add(1) {
+                        ranges.push((start, u64::MAX));
+                    }
+                }
+            }
+        }
+        coalesce(ranges)
+    }
+}
+
+/// Sorts the arcs and merges overlapping and adjacent ones.
+fn coalesce(mut ranges: Vec<(u64, u64)>) -> Vec<(u64, u64)> {
+    ranges.sort_unstable();
+    ranges.dedup_by(|next, previous| {
+        let overlapping = next.0 <= previous.1.saturating_add(1);
+        if overlapping {
+            previous.1 = previous.1.max(next.1);
+        }
+        overlapping
+    });
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{DefaultBuildHasher, HashRing, HashRingBuilder, RingEvent};
+    use std::sync::{Arc, Mutex};
+
+    type Recorded = Vec<(&'static str, &'static str, Vec<(u64, u64)>)>;
+
+    fn recording_ring() -> (
+        HashRing<&'static str, DefaultBuildHasher>,
+        Arc<Mutex<Recorded>>,
+    ) {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let recorded = events.clone();
+        let ring = HashRingBuilder::default()
+            .virtual_node_fn(|_, node: &&str, index| match (*node, index) {
+                ("a", index) => 100 * (index + 1),
+                ("b", index) => 100 * (index + 1) + 50,
+                ("d", index) => 200 + 1000 * index,
+                (_, index) => 1000 + index,
+            })
+            .event_callback(move |event: &RingEvent<'_, &'static str>| {
+                let kind = match event {
+                    RingEvent::NodeAdded { .. } => "added",
+                    RingEvent::NodeRemoved { .. } => "removed",
+                    RingEvent::WeightChanged { .. } => "weight_changed",
+                    RingEvent::Batch { .. } => "batch",
+                };
+                recorded.lock().unwrap().push((
+                    kind,
+                    event.node().copied().unwrap_or_default(),
+                    event.ranges().to_vec(),
+                ));
+            })
+            .build();
+        (ring, events)
+    }
+
+    #[test]
+    fn events_report_the_arcs_changing_their_owner() {
+        let (mut ring, events) = recording_ring();
+        ring.insert("a", 2);
+        ring.insert("b", 1);
+        ring.set_weight(&"b", 2);
+        ring.remove(&"a");
+        ring.insert("c", 1);
+        ring.clear();
+
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![
+                ("added", "a", vec![(0, u64::MAX)]),
+                ("added", "b", vec![(101, 150)]),
+                ("weight_changed", "b", vec![(201, 250)]),
+                ("removed", "a", vec![(0, 100), (151, 200), (251, u64::MAX)]),
+                ("added", "c", vec![(251, 1000)]),
+                ("removed", "b", vec![(0, u64::MAX)]),
+                ("removed", "c", vec![(0, u64::MAX)]),
+            ]
+        );
+    }
+
+    #[test]
+    fn batch_changes_report_events() {
+        let (mut ring, events) = recording_ring();
+        ring.insert_many([("a", 1), ("b", 1)]);
+        ring.retain(|node, _| *node == "b");
+
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![
+                ("added", "a", vec![(0, 100), (151, u64::MAX)]),
+                ("added", "b", vec![(101, 150)]),
+                ("removed", "a", vec![(0, 100), (151, u64::MAX)]),
+            ]
+        );
+    }
+
+    #[test]
+    fn deduplicating_positions_reports_a_single_batch() {
+        let (mut ring, events) = recording_ring();
+        ring.insert("d", 2);
+        // "a" takes over the first position of "d", which has the lower hash
+        ring.insert("a", 2);
+        let before = ring.clone();
+        events.lock().unwrap().clear();
+
+        assert_eq!(ring.deduplicate_positions(), 1);
+        let events = events.lock().unwrap();
+        assert_eq!(events.len(), 1);
+        let (kind, _, ranges) = &events[0];
+        assert_eq!(*kind, "batch");
+        assert!(!ranges.is_empty());
+        for hash in (0..2_000).chain([u64::MAX]) {
+            if ring.get_by_hash(hash) != before.get_by_hash(hash) {
+                assert!(ranges
+                    .iter()
+                    .any(|(start, end)| (*start..=*end).contains(&hash)));
+            }
+        }
+    }
+
+    #[test]
+    fn rebuilding_reports_a_single_batch() {
+        let (mut ring, events) = recording_ring();
+        ring.insert("a", 1);
+        ring.insert("b", 1);
+        events.lock().unwrap().clear();
+
+        let ring = ring.rebuild_with(|_: &_, node: &&str, _| match *node {
+            "a" => 100,
+            _ => 300,
+        });
+        assert_eq!(ring.len(), 2);
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![("batch", "", vec![(101, 300)])]
+        );
+    }
+}
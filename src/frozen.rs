@@ -0,0 +1,270 @@
+use crate::{HashRing, KeyHash, MasterNode};
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hash};
+use std::sync::Arc;
+
+/// An immutable ring laid out for fast lookups.
+///
+/// The positions of all virtual nodes are stored in a sorted array, next to an array of compact indices into
+/// the nodes, which is searched without branching on the comparisons. This avoids the pointer chasing of the
+/// `BTreeMap` of a [HashRing](HashRing) and routes every key to the same node as the ring it was frozen from.
+///
+/// This `struct` is created by the [freeze](HashRing::freeze) method on [HashRing](HashRing).
+/// See its documentation for more.
+#[derive(Debug, Clone)]
+pub struct FrozenHashRing<N, B> {
+    pub(crate) positions: Vec<u64>,
+    // The index into `nodes` of the node of every position
+    pub(crate) owners: Vec<u32>,
+    nodes: Vec<Arc<MasterNode<N>>>,
+    hash_builder: B,
+}
+
+impl<N, B> HashRing<N, B> {
+    /// Converts the ring into a [FrozenHashRing](FrozenHashRing) optimized for lookups.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut ring: HashRing<&str, _> = HashRing::default();
+    /// ring.insert("10.0.0.1:1234", 10);
+    /// ring.insert("10.0.0.2:1234", 10);
+    ///
+    /// let expected = ring.get("Some key").copied();
+    /// let frozen = ring.freeze();
+    /// assert_eq!(frozen.get("Some key").copied(), expected);
+    /// ```
+    pub fn freeze(self) -> FrozenHashRing<N, B> {
+        let mut indices = HashMap::new();
+        let mut nodes = Vec::new();
+        let (positions, owners) = self
+            .virtual_nodes
+            .into_iter()
+            .map(|(position, master_node)| {
+                let index = *indices.entry(Arc::as_ptr(&master_node)).or_insert_with(|| {
+                    nodes.push(master_node.clone());
+                    (nodes.len() - 1) as u32
+                });
+                (position, index)
+            })
+            .unzip();
+        FrozenHashRing {
+            positions,
+            owners,
+            nodes,
+            hash_builder: self.hash_builder,
+        }
+    }
+}
+
+impl<N, B> FrozenHashRing<N, B> {
+    /// Returns the number of nodes.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns `true` if the ring contains no nodes.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Returns the total number of virtual nodes.
+    pub fn total_virtual_nodes(&self) -> usize {
+        self.positions.len()
+    }
+
+    /// Returns a reference to the ring's `BuildHasher`.
+    pub fn hasher(&self) -> &B {
+        &self.hash_builder
+    }
+
+    /// An iterator visiting all node-weight pairs in arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item = (&N, u64)> {
+        self.nodes
+            .iter()
+            .map(|master_node| (&master_node.node, master_node.weight.get()))
+    }
+
+    /// Returns a reference to the node of the already hashed key, like [HashRing::get_prehashed](HashRing::get_prehashed).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut ring: HashRing<&str, _> = HashRing::default();
+    /// ring.insert("10.0.0.1:1234", 10);
+    ///
+    /// let key_hash = ring.hash_key("Some key");
+    /// assert_eq!(ring.freeze().get_prehashed(key_hash), Some(&"10.0.0.1:1234"));
+    /// ```
+    #[inline]
+    pub fn get_prehashed(&self, key_hash: KeyHash) -> Option<&N> {
+        self.get_by_hash(key_hash.get())
+    }
+
+    /// Returns references to the nodes of many already hashed keys, in the order of the keys.
+    ///
+    /// The hashes are sorted first, so every hash is searched for starting at the position found for the previous
+    /// one, galloping ahead in growing steps. For batches of a thousand hashes and more, this visits the positions
+    /// mostly in order and takes about half the time of looking up every hash on its own with
+    /// [get_prehashed](FrozenHashRing::get_prehashed). For a few hashes, sorting them costs more than it saves.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut ring: HashRing<&str, _> = HashRing::default();
+    /// ring.insert("10.0.0.1:1234", 10);
+    /// ring.insert("10.0.0.2:1234", 10);
+    ///
+    /// let key_hashes = (0..100).map(|key| ring.hash_key(key)).collect::<Vec<_>>();
+    /// let frozen = ring.freeze();
+    /// let nodes = frozen.get_many_prehashed(&key_hashes);
+    /// assert!(nodes.into_iter().zip(key_hashes).all(|(node, key_hash)| node == frozen.get_prehashed(key_hash)));
+    /// ```
+    pub fn get_many_prehashed(&self, key_hashes: &[KeyHash]) -> Vec<Option<&N>> {
+        let mut nodes = vec![None; key_hashes.len()];
+        if self.positions.is_empty() {
+            return nodes;
+        }
+        let mut order = (0..key_hashes.len()).collect::<Vec<_>>();
+        order.sort_unstable_by_key(|index| key_hashes[*index]);
+
+        // All positions before the cursor are less than the current hash
+        let mut cursor = 0;
+        for index in order {
+            let key_hash = key_hashes[index].get();
+            let mut step = 1;
+            while cursor + step <= self.positions.len()
+                && self.positions[cursor + step - 1] < key_hash
+            {
+                step *= 2;
+            }
+            let start = cursor + step / 2;
+            let end = (cursor + step).min(self.positions.len());
+            cursor =
+                start + self.positions[start..end].partition_point(|position| *position < key_hash);
+            let owner = self.owners.get(cursor).unwrap_or(&self.owners[0]);
+            nodes[index] = Some(&self.nodes[*owner as usize].node);
+        }
+        nodes
+    }
+
+    /// Returns a reference to the node owning the first position at or after the hash, wrapping around.
+    #[inline]
+    pub(crate) fn get_by_hash(&self, key_hash: u64) -> Option<&N> {
+        let index = successor_index(
+            self.positions.len(),
+            |index| self.positions[index],
+            key_hash,
+        )?;
+        Some(&self.nodes[self.owners[index] as usize].node)
+    }
+}
+
+/// Returns the index of the first of the sorted positions at or after the hash, wrapping around,
+/// or `None` if there are no positions.
+#[inline]
+pub(crate) fn successor_index<P>(len: usize, position: P, key_hash: u64) -> Option<usize>
+where
+    P: Fn(usize) -> u64,
+{
+    let mut base = 0;
+    let mut size = len;
+    if size == 0 {
+        return None;
+    }
+    // The first position not less than the hash is within `base..=base + size`
+    while size > 1 {
+        let half = size / 2;
+        let mid = base + half;
+        base = if position(mid) < key_hash { mid } else { base };
+        size -= half;
+    }
+    let index = base + usize::from(position(base) < key_hash);
+    Some(if index == len { 0 } else { index })
+}
+
+impl<N, B> FrozenHashRing<N, B>
+where
+    B: BuildHasher,
+{
+    /// Returns a reference to the node of the key, like [HashRing::get](HashRing::get).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut ring: HashRing<&str, _> = HashRing::default();
+    /// assert_eq!(ring.clone().freeze().get("Some key"), None);
+    ///
+    /// ring.insert("10.0.0.1:1234", 10);
+    /// assert_eq!(ring.freeze().get("Some key"), Some(&"10.0.0.1:1234"));
+    /// ```
+    #[inline]
+    pub fn get<K>(&self, key: K) -> Option<&N>
+    where
+        K: Hash,
+    {
+        self.get_by_hash(self.hash_builder.hash_one(key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{HashRing, KeyHash};
+
+    #[test]
+    fn frozen_ring_routes_like_the_ring() {
+        let mut ring: HashRing<u32, _> = HashRing::new();
+        for node in 0..20 {
+            ring.insert(node, u64::from(node % 5 + 1) * 10);
+        }
+        let frozen = ring.clone().freeze();
+        assert_eq!(frozen.len(), ring.len());
+        assert_eq!(frozen.total_virtual_nodes(), ring.virtual_nodes.len());
+
+        let mut hashes = vec![0, u64::MAX];
+        for position in ring.virtual_nodes.keys() {
+            hashes.extend([
+                position.wrapping_sub(1),
+                *position,
+                position.wrapping_add(1),
+            ]);
+        }
+        for hash in hashes {
+            assert_eq!(frozen.get_by_hash(hash), ring.get_by_hash(hash), "{hash}");
+        }
+    }
+
+    #[test]
+    fn batched_lookups_match_single_lookups() {
+        let mut ring: HashRing<u32, _> = HashRing::new();
+        for node in 0..20 {
+            ring.insert(node, 10);
+        }
+        let mut key_hashes = (0..1_000).map(|key| ring.hash_key(key)).collect::<Vec<_>>();
+        let positions = ring.virtual_nodes.keys().copied().collect::<Vec<_>>();
+        key_hashes.extend(
+            [0, u64::MAX, positions[0], positions[positions.len() - 1]]
+                .into_iter()
+                .map(KeyHash::new),
+        );
+        key_hashes.extend(key_hashes.clone());
+
+        let frozen = ring.freeze();
+        let nodes = frozen.get_many_prehashed(&key_hashes);
+        for (node, key_hash) in nodes.into_iter().zip(key_hashes) {
+            assert_eq!(node, frozen.get_prehashed(key_hash));
+        }
+        assert!(HashRing::<u32, _>::new()
+            .freeze()
+            .get_many_prehashed(&[KeyHash::new(0)])
+            .eq(&[None]));
+    }
+}
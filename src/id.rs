@@ -0,0 +1,258 @@
+use crate::{HashRing, MasterNode};
+use std::hash::{BuildHasher, Hash};
+use std::sync::Arc;
+
+/// A handle to a node of a [HashRing](HashRing), allowing to look up, update and remove it without hashing the node.
+///
+/// The id is the position of the first virtual node of the node, which identifies the node within the ring.
+/// It is stable as long as the node is part of the ring, reinserting the node yields the same id again.
+/// Once the node is removed or replaced by a colliding node, the id no longer refers to any node.
+///
+/// # Examples
+///
+/// ```
+/// use hulahoop::HashRing;
+///
+/// let mut ring: HashRing<&str, _> = HashRing::default();
+///
+/// let id = ring.insert_with_id("10.0.0.1:1234", 10).unwrap();
+/// assert_eq!(ring.node_id(&"10.0.0.1:1234"), Some(id));
+/// assert_eq!(ring.get_by_id(id), Some(&"10.0.0.1:1234"));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NodeId(pub(crate) u64);
+
+impl<N, B> HashRing<N, B>
+where
+    N: Hash,
+    B: BuildHasher,
+{
+    /// Inserts a node like [insert](HashRing::insert), returning its id.
+    ///
+    /// Returns `None` if the node was not inserted because `weight` is 0 or the total virtual node budget is exhausted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut ring: HashRing<&str, _> = HashRing::default();
+    ///
+    /// assert!(ring.insert_with_id("10.0.0.1:1234", 10).is_some());
+    /// assert_eq!(ring.insert_with_id("10.0.0.2:1234", 0), None);
+    /// ```
+    pub fn insert_with_id(&mut self, node: N, weight: u64) -> Option<NodeId> {
+        let id = NodeId(self.virtual_node_hash(&node, 0));
+        self.insert(node, weight);
+        self.get_master_node_by_id(id).map(|_| id)
+    }
+
+    /// Returns the id of the node, or `None` if the node is not part of the ring or a virtual node of another node
+    /// took over the position identifying it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut ring: HashRing<&str, _> = HashRing::default();
+    /// assert_eq!(ring.node_id(&"10.0.0.1:1234"), None);
+    ///
+    /// ring.insert("10.0.0.1:1234", 10);
+    /// assert!(ring.node_id(&"10.0.0.1:1234").is_some());
+    /// ```
+    pub fn node_id(&self, node: &N) -> Option<NodeId> {
+        let id = NodeId(self.virtual_node_hash(node, 0));
+        self.get_master_node_by_id(id).map(|_| id)
+    }
+
+    /// Returns a reference to the node with the id, or `None` if no node has the id.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut ring: HashRing<&str, _> = HashRing::default();
+    ///
+    /// let id = ring.insert_with_id("10.0.0.1:1234", 10).unwrap();
+    /// assert_eq!(ring.get_by_id(id), Some(&"10.0.0.1:1234"));
+    ///
+    /// ring.remove(&"10.0.0.1:1234");
+    /// assert_eq!(ring.get_by_id(id), None);
+    /// ```
+    pub fn get_by_id(&self, id: NodeId) -> Option<&N> {
+        self.get_master_node_by_id(id)
+            .map(|master_node| &master_node.node)
+    }
+
+    /// Removes the node with the id, returning the number of its virtual nodes like [remove](HashRing::remove).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut ring: HashRing<&str, _> = HashRing::default();
+    ///
+    /// let id = ring.insert_with_id("10.0.0.1:1234", 10).unwrap();
+    /// assert_eq!(ring.remove_by_id(id), 10);
+    /// assert_eq!(ring.remove_by_id(id), 0);
+    /// ```
+    pub fn remove_by_id(&mut self, id: NodeId) -> u64 {
+        match self.get_master_node_by_id(id) {
+            Some(master_node) => self.remove_master_node(master_node.clone()).1,
+            None => 0,
+        }
+    }
+
+    /// Updates the weight of the node with the id like [set_weight](HashRing::set_weight),
+    /// returning its previous actual weight.
+    ///
+    /// Returns `None` if no node has the id. A `weight` of 0 removes the node.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut ring: HashRing<&str, _> = HashRing::default();
+    ///
+    /// let id = ring.insert_with_id("10.0.0.1:1234", 10).unwrap();
+    /// assert_eq!(ring.set_weight_by_id(id, 20), Some(10));
+    /// assert_eq!(ring.total_weight(), 20);
+    /// ```
+    pub fn set_weight_by_id(&mut self, id: NodeId, weight: u64) -> Option<u64>
+    where
+        N: Clone,
+    {
+        let master_node = self.get_master_node_by_id(id)?.clone();
+        let node = master_node.node.clone();
+        Some(self.update_master_node(master_node, node, weight))
+    }
+
+    /// Calls `f` with a mutable reference to the node with the id, returning its result.
+    ///
+    /// All virtual nodes share the node, so it can only be mutated while they are detached from it,
+    /// which takes time linear in the weight of the node. For data that changes often, like connection counters,
+    /// prefer interior mutability within the node instead.
+    /// `f` must not change the hash of the node, otherwise the node no longer matches its positions.
+    ///
+    /// Returns `None` if no node has the id or an [upgraded Weak](HashRing::weak_of) reference to it is alive.
+    /// The node is moved into a new allocation by the update, so `Weak` references obtained before no longer upgrade.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::{HashRing, Keyed};
+    ///
+    /// let mut ring: HashRing<Keyed<&str, u64>, _> = HashRing::default();
+    ///
+    /// let id = ring.insert_with_id(Keyed::new("10.0.0.1:1234", 0), 10).unwrap();
+    /// ring.update_by_id(id, |node| *node.value_mut() += 1);
+    /// assert_eq!(ring.get("Some key").map(Keyed::value), Some(&1));
+    /// assert_eq!(ring.update_by_id(id, |node| *node.value()), Some(1));
+    /// ```
+    pub fn update_by_id<F, R>(&mut self, id: NodeId, f: F) -> Option<R>
+    where
+        F: FnOnce(&mut N) -> R,
+    {
+        let master_node = self.get_master_node_by_id(id)?.clone();
+        let positions = self
+            .master_node_positions(&master_node)
+            .into_iter()
+            .filter(|position| self.is_owned_by(position, &master_node))
+            .collect::<Vec<_>>();
+        // Drop all references to the master node held by the virtual nodes, so the node can be mutated
+        for position in &positions {
+            self.virtual_nodes.remove(position);
+        }
+        self.unindex_master_node(&master_node);
+        let (master_node, result) = match Arc::try_unwrap(master_node) {
+            Ok(mut master_node) => {
+                let result = f(&mut master_node.node);
+                (Arc::new(master_node), Some(result))
+            }
+            Err(master_node) => (master_node, None),
+        };
+        debug_assert_eq!(
+            self.virtual_node_hash(&master_node.node, 0),
+            id.0,
+            "the hash of a node must not change"
+        );
+        for position in positions {
+            self.virtual_nodes.insert(position, master_node.clone());
+        }
+        self.index_master_node(&master_node);
+        if result.is_some() {
+            self.version += 1;
+        }
+        result
+    }
+
+    fn get_master_node_by_id(&self, id: NodeId) -> Option<&Arc<MasterNode<N>>> {
+        self.virtual_nodes
+            .get(&id.0)
+            // The position may be held by a virtual node of another node
+            .filter(|master_node| master_node.id == id.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{HashRing, Keyed};
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::BuildHasherDefault;
+
+    #[test]
+    fn id_does_not_refer_to_another_node_owning_its_position() {
+        let mut ring = HashRing::with_virtual_node_fn(
+            BuildHasherDefault::<DefaultHasher>::default(),
+            |_, node: &&str, index| match (*node, index) {
+                ("a", _) => index,
+                (_, 0) => 10,
+                (_, _) => index - 1,
+            },
+        );
+        let a = ring.insert_with_id("a", 2).unwrap();
+        assert_eq!(ring.get_by_id(a), Some(&"a"));
+        assert_eq!(ring.node_id(&"a"), Some(a));
+
+        // The second virtual node of "b" takes over the first virtual node of "a"
+        let b = ring.insert_with_id("b", 2).unwrap();
+        assert_eq!(ring.get_by_id(b), Some(&"b"));
+        assert_eq!(ring.get_by_id(a), None);
+        assert_eq!(ring.node_id(&"a"), None);
+        assert_eq!(ring.node_id(&"b"), Some(b));
+        assert_eq!(ring.remove_by_id(a), 0);
+        assert_eq!(ring.set_weight_by_id(a, 1), None);
+        assert_eq!(ring.len(), 2);
+    }
+
+    #[test]
+    fn updating_a_node_fails_while_it_is_borrowed_elsewhere() {
+        let mut ring: HashRing<String, _> = HashRing::new();
+        let id = ring.insert_with_id("a".to_string(), 10).unwrap();
+        let master_node = ring.weak_of(&"a".to_string()).unwrap().upgrade();
+        assert_eq!(ring.update_by_id(id, |node| node.push('!')), None);
+
+        drop(master_node);
+        assert_eq!(ring.update_by_id(id, |node| node.len()), Some(1));
+        assert_eq!(ring.positions_of(&"a".to_string()).len(), 10);
+    }
+
+    #[test]
+    fn updating_a_node_invalidates_weak_references() {
+        let mut ring: HashRing<Keyed<&str, u64>, _> = HashRing::new();
+        let id = ring.insert_with_id(Keyed::new("a", 0), 10).unwrap();
+        let weak = ring.weak_of(&Keyed::new("a", 0)).unwrap();
+
+        assert_eq!(
+            ring.update_by_id(id, |node| *node.value_mut() += 1),
+            Some(())
+        );
+        assert!(weak.upgrade().is_none());
+        assert_eq!(ring.get_by_id(id).map(Keyed::value), Some(&1));
+        assert_eq!(ring.positions_of(&Keyed::new("a", 0)).len(), 10);
+    }
+}
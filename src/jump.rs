@@ -0,0 +1,233 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{BuildHasher, BuildHasherDefault, Hash};
+use std::num::NonZeroU32;
+
+/// Jump consistent hashing as described by Lamping and Veach in
+/// [A Fast, Minimal Memory, Consistent Hash Algorithm](https://arxiv.org/abs/1406.2294).
+///
+/// A key is mapped to one of `buckets` buckets in `O(ln buckets)` time without any memory.
+/// When the number of buckets grows from `n` to `n + 1`, only about `1 / (n + 1)` of the keys move, all to the new bucket.
+/// Buckets can only be added or removed at the end, see [JumpRing](JumpRing) for a node list built on top of it.
+///
+/// The buckets are the same as the ones of Guava's `Hashing.consistentHash(long, int)`.
+///
+/// # Examples
+///
+/// ```
+/// use std::num::NonZeroU32;
+/// use hulahoop::JumpHash;
+///
+/// let buckets = NonZeroU32::new(100).unwrap();
+/// assert_eq!(JumpHash::bucket(1, buckets), 55);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct JumpHash;
+
+impl JumpHash {
+    /// Returns the bucket in `0..buckets` of the key.
+    pub fn bucket(key: u64, buckets: NonZeroU32) -> u32 {
+        // The linear congruential generator used by Guava
+        let mut state = key;
+        let mut next_double = || {
+            state = state.wrapping_mul(2862933555777941757).wrapping_add(1);
+            ((state >> 33) + 1) as f64 / (1u64 << 31) as f64
+        };
+        let mut candidate = 0;
+        loop {
+            // The conversion saturates just like in Java
+            let next = ((candidate + 1) as f64 / next_double()) as i32;
+            if next >= 0 && (next as u32) < buckets.get() {
+                candidate = next as u32;
+            } else {
+                return candidate;
+            }
+        }
+    }
+}
+
+/// A list of nodes selected by [JumpHash](JumpHash), the node at index `i` being the bucket `i`.
+///
+/// Nodes can only be added to and removed from the end of the list, which is what jump consistent hashing
+/// keeps consistent. Nodes do not have a weight.
+///
+/// # Examples
+///
+/// ```
+/// use hulahoop::JumpRing;
+///
+/// let mut ring: JumpRing<&str, _> = JumpRing::new();
+///
+/// ring.push("10.0.0.1:1234");
+/// ring.push("10.0.0.2:1234");
+/// assert_eq!(ring.get_by_hash(1), Some(&"10.0.0.1:1234"));
+///
+/// assert_eq!(ring.pop(), Some("10.0.0.2:1234"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct JumpRing<N, B> {
+    nodes: Vec<N>,
+    hash_builder: B,
+}
+
+impl<N> JumpRing<N, BuildHasherDefault<DefaultHasher>> {
+    /// Creates a new `JumpRing` with the default hasher.
+    pub fn new() -> Self {
+        Self::with_hasher(BuildHasherDefault::default())
+    }
+}
+
+impl<N> Default for JumpRing<N, BuildHasherDefault<DefaultHasher>> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<N, B> JumpRing<N, B> {
+    /// Creates a new `JumpRing` which will use the given `hash_builder` to hash keys.
+    pub fn with_hasher(hash_builder: B) -> Self {
+        Self {
+            nodes: Vec::new(),
+            hash_builder,
+        }
+    }
+
+    /// Appends a node as the last bucket.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the ring already has `u32::MAX` nodes.
+    pub fn push(&mut self, node: N) {
+        assert!(
+            self.nodes.len() < u32::MAX as usize,
+            "the number of buckets must fit into a u32"
+        );
+        self.nodes.push(node);
+    }
+
+    /// Removes the last node, returning it or `None` if the ring is empty.
+    pub fn pop(&mut self) -> Option<N> {
+        self.nodes.pop()
+    }
+
+    /// Returns the nodes in bucket order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::JumpRing;
+    ///
+    /// let mut ring: JumpRing<&str, _> = JumpRing::new();
+    /// ring.push("10.0.0.1:1234");
+    /// assert_eq!(ring.nodes(), &["10.0.0.1:1234"]);
+    /// ```
+    pub fn nodes(&self) -> &[N] {
+        &self.nodes
+    }
+
+    /// Returns the number of nodes.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns `true` if the ring contains no nodes.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Returns a reference to the node of the bucket of an already hashed key, or `None` if the ring is empty.
+    ///
+    /// Use this to place keys like another implementation does, e.g. with the 64-bit hash code Guava passes to
+    /// `Hashing.consistentHash`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::JumpRing;
+    ///
+    /// let mut ring: JumpRing<u32, _> = JumpRing::new();
+    /// assert_eq!(ring.get_by_hash(1), None);
+    ///
+    /// ring.extend(0..100);
+    /// assert_eq!(ring.get_by_hash(1), Some(&55));
+    /// ```
+    pub fn get_by_hash(&self, key_hash: u64) -> Option<&N> {
+        let buckets = NonZeroU32::new(self.nodes.len() as u32)?;
+        self.nodes.get(JumpHash::bucket(key_hash, buckets) as usize)
+    }
+}
+
+impl<N, B> JumpRing<N, B>
+where
+    B: BuildHasher,
+{
+    /// Returns a reference to the node of the bucket of the key, or `None` if the ring is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::JumpRing;
+    ///
+    /// let mut ring: JumpRing<&str, _> = JumpRing::new();
+    /// assert_eq!(ring.get("Some key"), None);
+    ///
+    /// ring.push("10.0.0.1:1234");
+    /// assert_eq!(ring.get("Some key"), Some(&"10.0.0.1:1234"));
+    /// ```
+    pub fn get<K>(&self, key: K) -> Option<&N>
+    where
+        K: Hash,
+    {
+        self.get_by_hash(self.hash_builder.hash_one(key))
+    }
+}
+
+impl<N, B> Extend<N> for JumpRing<N, B> {
+    fn extend<T: IntoIterator<Item = N>>(&mut self, iter: T) {
+        for node in iter {
+            self.push(node);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{JumpHash, JumpRing};
+    use std::num::NonZeroU32;
+
+    fn bucket(key: u64, buckets: u32) -> u32 {
+        JumpHash::bucket(key, NonZeroU32::new(buckets).unwrap())
+    }
+
+    #[test]
+    fn buckets_match_guava() {
+        let golden_100 = [
+            0, 55, 62, 8, 45, 59, 86, 97, 82, 59, 73, 37, 17, 56, 86, 21, 90, 37, 38, 83,
+        ];
+        for (key, expected) in golden_100.into_iter().enumerate() {
+            assert_eq!(bucket(key as u64, 100), expected);
+        }
+        assert_eq!(bucket(10863919174838991, 11), 6);
+        assert_eq!(bucket(2016238256797177309, 11), 3);
+        assert_eq!(bucket(1673758223894951030, 11), 5);
+        assert_eq!(bucket(2, 100001), 80343);
+        assert_eq!(bucket(2201, 100001), 22152);
+        assert_eq!(bucket(2202, 100001), 15018);
+    }
+
+    #[test]
+    fn keys_only_move_to_a_pushed_node() {
+        let mut ring: JumpRing<u32, _> = JumpRing::new();
+        ring.extend(0..10);
+        let before = (0..1_000)
+            .map(|key| *ring.get(key).unwrap())
+            .collect::<Vec<_>>();
+
+        ring.push(10);
+        let moved = (0..1_000)
+            .zip(before)
+            .filter(|(key, node)| ring.get(key) != Some(node))
+            .inspect(|(key, _)| assert_eq!(ring.get(key), Some(&10)))
+            .count();
+        assert!((50..150).contains(&moved), "{moved}");
+    }
+}
@@ -0,0 +1,293 @@
+//! A consistent hash ring compatible with [libketama](https://github.com/RJ/ketama).
+
+use crate::HashRing;
+use std::collections::hash_map::DefaultHasher;
+use std::fmt::{Debug, Display};
+use std::hash::{BuildHasherDefault, Hash};
+
+/// The number of MD5 digests libketama computes per node when all nodes have the same weight.
+///
+/// Every digest yields four points on the continuum, resulting in 160 points per node.
+pub const KETAMA_DEFAULT_WEIGHT: u64 = 40;
+
+const POINTS_PER_DIGEST: u64 = 4;
+
+/// A hash ring producing the same node selection as libketama.
+///
+/// The `i`-th digest of a node is the MD5 hash of `"<node>-<i>"`, using the `Display` representation of the node.
+/// Each digest is split into four little-endian 32-bit points on the continuum.
+/// Keys are hashed with MD5 as well, using the first four bytes of the digest.
+///
+/// # Examples
+///
+/// ```
+/// use hulahoop::ketama::{KetamaRing, KETAMA_DEFAULT_WEIGHT};
+///
+/// let mut ring: KetamaRing<&str> = KetamaRing::new();
+///
+/// ring.insert("10.0.1.1:11211", KETAMA_DEFAULT_WEIGHT);
+/// ring.insert("10.0.1.2:11211", KETAMA_DEFAULT_WEIGHT);
+/// ring.insert("10.0.1.3:11211", KETAMA_DEFAULT_WEIGHT);
+///
+/// assert_eq!(ring.get("foo"), Some(&"10.0.1.2:11211"));
+/// ```
+pub struct KetamaRing<N> {
+    ring: HashRing<N, BuildHasherDefault<DefaultHasher>>,
+}
+
+impl<N> Debug for KetamaRing<N>
+where
+    N: Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KetamaRing")
+            .field("ring", &self.ring)
+            .finish()
+    }
+}
+
+impl<N> Default for KetamaRing<N>
+where
+    N: Display + Hash,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<N> KetamaRing<N>
+where
+    N: Display + Hash,
+{
+    /// Creates an empty `KetamaRing`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::ketama::KetamaRing;
+    ///
+    /// let ring: KetamaRing<&str> = KetamaRing::new();
+    /// assert!(ring.is_empty());
+    /// ```
+    pub fn new() -> Self {
+        Self {
+            ring: HashRing::with_virtual_node_fn(
+                BuildHasherDefault::default(),
+                |_, node: &N, virtual_node_identifier| {
+                    let digest =
+                        md5(
+                            format!("{}-{}", node, virtual_node_identifier / POINTS_PER_DIGEST)
+                                .as_bytes(),
+                        );
+                    let offset = (virtual_node_identifier % POINTS_PER_DIGEST) as usize * 4;
+                    point(&digest[offset..offset + 4])
+                },
+            ),
+        }
+    }
+
+    /// Inserts a node to the `KetamaRing`.
+    ///
+    /// The `weight` is the number of MD5 digests computed for the node, each adding four points to the continuum.
+    /// libketama uses [KETAMA_DEFAULT_WEIGHT](KETAMA_DEFAULT_WEIGHT) digests per node if all nodes have the same weight,
+    /// and `floor(node_weight / total_weight * 40 * number_of_nodes)` digests otherwise.
+    ///
+    /// If the ring did have this node present, the old node is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::ketama::{KetamaRing, KETAMA_DEFAULT_WEIGHT};
+    ///
+    /// let mut ring: KetamaRing<&str> = KetamaRing::new();
+    ///
+    /// assert_eq!(ring.insert("10.0.1.1:11211", KETAMA_DEFAULT_WEIGHT), None);
+    /// assert_eq!(ring.insert("10.0.1.1:11211", KETAMA_DEFAULT_WEIGHT), Some("10.0.1.1:11211"));
+    /// ```
+    pub fn insert(&mut self, node: N, weight: u64) -> Option<N> {
+        self.ring
+            .insert(node, weight.saturating_mul(POINTS_PER_DIGEST))
+    }
+
+    /// Returns a reference to the node libketama would select for the key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::ketama::{KetamaRing, KETAMA_DEFAULT_WEIGHT};
+    ///
+    /// let mut ring: KetamaRing<&str> = KetamaRing::new();
+    ///
+    /// ring.insert("10.0.1.1:11211", KETAMA_DEFAULT_WEIGHT);
+    /// assert_eq!(ring.get("Some key"), Some(&"10.0.1.1:11211"));
+    /// assert_eq!(ring.get(b"Some key"), Some(&"10.0.1.1:11211"));
+    /// ```
+    #[inline]
+    pub fn get<K>(&self, key: K) -> Option<&N>
+    where
+        K: AsRef<[u8]>,
+    {
+        self.ring.get_by_hash(point(&md5(key.as_ref())[..4]))
+    }
+
+    /// Removes a node from the `KetamaRing`, returning the number of points of the removed node.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::ketama::{KetamaRing, KETAMA_DEFAULT_WEIGHT};
+    ///
+    /// let mut ring: KetamaRing<&str> = KetamaRing::new();
+    ///
+    /// ring.insert("10.0.1.1:11211", KETAMA_DEFAULT_WEIGHT);
+    /// assert_eq!(ring.remove(&"10.0.1.1:11211"), 160);
+    /// assert_eq!(ring.remove(&"10.0.1.1:11211"), 0);
+    /// ```
+    pub fn remove(&mut self, node: &N) -> u64 {
+        self.ring.remove(node)
+    }
+
+    /// Returns the number of nodes in the `KetamaRing`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::ketama::{KetamaRing, KETAMA_DEFAULT_WEIGHT};
+    ///
+    /// let mut ring: KetamaRing<&str> = KetamaRing::new();
+    ///
+    /// ring.insert("10.0.1.1:11211", KETAMA_DEFAULT_WEIGHT);
+    /// assert_eq!(ring.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.ring.len()
+    }
+
+    /// Returns `true` if the ring contains no nodes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::ketama::KetamaRing;
+    ///
+    /// let ring: KetamaRing<&str> = KetamaRing::new();
+    /// assert!(ring.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.ring.is_empty()
+    }
+}
+
+fn point(bytes: &[u8]) -> u64 {
+    u64::from(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+const S: [u32; 64] = [
+    7, 12, 17, 22, 7, 12, 17, 22, 7, 12, 17, 22, 7, 12, 17, 22, 5, 9, 14, 20, 5, 9, 14, 20, 5, 9,
+    14, 20, 5, 9, 14, 20, 4, 11, 16, 23, 4, 11, 16, 23, 4, 11, 16, 23, 4, 11, 16, 23, 6, 10, 15,
+    21, 6, 10, 15, 21, 6, 10, 15, 21, 6, 10, 15, 21,
+];
+
+const K: [u32; 64] = [
+    0xd76aa478, 0xe8c7b756, 0x242070db, 0xc1bdceee, 0xf57c0faf, 0x4787c62a, 0xa8304613, 0xfd469501,
+    0x698098d8, 0x8b44f7af, 0xffff5bb1, 0x895cd7be, 0x6b901122, 0xfd987193, 0xa679438e, 0x49b40821,
+    0xf61e2562, 0xc040b340, 0x265e5a51, 0xe9b6c7aa, 0xd62f105d, 0x02441453, 0xd8a1e681, 0xe7d3fbc8,
+    0x21e1cde6, 0xc33707d6, 0xf4d50d87, 0x455a14ed, 0xa9e3e905, 0xfcefa3f8, 0x676f02d9, 0x8d2a4c8a,
+    0xfffa3942, 0x8771f681, 0x6d9d6122, 0xfde5380c, 0xa4beea44, 0x4bdecfa9, 0xf6bb4b60, 0xbebfbc70,
+    0x289b7ec6, 0xeaa127fa, 0xd4ef3085, 0x04881d05, 0xd9d4d039, 0xe6db99e5, 0x1fa27cf8, 0xc4ac5665,
+    0xf4292244, 0x432aff97, 0xab9423a7, 0xfc93a039, 0x655b59c3, 0x8f0ccc92, 0xffeff47d, 0x85845dd1,
+    0x6fa87e4f, 0xfe2ce6e0, 0xa3014314, 0x4e0811a1, 0xf7537e82, 0xbd3af235, 0x2ad7d2bb, 0xeb86d391,
+];
+
+/// Computes the MD5 digest of `input` as used by libketama.
+fn md5(input: &[u8]) -> [u8; 16] {
+    let mut state: [u32; 4] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476];
+
+    let mut message = input.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&((input.len() as u64).wrapping_mul(8)).to_le_bytes());
+
+    for chunk in message.chunks_exact(64) {
+        let mut words = [0u32; 16];
+        for (word, bytes) in words.iter_mut().zip(chunk.chunks_exact(4)) {
+            *word = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+        }
+        let [mut a, mut b, mut c, mut d] = state;
+        for i in 0..64 {
+            let (f, g) = match i / 16 {
+                0 => ((b & c) | (!b & d), i),
+                1 => ((d & b) | (!d & c), (5 * i + 1) % 16),
+                2 => (b ^ c ^ d, (3 * i + 5) % 16),
+                _ => (c ^ (b | !d), (7 * i) % 16),
+            };
+            let rotated = a
+                .wrapping_add(f)
+                .wrapping_add(K[i])
+                .wrapping_add(words[g])
+                .rotate_left(S[i]);
+            a = d;
+            d = c;
+            c = b;
+            b = b.wrapping_add(rotated);
+        }
+        state[0] = state[0].wrapping_add(a);
+        state[1] = state[1].wrapping_add(b);
+        state[2] = state[2].wrapping_add(c);
+        state[3] = state[3].wrapping_add(d);
+    }
+
+    let mut digest = [0u8; 16];
+    for (bytes, word) in digest.chunks_exact_mut(4).zip(state.iter()) {
+        bytes.copy_from_slice(&word.to_le_bytes());
+    }
+    digest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(digest: [u8; 16]) -> String {
+        digest.iter().map(|byte| format!("{byte:02x}")).collect()
+    }
+
+    #[test]
+    fn md5_matches_reference_digests() {
+        assert_eq!(hex(md5(b"")), "d41d8cd98f00b204e9800998ecf8427e");
+        assert_eq!(hex(md5(b"a")), "0cc175b9c0f1b6a831c399e269772661");
+        assert_eq!(hex(md5(b"abc")), "900150983cd24fb0d6963f7d28e17f72");
+        assert_eq!(
+            hex(md5(b"message digest")),
+            "f96b697d7cb7938d525a2f31aaf161d0"
+        );
+        assert_eq!(hex(md5(&[b'a'; 64])), "014842d480b571495a4a0363793f7367");
+        assert_eq!(hex(md5(&[b'a'; 100])), "36a92cc94a9e0fa21f625f8bfb007adf");
+    }
+
+    #[test]
+    fn node_selection_matches_libketama() {
+        let mut ring: KetamaRing<&str> = KetamaRing::new();
+        ring.insert("10.0.1.1:11211", KETAMA_DEFAULT_WEIGHT);
+        ring.insert("10.0.1.2:11211", KETAMA_DEFAULT_WEIGHT);
+        ring.insert("10.0.1.3:11211", KETAMA_DEFAULT_WEIGHT);
+
+        assert_eq!(ring.ring.virtual_nodes.len(), 480);
+        assert_eq!(ring.get("foo"), Some(&"10.0.1.2:11211"));
+        assert_eq!(ring.get("bar"), Some(&"10.0.1.1:11211"));
+        assert_eq!(ring.get("baz"), Some(&"10.0.1.2:11211"));
+        assert_eq!(ring.get("hulahoop"), Some(&"10.0.1.2:11211"));
+        assert_eq!(ring.get("memcached"), Some(&"10.0.1.3:11211"));
+        assert_eq!(ring.get("some_key_42"), Some(&"10.0.1.2:11211"));
+
+        assert_eq!(ring.remove(&"10.0.1.2:11211"), 160);
+        assert_eq!(ring.get("foo"), Some(&"10.0.1.1:11211"));
+        assert_eq!(ring.get("bar"), Some(&"10.0.1.1:11211"));
+        assert_eq!(ring.get("baz"), Some(&"10.0.1.1:11211"));
+        assert_eq!(ring.get("hulahoop"), Some(&"10.0.1.1:11211"));
+        assert_eq!(ring.get("memcached"), Some(&"10.0.1.3:11211"));
+        assert_eq!(ring.get("some_key_42"), Some(&"10.0.1.1:11211"));
+    }
+}
@@ -0,0 +1,88 @@
+use crate::HashRing;
+use std::hash::{BuildHasher, Hash};
+
+/// The hash of a key, allowing to look up the same key repeatedly without hashing it again.
+///
+/// A `KeyHash` only depends on the key and the `BuildHasher` of the ring, so it stays valid across changes of the
+/// ring and can be used with every ring using an equal `BuildHasher`. It can be sent over the wire as its `u64` value.
+///
+/// # Examples
+///
+/// ```
+/// use hulahoop::{HashRing, KeyHash};
+///
+/// let mut ring: HashRing<&str, _> = HashRing::default();
+/// ring.insert("10.0.0.1:1234", 10);
+///
+/// let key_hash = ring.hash_key("Some key");
+/// let received = KeyHash::new(key_hash.get());
+/// assert_eq!(ring.get_prehashed(received), ring.get("Some key"));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct KeyHash(u64);
+
+impl KeyHash {
+    /// Creates a `KeyHash` from a hash computed by the `BuildHasher` of a ring.
+    pub fn new(hash: u64) -> Self {
+        Self(hash)
+    }
+
+    /// Returns the hash as a primitive type.
+    pub fn get(self) -> u64 {
+        self.0
+    }
+}
+
+impl From<u64> for KeyHash {
+    fn from(hash: u64) -> Self {
+        Self(hash)
+    }
+}
+
+impl From<KeyHash> for u64 {
+    fn from(key_hash: KeyHash) -> Self {
+        key_hash.0
+    }
+}
+
+impl<N, B> HashRing<N, B>
+where
+    N: Hash,
+    B: BuildHasher,
+{
+    /// Returns the hash of the key, to be used with [get_prehashed](HashRing::get_prehashed).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::hash::BuildHasher;
+    /// use hulahoop::HashRing;
+    ///
+    /// let ring: HashRing<&str, _> = HashRing::default();
+    /// assert_eq!(ring.hash_key("Some key").get(), ring.hasher().hash_one("Some key"));
+    /// ```
+    pub fn hash_key<K>(&self, key: K) -> KeyHash
+    where
+        K: Hash,
+    {
+        KeyHash(self.hash_builder.hash_one(key))
+    }
+
+    /// Returns a reference to the node of the already hashed key, like [get](HashRing::get).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut ring: HashRing<&str, _> = HashRing::default();
+    /// let key_hash = ring.hash_key("Some key");
+    /// assert_eq!(ring.get_prehashed(key_hash), None);
+    ///
+    /// ring.insert("10.0.0.1:1234", 10);
+    /// assert_eq!(ring.get_prehashed(key_hash), Some(&"10.0.0.1:1234"));
+    /// ```
+    pub fn get_prehashed(&self, key_hash: KeyHash) -> Option<&N> {
+        self.get_by_hash(key_hash.0)
+    }
+}
@@ -0,0 +1,103 @@
+use crate::HashRing;
+use std::borrow::Borrow;
+use std::hash::{BuildHasher, Hash, Hasher};
+
+/// A node consisting of an identity `key`, which alone determines its positions, and an arbitrary `value`.
+///
+/// Only the key is hashed and compared, so the value can change without moving any virtual node,
+/// see [replace](HashRing::replace). As a `Keyed` node borrows as its key, it can be looked up and removed by the key.
+///
+/// # Examples
+///
+/// ```
+/// use hulahoop::{HashRing, Keyed};
+///
+/// let mut ring: HashRing<Keyed<&str, String>, _> = HashRing::default();
+///
+/// ring.insert_with_key("node-1", "10.0.0.1:1234".to_string(), 10);
+/// assert_eq!(ring.get("Some key").map(Keyed::value), Some(&"10.0.0.1:1234".to_string()));
+///
+/// ring.replace(&Keyed::new("node-1", String::new()), Keyed::new("node-1", "10.0.0.2:1234".to_string()))
+///     .expect("the positions of the node do not change");
+/// assert_eq!(ring.get("Some key").map(Keyed::value), Some(&"10.0.0.2:1234".to_string()));
+///
+/// assert_eq!(ring.remove(&"node-1"), 10);
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Keyed<K, V> {
+    key: K,
+    value: V,
+}
+
+impl<K, V> Keyed<K, V> {
+    /// Creates a node with the given identity and value.
+    pub fn new(key: K, value: V) -> Self {
+        Self { key, value }
+    }
+
+    /// Returns a reference to the identity of the node.
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    /// Returns a reference to the value of the node.
+    pub fn value(&self) -> &V {
+        &self.value
+    }
+
+    /// Returns a mutable reference to the value of the node.
+    ///
+    /// The value is not hashed, so it can be changed without affecting the positions of the node,
+    /// see [update_by_id](HashRing::update_by_id).
+    pub fn value_mut(&mut self) -> &mut V {
+        &mut self.value
+    }
+
+    /// Takes ownership of the identity and the value of the node.
+    pub fn into_parts(self) -> (K, V) {
+        (self.key, self.value)
+    }
+}
+
+impl<K: Hash, V> Hash for Keyed<K, V> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key.hash(state);
+    }
+}
+
+impl<K: PartialEq, V> PartialEq for Keyed<K, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key
+    }
+}
+
+impl<K: Eq, V> Eq for Keyed<K, V> {}
+
+impl<K, V> Borrow<K> for Keyed<K, V> {
+    fn borrow(&self) -> &K {
+        &self.key
+    }
+}
+
+impl<K, V, B> HashRing<Keyed<K, V>, B>
+where
+    K: Hash,
+    B: BuildHasher,
+{
+    /// Inserts a node with the identity `key` and `value`, see [insert](HashRing::insert).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::{HashRing, Keyed};
+    ///
+    /// let mut ring: HashRing<Keyed<u32, &str>, _> = HashRing::default();
+    ///
+    /// assert_eq!(ring.insert_with_key(1, "10.0.0.1:1234", 10), None);
+    /// let old = ring.insert_with_key(1, "10.0.0.2:1234", 10);
+    /// assert_eq!(old.map(Keyed::into_parts), Some((1, "10.0.0.1:1234")));
+    /// ```
+    pub fn insert_with_key(&mut self, key: K, value: V, weight: u64) -> Option<Keyed<K, V>> {
+        self.insert(Keyed::new(key, value), weight)
+    }
+}
@@ -10,23 +10,129 @@
 #![cfg_attr(all(test, feature = "nightly"), feature(test))]
 #![cfg_attr(docsrs, feature(doc_cfg))]
 
+#[cfg(feature = "rkyv")]
+#[cfg_attr(docsrs, doc(cfg(feature = "rkyv")))]
+mod archive;
+mod builder;
+mod cache;
+mod collision;
+mod entry;
+mod error;
+mod event;
+mod frozen;
+mod id;
+mod jump;
+#[cfg(feature = "ketama")]
+#[cfg_attr(docsrs, doc(cfg(feature = "ketama")))]
+pub mod ketama;
+mod key_hash;
+mod keyed;
+mod lookup;
+mod maglev;
+mod multi_probe;
+mod partition;
+#[cfg(feature = "async")]
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+mod publisher;
+#[cfg(feature = "redis")]
+#[cfg_attr(docsrs, doc(cfg(feature = "redis")))]
+pub mod redis;
+mod rendezvous;
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+mod serialization;
+mod shared;
+#[cfg(feature = "snapshot")]
+#[cfg_attr(docsrs, doc(cfg(feature = "snapshot")))]
+mod snapshot;
+mod stats;
+mod strategy;
+mod transaction;
+#[cfg(feature = "viz")]
+mod viz;
+mod weight;
+
+#[cfg(feature = "rkyv")]
+pub use archive::ArchivedFrozenHashRing;
+pub use builder::HashRingBuilder;
+pub use cache::CachedHashRing;
+pub use collision::CollisionPolicy;
+pub use entry::{Entry, OccupiedEntry, VacantEntry};
+pub use error::{HulahoopError, RejectedInsert};
+pub use event::RingEvent;
+pub use frozen::FrozenHashRing;
+pub use id::NodeId;
+pub use jump::{JumpHash, JumpRing};
+pub use key_hash::KeyHash;
+pub use keyed::Keyed;
+pub use lookup::LookupTable;
+pub use maglev::MaglevTable;
+pub use multi_probe::MultiProbeRing;
+pub use partition::PartitionedRing;
+#[cfg(feature = "async")]
+pub use publisher::{RingPublisher, RingSubscriber};
+pub use rendezvous::RendezvousRing;
+pub use shared::SharedHashRing;
+pub use stats::RingStats;
+pub use strategy::VirtualNodeStrategy;
+pub use transaction::Transaction;
+pub use weight::Weight;
+
 #[cfg(feature = "fxhash")]
 use rustc_hash::FxHasher;
 use std::borrow::Borrow;
+use std::cmp::Reverse;
 #[cfg(not(feature = "fxhash"))]
 use std::collections::hash_map::DefaultHasher;
-use std::collections::{BTreeMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fmt::Debug;
 use std::hash::BuildHasherDefault;
 use std::hash::{BuildHasher, Hash, Hasher};
-use std::num::NonZeroU64;
-use std::sync::Arc;
-use std::vec::IntoIter;
+use std::num::{NonZeroU64, NonZeroUsize};
+use std::ops::Bound;
+use std::sync::{Arc, Weak};
+use std::vec;
 
-#[derive(Debug)]
-struct MasterNode<N> {
+/// A node of a `HashRing` together with its weight.
+///
+/// All virtual nodes of a node share the same `MasterNode`.
+/// A [Weak](Weak) reference to it can be obtained with [weak_of](HashRing::weak_of).
+#[derive(Debug, Clone)]
+pub struct MasterNode<N> {
     node: N,
+    // The position of the first virtual node, identifying the node within the ring
+    id: u64,
     weight: NonZeroU64,
+    // The number of virtual node identifiers `0..requested_weight` the positions were computed for
+    requested_weight: NonZeroU64,
+    // The identifiers and positions of virtual nodes moved away from their computed position, ordered by identifier
+    reassigned_positions: Vec<(u64, u64)>,
+}
+
+impl<N> MasterNode<N> {
+    /// Returns a reference to the node.
+    pub fn node(&self) -> &N {
+        &self.node
+    }
+
+    /// Returns the actual number of virtual nodes of the node.
+    pub fn weight(&self) -> u64 {
+        self.weight.get()
+    }
+
+    /// Returns the id of the node, see [NodeId](NodeId).
+    pub fn id(&self) -> NodeId {
+        NodeId(self.id)
+    }
+
+    /// Returns the position of the virtual node with the given identifier, given its computed position.
+    fn position(&self, virtual_node_identifier: u64, computed_position: u64) -> u64 {
+        self.reassigned_positions
+            .binary_search_by_key(&virtual_node_identifier, |(identifier, _)| *identifier)
+            .map_or(computed_position, |index| {
+                self.reassigned_positions[index].1
+            })
+    }
 }
 
 /// An iterator over the nodes of a `HashRing`.
@@ -34,11 +140,11 @@ struct MasterNode<N> {
 /// This `struct` is created by the [iter](HashRing::iter) method on [HashRing](HashRing). See its documentation for more.
 #[derive(Debug)]
 pub struct Iter<'a, N> {
-    inner: IntoIter<(u64, (&'a N, u64))>,
+    inner: vec::IntoIter<(u64, (&'a N, u64))>,
 }
 
 impl<'a, N> Iter<'a, N> {
-    fn new(iter: IntoIter<(u64, (&'a N, u64))>) -> Self {
+    fn new(iter: vec::IntoIter<(u64, (&'a N, u64))>) -> Self {
         Self { inner: iter }
     }
 }
@@ -51,6 +157,23 @@ impl<'a, N> Iterator for Iter<'a, N> {
     }
 }
 
+/// An owning iterator over the nodes of a `HashRing`.
+///
+/// This `struct` is created by the `into_iter` method on [HashRing](HashRing)
+/// (provided by the [IntoIterator](IntoIterator) trait). See its documentation for more.
+#[derive(Debug)]
+pub struct IntoIter<N> {
+    inner: vec::IntoIter<(N, u64)>,
+}
+
+impl<N> Iterator for IntoIter<N> {
+    type Item = (N, u64);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+}
+
 /// A hash ring for consistent hashing.
 ///
 ///
@@ -63,10 +186,201 @@ impl<'a, N> Iterator for Iter<'a, N> {
 /// ring.insert("10.0.0.1:1234", 1);
 /// assert_eq!(ring.get("Some key"), Some(&"10.0.0.1:1234"));
 /// ```
-#[derive(Debug)]
 pub struct HashRing<N, B> {
     virtual_nodes: BTreeMap<u64, Arc<MasterNode<N>>>,
+    // Master nodes by the hash of their node, to find nodes whose first position is held by another node and, with a
+    // custom strategy, which cannot compute the positions of borrowed forms of nodes, any node. Entries of master nodes
+    // which are no longer part of the ring are skipped on lookup.
+    master_nodes_by_hash: HashMap<u64, Vec<Weak<MasterNode<N>>>>,
     hash_builder: B,
+    virtual_node_fn: Option<VirtualNodeFn<N, B>>,
+    total_virtual_node_budget: Option<u64>,
+    replication_factor: NonZeroUsize,
+    collision_policy: CollisionPolicy,
+    collision_callback: Option<CollisionCallback<N>>,
+    event_callback: Option<EventCallback<N>>,
+    // Number of master nodes owning at least one virtual node, kept up to date by every change of the virtual nodes
+    node_count: usize,
+    generation: u64,
+    // Incremented by every change of the virtual nodes or nodes
+    version: u64,
+}
+
+type VirtualNodeFn<N, B> = Arc<dyn VirtualNodeStrategy<N, B> + Send + Sync>;
+
+type CollisionCallback<N> = Arc<dyn Fn(&N, u64, u64) + Send + Sync>;
+
+type EventCallback<N> = Arc<dyn Fn(&RingEvent<'_, N>) + Send + Sync>;
+
+#[cfg(not(feature = "fxhash"))]
+type DefaultBuildHasher = BuildHasherDefault<DefaultHasher>;
+#[cfg(feature = "fxhash")]
+type DefaultBuildHasher = BuildHasherDefault<FxHasher>;
+
+impl<N, B> Debug for HashRing<N, B>
+where
+    N: Debug,
+    B: Debug,
+{
+    /// Prints a summary of the ring. The alternate formatter `{:#?}` additionally prints every virtual node.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let alternate = f.alternate();
+        let mut debug_struct = f.debug_struct("HashRing");
+        debug_struct
+            .field("nodes", &self.key_space_shares().len())
+            .field("virtual_nodes", &self.virtual_nodes.len())
+            .field("balance_factor", &self.balance_factor());
+        if alternate {
+            debug_struct.field("positions", &self.virtual_nodes);
+        }
+        debug_struct
+            .field("hash_builder", &self.hash_builder)
+            .field("custom_virtual_node_fn", &self.virtual_node_fn.is_some())
+            .field("total_virtual_node_budget", &self.total_virtual_node_budget)
+            .field("replication_factor", &self.replication_factor)
+            .field("collision_policy", &self.collision_policy)
+            .field("collision_callback", &self.collision_callback.is_some())
+            .field("event_callback", &self.event_callback.is_some())
+            .field("generation", &self.generation)
+            .field("version", &self.version)
+            .finish()
+    }
+}
+
+/// The number of distinct hashes, i.e. the size of the key space of the ring.
+const KEY_SPACE: u128 = u64::MAX as u128 + 1;
+
+impl<N, B> HashRing<N, B> {
+    /// The largest weight accepted by [try_insert](HashRing::try_insert).
+    pub const MAX_WEIGHT: u64 = u32::MAX as u64;
+
+    fn into_master_nodes(
+        virtual_nodes: BTreeMap<u64, Arc<MasterNode<N>>>,
+    ) -> impl Iterator<Item = MasterNode<N>>
+    where
+        N: Clone,
+    {
+        let mut master_nodes = virtual_nodes.into_values().collect::<Vec<_>>();
+        master_nodes.sort_unstable_by_key(|master_node| (master_node.id, Arc::as_ptr(master_node)));
+        master_nodes.dedup_by(|x, y| Arc::ptr_eq(x, y));
+        // Master nodes still referenced elsewhere, e.g. through an upgraded `Weak`, are cloned instead of dropped
+        master_nodes.into_iter().map(Arc::unwrap_or_clone)
+    }
+
+    /// Returns the share of the key space of the node with the largest share, multiplied by the number of nodes.
+    ///
+    /// The share of a node is the length of all arcs of the ring ending at its virtual nodes.
+    /// A perfectly balanced ring has a balance factor of 1.0, a ring where one node owns all keys has a balance factor
+    /// equal to the number of nodes. It measures the worst case, see [key_share_stddev](HashRing::key_share_stddev)
+    /// for a measure of the spread of all shares. Returns 0.0 for an empty ring.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut ring: HashRing<&str, _> = HashRing::default();
+    /// assert_eq!(ring.balance_factor(), 0.0);
+    ///
+    /// ring.insert("10.0.0.1:1234", 10);
+    /// assert_eq!(ring.balance_factor(), 1.0);
+    ///
+    /// ring.insert("10.0.0.2:1234", 10);
+    /// assert!(ring.balance_factor() >= 1.0);
+    /// ```
+    pub fn balance_factor(&self) -> f64 {
+        self.stats().balance_factor()
+    }
+
+    /// Returns the population standard deviation of the shares of the key space of all nodes, as fractions of the key space.
+    ///
+    /// Unlike the [balance_factor](HashRing::balance_factor), which only reflects the node with the largest share,
+    /// this reflects the spread of the shares of all nodes, so a gradually worsening balance shows up before any single
+    /// node is overloaded. Returns 0.0 for an empty ring.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut ring: HashRing<&str, _> = HashRing::default();
+    ///
+    /// ring.insert("10.0.0.1:1234", 10);
+    /// assert_eq!(ring.key_share_stddev(), 0.0);
+    ///
+    /// ring.insert("10.0.0.2:1234", 10);
+    /// assert!(ring.key_share_stddev() < 0.5);
+    /// ```
+    pub fn key_share_stddev(&self) -> f64 {
+        self.stats().key_share_stddev()
+    }
+
+    /// Computes [RingStats](RingStats) about the distribution of the key space in a single pass over the ring.
+    ///
+    /// Use this instead of calling [balance_factor](HashRing::balance_factor) and
+    /// [key_share_stddev](HashRing::key_share_stddev) separately to compute several metrics at once.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut ring: HashRing<&str, _> = HashRing::default();
+    ///
+    /// ring.insert("10.0.0.1:1234", 10);
+    /// ring.insert("10.0.0.2:1234", 20);
+    ///
+    /// let stats = ring.stats();
+    /// assert_eq!(stats.len(), 2);
+    /// assert_eq!(stats.total_weight(), 30);
+    /// assert_eq!(stats.balance_factor(), ring.balance_factor());
+    /// assert!(stats.key_share(&"10.0.0.1:1234").is_some_and(|share| share < 1.0));
+    /// ```
+    pub fn stats(&self) -> RingStats<'_, N> {
+        RingStats::new(self)
+    }
+
+    /// Returns the size of the key space routed to every node, keyed by its master node.
+    fn key_space_shares(&self) -> HashMap<*const MasterNode<N>, u128> {
+        key_space_shares_of(
+            self.virtual_nodes
+                .iter()
+                .map(|(position, master_node)| (*position, Arc::as_ptr(master_node))),
+        )
+    }
+}
+
+/// Returns the size of the key space routed to every owner of the given positions, which must be in ascending order.
+///
+/// Each position owns the keys between the preceding position (exclusive) and itself (inclusive).
+fn key_space_shares_of<T, I>(positions: I) -> HashMap<T, u128>
+where
+    T: Eq + Hash,
+    I: DoubleEndedIterator<Item = (u64, T)> + Clone,
+{
+    let mut key_space_shares = HashMap::new();
+    let mut previous_position = positions
+        .clone()
+        .next_back()
+        .map(|(position, _)| position as u128);
+    for (position, owner) in positions {
+        let position = position as u128;
+        // The first position also owns the keys after the last position
+        let share = match previous_position {
+            Some(previous) if previous < position => position - previous,
+            Some(previous) => KEY_SPACE - previous + position,
+            None => KEY_SPACE,
+        };
+        *key_space_shares.entry(owner).or_insert(0) += share;
+        previous_position = Some(position);
+    }
+    key_space_shares
+}
+
+/// Returns the share of the key space of the owner with the largest share, multiplied by the number of owners.
+fn balance_factor_of<T>(key_space_shares: &HashMap<T, u128>) -> f64 {
+    let max_key_space_share = key_space_shares.values().max().copied().unwrap_or(0);
+    max_key_space_share as f64 * key_space_shares.len() as f64 / KEY_SPACE as f64
 }
 
 #[cfg(not(feature = "fxhash"))]
@@ -74,7 +388,17 @@ impl<N> Default for HashRing<N, BuildHasherDefault<DefaultHasher>> {
     fn default() -> Self {
         Self {
             virtual_nodes: Default::default(),
+            master_nodes_by_hash: HashMap::new(),
             hash_builder: BuildHasherDefault::default(),
+            virtual_node_fn: None,
+            total_virtual_node_budget: None,
+            replication_factor: NonZeroUsize::MIN,
+            collision_policy: CollisionPolicy::Replace,
+            collision_callback: None,
+            event_callback: None,
+            node_count: 0,
+            generation: 0,
+            version: 0,
         }
     }
 }
@@ -96,7 +420,17 @@ impl<N> HashRing<N, BuildHasherDefault<DefaultHasher>> {
     pub fn new() -> Self {
         Self {
             virtual_nodes: BTreeMap::new(),
+            master_nodes_by_hash: HashMap::new(),
             hash_builder: BuildHasherDefault::default(),
+            virtual_node_fn: None,
+            total_virtual_node_budget: None,
+            replication_factor: NonZeroUsize::MIN,
+            collision_policy: CollisionPolicy::Replace,
+            collision_callback: None,
+            event_callback: None,
+            node_count: 0,
+            generation: 0,
+            version: 0,
         }
     }
 }
@@ -106,7 +440,17 @@ impl<N> Default for HashRing<N, BuildHasherDefault<FxHasher>> {
     fn default() -> Self {
         Self {
             virtual_nodes: Default::default(),
+            master_nodes_by_hash: HashMap::new(),
             hash_builder: BuildHasherDefault::default(),
+            virtual_node_fn: None,
+            total_virtual_node_budget: None,
+            replication_factor: NonZeroUsize::MIN,
+            collision_policy: CollisionPolicy::Replace,
+            collision_callback: None,
+            event_callback: None,
+            node_count: 0,
+            generation: 0,
+            version: 0,
         }
     }
 }
@@ -128,7 +472,17 @@ impl<N> HashRing<N, BuildHasherDefault<FxHasher>> {
     pub fn new() -> Self {
         Self {
             virtual_nodes: BTreeMap::new(),
+            master_nodes_by_hash: HashMap::new(),
             hash_builder: BuildHasherDefault::default(),
+            virtual_node_fn: None,
+            total_virtual_node_budget: None,
+            replication_factor: NonZeroUsize::MIN,
+            collision_policy: CollisionPolicy::Replace,
+            collision_callback: None,
+            event_callback: None,
+            node_count: 0,
+            generation: 0,
+            version: 0,
         }
     }
 }
@@ -155,72 +509,198 @@ where
     pub fn with_hasher(hash_builder: B) -> Self {
         Self {
             virtual_nodes: BTreeMap::new(),
+            master_nodes_by_hash: HashMap::new(),
             hash_builder,
+            virtual_node_fn: None,
+            total_virtual_node_budget: None,
+            replication_factor: NonZeroUsize::MIN,
+            collision_policy: CollisionPolicy::Replace,
+            collision_callback: None,
+            event_callback: None,
+            node_count: 0,
+            generation: 0,
+            version: 0,
         }
     }
 
-    /// Returns a reference to the ring’s `BuildHasher`.
+    /// Creates an empty `HashRing` which uses `virtual_node_fn` to compute the position of each virtual node.
+    ///
+    /// The function receives the ring's `hash_builder`, the node and the index of the virtual node
+    /// (`0..weight`) and must return the position of that virtual node on the ring.
+    /// This allows matching the placement of other consistent hashing implementations exactly.
+    /// Keys are still hashed with the `hash_builder`.
     ///
     /// # Examples
     ///
     /// ```
+    /// use std::collections::hash_map::DefaultHasher;
+    /// use std::hash::{BuildHasher, BuildHasherDefault, Hash, Hasher};
     /// use hulahoop::HashRing;
-    /// use std::collections::hash_map::RandomState;
     ///
-    /// let hasher = RandomState::new();
-    /// let ring: HashRing<&str, _> = HashRing::with_hasher(hasher);
-    /// let hasher: &RandomState = ring.hasher();
+    /// let mut ring: HashRing<&str, _> = HashRing::with_virtual_node_fn(
+    ///     BuildHasherDefault::<DefaultHasher>::default(),
+    ///     |hash_builder, node, index| {
+    ///         let mut hasher = hash_builder.build_hasher();
+    ///         (node, index).hash(&mut hasher);
+    ///         hasher.finish()
+    ///     },
+    /// );
+    ///
+    /// ring.insert("10.0.0.1:1234", 10);
+    /// assert_eq!(ring.get("Some key"), Some(&"10.0.0.1:1234"));
     /// ```
-    pub fn hasher(&self) -> &B {
-        &self.hash_builder
+    pub fn with_virtual_node_fn<F>(hash_builder: B, virtual_node_fn: F) -> Self
+    where
+        F: Fn(&B, &N, u64) -> u64 + Send + Sync + 'static,
+    {
+        Self {
+            virtual_nodes: BTreeMap::new(),
+            master_nodes_by_hash: HashMap::new(),
+            hash_builder,
+            virtual_node_fn: Some(Arc::new(virtual_node_fn)),
+            total_virtual_node_budget: None,
+            replication_factor: NonZeroUsize::MIN,
+            collision_policy: CollisionPolicy::Replace,
+            collision_callback: None,
+            event_callback: None,
+            node_count: 0,
+            generation: 0,
+            version: 0,
+        }
     }
 
-    /// Inserts a node to the `HashRing`.
+    /// Creates a `HashRing` from a map of nodes and their weights, using the given `hash_builder` to hash nodes and keys.
     ///
-    /// A `weight`, representing the number of virtual nodes for the given `node`, must be provided.
+    /// # Examples
     ///
-    /// There can be hash collisions resulting in fewer than `weight` virtual nodes added.
-    /// If the ring did not have this node present or `weight` is 0, None is returned.
-    /// If the ring did have this node present, the virtual nodes are updated, and the old node is returned.
+    /// ```
+    /// use std::collections::HashMap;
+    /// use std::collections::hash_map::RandomState;
+    /// use hulahoop::HashRing;
+    ///
+    /// let weights = HashMap::from([("10.0.0.1:1234", 10), ("10.0.0.2:1234", 5)]);
+    /// let ring = HashRing::from_weights(weights.clone(), RandomState::new());
+    ///
+    /// assert_eq!(ring.len(), 2);
+    /// assert_eq!(ring.to_weights_map(), weights);
+    /// ```
+    pub fn from_weights<S>(weights: HashMap<N, u64, S>, hash_builder: B) -> Self {
+        let mut ring = Self::with_hasher(hash_builder);
+        for (node, weight) in weights {
+            ring.insert(node, weight);
+        }
+        ring
+    }
+
+    /// Creates a `HashRing` from nodes and their relative weights, distributing `total_virtual_nodes` among them.
+    ///
+    /// Every node gets a number of virtual nodes proportional to its relative weight, rounded such that the
+    /// weights add up to `total_virtual_nodes` exactly. Nodes with a relative weight which is not positive and finite
+    /// are ignored. Every other node gets at least one virtual node, so the total may exceed `total_virtual_nodes`
+    /// if it is smaller than the number of nodes. The ring is built like with [build_from](HashRing::build_from).
     ///
     /// # Examples
     ///
     /// ```
+    /// use std::collections::hash_map::RandomState;
     /// use hulahoop::HashRing;
     ///
-    /// let mut ring: HashRing<&str, _> = HashRing::default();
+    /// let nodes = [("10.0.0.1:1234", 1.0), ("10.0.0.2:1234", 2.5), ("10.0.0.3:1234", 0.5)];
+    /// let ring = HashRing::from_relative_weights(nodes, 400, RandomState::new());
     ///
-    /// assert_eq!(ring.insert("10.0.0.1:1234", 1), None);
-    /// assert_eq!(ring.insert("10.0.0.1:1234", 1), Some("10.0.0.1:1234"));
+    /// assert_eq!(ring.to_weights_map().get("10.0.0.2:1234"), Some(&250));
+    /// assert_eq!(ring.to_weights_map().values().sum::<u64>(), 400);
     /// ```
-    pub fn insert(&mut self, node: N, weight: u64) -> Option<N> {
-        if weight == 0 {
-            return None;
+    pub fn from_relative_weights<I>(nodes: I, total_virtual_nodes: u64, hash_builder: B) -> Self
+    where
+        N: Eq,
+        I: IntoIterator<Item = (N, f64)>,
+    {
+        let nodes = nodes
+            .into_iter()
+            .filter(|(_, weight)| weight.is_finite() && *weight > 0.0)
+            .collect::<Vec<_>>();
+        let total_relative_weight = nodes.iter().map(|(_, weight)| weight).sum::<f64>();
+        let shares = nodes
+            .iter()
+            .map(|(_, weight)| weight / total_relative_weight * total_virtual_nodes as f64)
+            .collect::<Vec<_>>();
+        let mut weights = shares
+            .iter()
+            .map(|share| (share.floor() as u64).max(1))
+            .collect::<Vec<_>>();
+        // Hand out the virtual nodes lost to rounding down to the nodes with the largest remainders
+        let mut remainders = (0..shares.len()).collect::<Vec<_>>();
+        remainders.sort_by(|x, y| {
+            (shares[*y] - shares[*y].floor()).total_cmp(&(shares[*x] - shares[*x].floor()))
+        });
+        let missing = total_virtual_nodes.saturating_sub(weights.iter().sum());
+        for index in remainders.into_iter().cycle().take(missing as usize) {
+            weights[index] += 1;
         }
-        let weight = NonZeroU64::new(weight).unwrap();
-        let virtual_node_hashes = self.compute_virtual_node_hashes(&node, weight);
-        let actual_weight = NonZeroU64::new(virtual_node_hashes.len() as u64).unwrap();
-        let mut colliding_node = None;
-        if self
-            .virtual_nodes
-            // It's guaranteed that at least one element is present
-            .contains_key(virtual_node_hashes.iter().next().unwrap())
-        {
-            colliding_node = self.remove_inner(&node).0;
+        // Take the virtual nodes given to nodes with a share below one back from the nodes with the largest shares
+        let excess = weights
+            .iter()
+            .sum::<u64>()
+            .saturating_sub(total_virtual_nodes);
+        for _ in 0..excess {
+            if let Some(index) = (0..weights.len())
+                .filter(|index| weights[*index] > 1)
+                .max_by(|x, y| shares[*x].total_cmp(&shares[*y]))
+            {
+                weights[index] -= 1;
+            }
         }
-        let master_node = Arc::new(MasterNode {
-            node,
-            weight: actual_weight,
-        });
+        Self::build_from(
+            nodes
+                .into_iter()
+                .zip(weights)
+                .map(|((node, _), weight)| (node, weight)),
+            hash_builder,
+        )
+    }
 
-        for virtual_node_hash in virtual_node_hashes.into_iter() {
-            self.virtual_nodes
-                .insert(virtual_node_hash, master_node.clone());
-        }
-        colliding_node
+    /// Creates a `HashRing` from nodes and their weights in a single pass, using the given `hash_builder` to hash nodes and keys.
+    ///
+    /// Instead of inserting every node on its own, the positions of all virtual nodes are computed and sorted once,
+    /// which is considerably faster for large initial topologies.
+    /// If a node is given more than once, the last weight is used.
+    /// Positions claimed by several nodes are resolved like in [deduplicate_positions](HashRing::deduplicate_positions),
+    /// so the result does not depend on the order of `nodes`. Only if distinct nodes have the same
+    /// [node_base_hash](HashRing::node_base_hash), positions claimed by both are given to the node given first.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::hash_map::RandomState;
+    /// use hulahoop::HashRing;
+    ///
+    /// let ring = HashRing::build_from([("10.0.0.1:1234", 10), ("10.0.0.2:1234", 5)], RandomState::new());
+    ///
+    /// assert_eq!(ring.len(), 2);
+    /// assert_eq!(ring.total_weight(), 15);
+    /// ```
+    pub fn build_from<I>(nodes: I, hash_builder: B) -> Self
+    where
+        N: Eq,
+        I: IntoIterator<Item = (N, u64)>,
+    {
+        let mut ring = Self::with_hasher(hash_builder);
+        ring.insert_many(nodes);
+        ring
     }
 
-    /// Returns a reference to the node with a hash closest to the hash of the key.
+    /// Inserts all nodes with their weights at once, like calling [insert](HashRing::insert) for every node.
+    ///
+    /// The positions of all virtual nodes are computed and sorted first and then merged into the ring in one go,
+    /// which is considerably faster than inserting many nodes one at a time.
+    /// If a node is given more than once, the last weight is used, and nodes with a weight of 0 are ignored.
+    /// Positions claimed by several of the given nodes are resolved like in [build_from](HashRing::build_from),
+    /// while positions of nodes already part of the ring are taken over like in [insert](HashRing::insert).
+    ///
+    /// With the [Reassign](CollisionPolicy::Reassign) policy or a
+    /// [total virtual node budget](HashRingBuilder::total_virtual_node_budget), the positions and weights of nodes depend
+    /// on the nodes inserted before them, so the nodes are inserted one at a time in the given order.
     ///
     /// # Examples
     ///
@@ -229,96 +709,248 @@ where
     ///
     /// let mut ring: HashRing<&str, _> = HashRing::default();
     ///
-    /// ring.insert("10.0.0.1:1234", 1);
-    /// assert_eq!(ring.get("Some key"), Some(&"10.0.0.1:1234"));
-    /// assert_eq!(ring.get(12345), Some(&"10.0.0.1:1234"));
+    /// ring.insert("10.0.0.1:1234", 10);
+    /// ring.insert_many([("10.0.0.2:1234", 10), ("10.0.0.3:1234", 5), ("10.0.0.1:1234", 20)]);
+    /// assert_eq!(ring.len(), 3);
+    /// assert_eq!(ring.total_weight(), 35);
     /// ```
-    #[inline]
-    pub fn get<K>(&self, key: K) -> Option<&N>
+    pub fn insert_many<I>(&mut self, nodes: I)
     where
-        K: Hash,
+        N: Eq,
+        I: IntoIterator<Item = (N, u64)>,
     {
-        let mut hasher = self.hash_builder.build_hasher();
-        key.hash(&mut hasher);
-        let key_hash = hasher.finish();
-        match self.virtual_nodes.range(key_hash..).next() {
-            Some((_, virtual_node)) => Some(virtual_node.node.borrow()),
-            None => {
-                // We couldn't find any node greater than the key hash,
-                // so let's loop around and take the first one in the HashRing if available.
-                self.virtual_nodes
-                    .iter()
-                    .next()
-                    .map(|(_, virtual_node)| virtual_node.node.borrow())
+        if self.collision_policy == CollisionPolicy::Reassign
+            || self.total_virtual_node_budget.is_some()
+        {
+            for (node, weight) in nodes {
+                self.insert(node, weight);
+            }
+            return;
+        }
+        // Distinct nodes may have the same hash, so only equal nodes are merged
+        let mut weights: BTreeMap<u64, Vec<(N, NonZeroU64)>> = BTreeMap::new();
+        for (node, weight) in nodes {
+            let nodes = weights.entry(self.node_base_hash(&node)).or_default();
+            let existing = nodes.iter().position(|(other, _)| *other == node);
+            match (existing, NonZeroU64::new(weight)) {
+                (Some(index), Some(weight)) => nodes[index] = (node, weight),
+                (Some(index), None) => {
+                    nodes.remove(index);
+                }
+                (None, Some(weight)) => nodes.push((node, weight)),
+                (None, None) => {}
+            }
+        }
+        let nodes = weights
+            .into_iter()
+            .flat_map(|(node_hash, nodes)| nodes.into_iter().map(move |node| (node_hash, node)))
+            .collect::<Vec<_>>();
+        for (_, (node, _)) in &nodes {
+            self.remove_inner(node);
+        }
+
+        let mut positions = Vec::new();
+        for (index, (node_hash, (node, weight))) in nodes.iter().enumerate() {
+            positions.extend(
+                (0..weight.get()).map(|identifier| {
+                    (self.virtual_node_hash(node, identifier), *node_hash, index)
+                }),
+            );
+        }
+        // The lowest claim of every position wins
+        positions.sort_unstable();
+        positions.dedup_by_key(|(position, _, _)| *position);
+
+        let mut actual_weights = vec![0u64; nodes.len()];
+        for (_, _, index) in &positions {
+            actual_weights[*index] += 1;
+        }
+        let master_nodes = nodes
+            .into_iter()
+            .zip(actual_weights)
+            .map(|((_, (node, requested_weight)), weight)| {
+                if let Some(collision_callback) = self
+                    .collision_callback
+                    .as_ref()
+                    .filter(|_| weight < requested_weight.get())
+                {
+                    collision_callback(&node, requested_weight.get(), weight);
+                }
+                NonZeroU64::new(weight).map(|weight| {
+                    Arc::new(MasterNode {
+                        id: self.virtual_node_hash(&node, 0),
+                        node,
+                        weight,
+                        requested_weight,
+                        reassigned_positions: Vec::new(),
+                    })
+                })
+            })
+            .collect::<Vec<_>>();
+        let evicted_master_nodes = positions
+            .iter()
+            .filter_map(|(position, _, _)| self.virtual_nodes.get(position).cloned())
+            .collect();
+        let mut virtual_nodes = positions
+            .into_iter()
+            .filter_map(|(position, _, index)| {
+                master_nodes[index]
+                    .as_ref()
+                    .map(|master_node| (position, master_node.clone()))
+            })
+            .collect();
+        self.virtual_nodes.append(&mut virtual_nodes);
+        self.node_count = self.node_count + master_nodes.iter().flatten().count()
+            - self.evict_master_nodes(evicted_master_nodes, None);
+        for master_node in master_nodes.iter().flatten() {
+            self.index_master_node(master_node);
+        }
+        if master_nodes.iter().any(Option::is_some) {
+            self.version += 1;
+        }
+        if self.event_callback.is_some() {
+            let mut master_nodes = master_nodes.into_iter().flatten().collect::<Vec<_>>();
+            master_nodes.sort_unstable_by_key(|master_node| master_node.id);
+            for master_node in master_nodes {
+                self.notify_added(&master_node, self.owned_positions(&master_node));
             }
         }
     }
 
-    /// Returns the number of nodes in the Hashring.
+    /// Rebuilds the ring, computing the positions of all virtual nodes with the given `strategy`.
     ///
-    /// It does not return the number of virtual nodes (as specified with `weight` in the [insert](HashRing::insert) method).
+    /// All nodes are reinserted with the weight they were originally inserted with.
+    /// As the positions of all virtual nodes change, practically all keys are remapped.
+    /// The [event callback](HashRingBuilder::event_callback) receives a single [Batch](RingEvent::Batch)
+    /// of all changes once the rebuild is done.
     ///
     /// # Examples
     ///
     /// ```
+    /// use std::hash::BuildHasher;
     /// use hulahoop::HashRing;
     ///
     /// let mut ring: HashRing<&str, _> = HashRing::default();
     ///
     /// ring.insert("10.0.0.1:1234", 10);
     /// ring.insert("10.0.0.2:1234", 10);
+    ///
+    /// let ring = ring.rebuild_with(|hash_builder: &_, node: &&str, index| {
+    ///     BuildHasher::hash_one(hash_builder, (index, node))
+    /// });
     /// assert_eq!(ring.len(), 2);
     /// ```
-    pub fn len(&self) -> usize {
-        self.virtual_nodes
-            .values()
-            .map(|node| {
-                let mut hasher = self.hash_builder.build_hasher();
-                node.node.hash(&mut hasher);
-                hasher.finish()
-            })
-            .collect::<HashSet<_>>()
-            .len()
+    pub fn rebuild_with<S>(mut self, strategy: S) -> Self
+    where
+        N: Clone,
+        S: VirtualNodeStrategy<N, B> + Send + Sync + 'static,
+    {
+        // The rebuild is reported as a single batch once all nodes are reinserted
+        let event_callback = self.event_callback.take();
+        let mut old_master_nodes = match event_callback {
+            Some(_) => self
+                .master_nodes()
+                .into_iter()
+                .map(|master_node| (master_node.clone(), self.owned_positions(master_node)))
+                .collect(),
+            None => Vec::new(),
+        };
+        // In the order the master nodes are reinserted
+        old_master_nodes
+            .sort_unstable_by_key(|(master_node, _)| (master_node.id, Arc::as_ptr(master_node)));
+        let HashRing {
+            virtual_nodes,
+            hash_builder,
+            total_virtual_node_budget,
+            replication_factor,
+            collision_policy,
+            collision_callback,
+            generation,
+            version,
+            ..
+        } = self;
+        let mut ring = Self {
+            virtual_nodes: BTreeMap::new(),
+            master_nodes_by_hash: HashMap::new(),
+            hash_builder,
+            virtual_node_fn: Some(Arc::new(strategy)),
+            total_virtual_node_budget,
+            replication_factor,
+            collision_policy,
+            collision_callback,
+            event_callback: None,
+            node_count: 0,
+            generation,
+            version,
+        };
+        let mut new_master_nodes = Vec::with_capacity(old_master_nodes.len());
+        for master_node in Self::into_master_nodes(virtual_nodes) {
+            let node = master_node.node;
+            let weight = ring.budgeted_weight(&node, master_node.requested_weight.get());
+            let new_master_node = NonZeroU64::new(weight).map(|weight| {
+                ring.remove_inner(&node);
+                ring.insert_absent(node, weight)
+            });
+            if event_callback.is_some() {
+                new_master_nodes.push(new_master_node);
+            }
+        }
+
+        ring.event_callback = event_callback;
+        if ring.event_callback.is_some() {
+            let changes = old_master_nodes
+                .into_iter()
+                .zip(new_master_nodes)
+                .map(|((old, old_positions), new)| {
+                    // Nodes may have been replaced by nodes reinserted after them
+                    let new = new.filter(|new| !ring.owned_positions(new).is_empty());
+                    (Some(old), new, old_positions)
+                })
+                .collect();
+            ring.notify_batch(changes);
+        }
+        ring
     }
 
-    /// Returns `true` if the ring contains no elements.
+    /// Returns a reference to the ring’s `BuildHasher`.
     ///
     /// # Examples
     ///
     /// ```
     /// use hulahoop::HashRing;
+    /// use std::collections::hash_map::RandomState;
     ///
-    /// let mut ring: HashRing<&str, _> = HashRing::default();
-    /// assert!(ring.is_empty());
-    ///
-    /// ring.insert("10.0.0.1:1234", 10);
-    /// assert!(!ring.is_empty());
+    /// let hasher = RandomState::new();
+    /// let ring: HashRing<&str, _> = HashRing::with_hasher(hasher);
+    /// let hasher: &RandomState = ring.hasher();
     /// ```
-    pub fn is_empty(&self) -> bool {
-        self.virtual_nodes.is_empty()
+    pub fn hasher(&self) -> &B {
+        &self.hash_builder
     }
 
-    /// Returns `true` if the ring contains the specified node.
+    /// Returns the generation of the ring.
+    ///
+    /// The generation starts at 0 and is only incremented by [with_bumped_generation](HashRing::with_bumped_generation).
+    /// It allows readers to detect that a newly published ring differs from the one they used before.
     ///
     /// # Examples
     ///
     /// ```
     /// use hulahoop::HashRing;
     ///
-    /// let mut ring: HashRing<&str, _> = HashRing::default();
-    ///
-    /// ring.insert("10.0.0.1:1234", 10);
-    /// assert_eq!(ring.contains_node(&"10.0.0.1:1234"), true);
-    /// assert_eq!(ring.contains_node(&"10.0.0.2:1234"), false);
+    /// let ring: HashRing<&str, _> = HashRing::default();
+    /// assert_eq!(ring.generation(), 0);
     /// ```
-    pub fn contains_node(&self, node: &N) -> bool {
-        self.get_master_node(node).is_some()
+    pub fn generation(&self) -> u64 {
+        self.generation
     }
 
-    /// An iterator visiting all node-weight pairs in arbitrary order. The iterator element type is `(&'a N, u64)`.
+    /// Returns the version of the ring.
     ///
-    /// The weight is the actual number of virtual nodes. It may be lower than the `weight` provided when inserting
-    /// a node in case of hash collisions.
+    /// The version starts at 0 and increases with every change of the nodes or virtual nodes of the ring,
+    /// e.g. by [insert](HashRing::insert) or [remove](HashRing::remove), while lookups and no-op changes keep it.
+    /// Clones and snapshots carry the version along, so clients can tell whether they routed keys with the same ring.
+    /// A single change may increase the version by more than one.
     ///
     /// # Examples
     ///
@@ -326,348 +958,5055 @@ where
     /// use hulahoop::HashRing;
     ///
     /// let mut ring: HashRing<&str, _> = HashRing::default();
+    /// assert_eq!(ring.version(), 0);
     ///
     /// ring.insert("10.0.0.1:1234", 10);
-    /// ring.insert("10.0.0.2:1234", 5);
+    /// let version = ring.version();
+    /// assert!(version > 0);
     ///
-    /// for (node, weight) in ring.iter() {
-    ///     println!("node: {node} weight: {weight}");
+    /// ring.remove(&"10.0.0.2:1234");
+    /// assert_eq!(ring.version(), version);
+    /// ring.remove(&"10.0.0.1:1234");
+    /// assert!(ring.version() > version);
+    /// ```
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// Returns the ring with its generation incremented by one.
+    ///
+    /// Together with [Clone](Clone), this supports publishing ring updates without locks:
+    /// clone the current ring, apply the changes, bump the generation and store the new ring
+    /// (e.g. in an `ArcSwap`).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut ring: HashRing<&str, _> = HashRing::default();
+    /// ring.insert("10.0.0.1:1234", 10);
+    ///
+    /// let mut next = ring.clone();
+    /// next.insert("10.0.0.2:1234", 10);
+    /// let next = next.with_bumped_generation();
+    ///
+    /// assert_eq!(ring.generation(), 0);
+    /// assert_eq!(next.generation(), 1);
+    /// ```
+    pub fn with_bumped_generation(mut self) -> Self {
+        self.generation = self.generation.wrapping_add(1);
+        self
+    }
+
+    /// Inserts a node to the `HashRing`.
+    ///
+    /// A `weight`, representing the number of virtual nodes for the given `node`, must be provided.
+    ///
+    /// There can be hash collisions resulting in fewer than `weight` virtual nodes added.
+    /// If a [total virtual node budget](HashRingBuilder::total_virtual_node_budget) is configured,
+    /// `weight` is reduced to the remaining budget.
+    /// If the ring did not have this node present or `weight` is 0, None is returned.
+    /// If the ring did have this node present, the virtual nodes are updated, and the old node is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut ring: HashRing<&str, _> = HashRing::default();
+    ///
+    /// assert_eq!(ring.insert("10.0.0.1:1234", 1), None);
+    /// assert_eq!(ring.insert("10.0.0.1:1234", 1), Some("10.0.0.1:1234"));
+    /// ```
+    pub fn insert(&mut self, node: N, weight: u64) -> Option<N> {
+        debug_assert!(
+            self.verify_hasher_determinism(),
+            "the hasher must produce the same hash for the same input"
+        );
+        let weight = NonZeroU64::new(self.budgeted_weight(&node, weight))?;
+        let colliding_node = self.remove_inner(&node).0;
+        self.insert_absent(node, weight);
+        colliding_node
+    }
+
+    /// Inserts a node which is not part of the ring, returning its master node.
+    fn insert_absent(&mut self, node: N, weight: NonZeroU64) -> Arc<MasterNode<N>> {
+        let (virtual_node_hashes, reassigned_positions) =
+            self.virtual_node_positions(&node, weight, None, None);
+        let master_node = self.insert_master_node(
+            node,
+            weight,
+            virtual_node_hashes,
+            reassigned_positions,
+            None,
+        );
+        if self.event_callback.is_some() {
+            self.notify_added(&master_node, self.owned_positions(&master_node));
+        }
+        master_node
+    }
+
+    /// Inserts a node to the `HashRing`, failing instead of silently ignoring or reducing the `weight`
+    /// or replacing other nodes.
+    ///
+    /// Like [insert](HashRing::insert), the old node is returned if the ring did have this node present.
+    ///
+    /// # Errors
+    ///
+    /// Returns [ZeroWeight](HulahoopError::ZeroWeight) if `weight` is 0,
+    /// [WeightTooLarge](HulahoopError::WeightTooLarge) if `weight` exceeds [MAX_WEIGHT](HashRing::MAX_WEIGHT) and
+    /// [BudgetExceeded](HulahoopError::BudgetExceeded) if `weight` exceeds the remaining
+    /// [total virtual node budget](HashRingBuilder::total_virtual_node_budget).
+    /// Returns [NodeCollision](HulahoopError::NodeCollision) if the node would replace a different node and,
+    /// with the [Replace](CollisionPolicy::Replace) policy, [VirtualNodeCollision](HulahoopError::VirtualNodeCollision)
+    /// if any virtual node would take over the position of another node's virtual node.
+    /// The ring is not modified in case of an error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::{HashRingBuilder, HulahoopError};
+    ///
+    /// let mut ring = HashRingBuilder::default()
+    ///     .total_virtual_node_budget(15)
+    ///     .build();
+    ///
+    /// assert_eq!(ring.try_insert("10.0.0.1:1234", 10), Ok(None));
+    /// assert_eq!(ring.try_insert("10.0.0.2:1234", 0), Err(HulahoopError::ZeroWeight));
+    /// assert_eq!(
+    ///     ring.try_insert("10.0.0.2:1234", 10),
+    ///     Err(HulahoopError::BudgetExceeded { requested: 10, remaining: 5 })
+    /// );
+    /// ```
+    pub fn try_insert(&mut self, node: N, weight: u64) -> Result<Option<N>, HulahoopError> {
+        if weight == 0 {
+            return Err(HulahoopError::ZeroWeight);
+        }
+        if weight > Self::MAX_WEIGHT {
+            return Err(HulahoopError::WeightTooLarge {
+                requested: weight,
+                max: Self::MAX_WEIGHT,
+            });
+        }
+        let available_weight = self.budgeted_weight(&node, weight);
+        if available_weight < weight {
+            return Err(HulahoopError::BudgetExceeded {
+                requested: weight,
+                remaining: available_weight,
+            });
+        }
+        let existing = self.get_master_node(&node);
+        if let Some(existing) = existing {
+            // Different nodes may share the position of their first virtual node
+            if existing.id != self.virtual_node_hash(&node, 0)
+                || self.node_base_hash(&existing.node) != self.node_base_hash(&node)
+            {
+                return Err(HulahoopError::NodeCollision);
+            }
+        }
+        if self.collision_policy == CollisionPolicy::Replace {
+            // It's guaranteed that the weight is not 0
+            let positions =
+                self.compute_virtual_node_hashes(&node, NonZeroU64::new(weight).unwrap());
+            let free_positions = positions
+                .iter()
+                .filter(|position| {
+                    self.virtual_nodes.get(position).is_none_or(|owner| {
+                        existing.is_some_and(|existing| Arc::ptr_eq(owner, existing))
+                    })
+                })
+                .count() as u64;
+            if free_positions < weight {
+                return Err(HulahoopError::VirtualNodeCollision {
+                    colliding: weight - free_positions,
+                });
+            }
+        }
+        Ok(self.insert(node, weight))
+    }
+
+    /// Inserts a node like [insert](HashRing::insert), but only if the [balance_factor](HashRing::balance_factor)
+    /// of the resulting ring does not exceed `max_balance_factor`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [RejectedInsert](RejectedInsert) containing the node and the balance factor the ring would have had
+    /// if inserting the node would exceed `max_balance_factor`. The ring is not modified in that case.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut ring = HashRing::with_virtual_node_fn(
+    ///     std::collections::hash_map::RandomState::new(),
+    ///     |_, node: &&str, _| if *node == "10.0.0.1:1234" { u64::MAX } else { 1 },
+    /// );
+    ///
+    /// assert_eq!(ring.insert_if_balanced("10.0.0.1:1234", 1, 1.5), Ok(None));
+    /// // The second node would only own the keys with the hashes 0 and 1
+    /// let rejected = ring.insert_if_balanced("10.0.0.2:1234", 1, 1.5).unwrap_err();
+    /// assert_eq!(rejected.into_node(), "10.0.0.2:1234");
+    /// assert_eq!(ring.len(), 1);
+    /// ```
+    pub fn insert_if_balanced(
+        &mut self,
+        node: N,
+        weight: u64,
+        max_balance_factor: f64,
+    ) -> Result<Option<N>, RejectedInsert<N>> {
+        if let Some(weight) = NonZeroU64::new(self.budgeted_weight(&node, weight)) {
+            let evicted_master_node = self.get_master_node(&node).map(Arc::as_ptr);
+            let mut positions = self
+                .virtual_nodes
+                .iter()
+                .map(|(position, master_node)| (*position, Some(Arc::as_ptr(master_node))))
+                .filter(|(_, master_node)| *master_node != evicted_master_node)
+                .collect::<BTreeMap<_, _>>();
+            let (virtual_node_hashes, _) =
+                self.virtual_node_positions(&node, weight, None, evicted_master_node);
+            positions.extend(
+                virtual_node_hashes
+                    .into_iter()
+                    .map(|position| (position, None)),
+            );
+            let balance_factor = balance_factor_of(&key_space_shares_of(
+                positions
+                    .iter()
+                    .map(|(position, owner)| (*position, *owner)),
+            ));
+            if balance_factor > max_balance_factor {
+                return Err(RejectedInsert {
+                    node,
+                    balance_factor,
+                });
+            }
+        }
+        Ok(self.insert(node, weight))
+    }
+
+    /// Inserts a node or updates the weight of an existing node, returning its previous actual weight.
+    ///
+    /// Unlike [insert](HashRing::insert), updating an existing node only adds or removes the virtual nodes making up
+    /// the difference between the old and the new `weight`.
+    /// The positions of all other virtual nodes are kept, so only keys close to the added or removed virtual nodes are remapped.
+    ///
+    /// If the ring did not have this node present, it is inserted and `None` is returned.
+    /// A `weight` of 0 removes an existing node.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut ring: HashRing<&str, _> = HashRing::default();
+    ///
+    /// assert_eq!(ring.upsert("10.0.0.1:1234", 10), None);
+    /// assert_eq!(ring.upsert("10.0.0.1:1234", 11), Some(10));
+    /// assert_eq!(ring.upsert("10.0.0.1:1234", 0), Some(11));
+    /// assert!(ring.is_empty());
+    /// ```
+    pub fn upsert(&mut self, node: N, weight: u64) -> Option<u64> {
+        match self.get_master_node(&node) {
+            Some(master_node) => Some(self.update_master_node(master_node.clone(), node, weight)),
+            None => {
+                self.insert(node, weight);
+                None
+            }
+        }
+    }
+
+    /// Updates the weight of the master node of `node`, returning its previous actual weight.
+    fn update_master_node(&mut self, master_node: Arc<MasterNode<N>>, node: N, weight: u64) -> u64 {
+        let old_weight = master_node.weight.get();
+        let weight = match NonZeroU64::new(self.budgeted_weight(&node, weight)) {
+            Some(weight) => weight,
+            None => {
+                self.remove_master_node(master_node);
+                return old_weight;
+            }
+        };
+        let (virtual_node_hashes, reassigned_positions) = self.virtual_node_positions(
+            &node,
+            weight,
+            Some(&master_node),
+            Some(Arc::as_ptr(&master_node)),
+        );
+        let old_positions = match self.event_callback {
+            Some(_) => self.owned_positions(&master_node),
+            None => Vec::new(),
+        };
+        for virtual_node_hash in self
+            .master_node_positions(&master_node)
+            .difference(&virtual_node_hashes)
+        {
+            if self.is_owned_by(virtual_node_hash, &master_node) {
+                self.virtual_nodes.remove(virtual_node_hash);
+            }
+        }
+        self.unindex_master_node(&master_node);
+        // Shared positions are reassigned to the new master node
+        let master_node = self.insert_master_node(
+            node,
+            weight,
+            virtual_node_hashes,
+            reassigned_positions,
+            Some(&master_node),
+        );
+        if self.event_callback.is_some() {
+            let old_positions = old_positions.into_iter().collect::<HashSet<_>>();
+            let new_positions = self
+                .owned_positions(&master_node)
+                .into_iter()
+                .collect::<HashSet<_>>();
+            let changed_positions = old_positions
+                .symmetric_difference(&new_positions)
+                .copied()
+                .collect();
+            self.notify_weight_changed(&master_node, old_weight, changed_positions);
+        }
+        old_weight
+    }
+
+    /// Updates the weight of an existing node, returning its previous actual weight.
+    ///
+    /// Like [upsert](HashRing::upsert), only the virtual nodes making up the difference between the old and the new
+    /// `weight` are added or removed. Unlike `upsert`, the node is only borrowed and not inserted if the ring did not
+    /// have it present, in which case `None` is returned. A `weight` of 0 removes the node.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut ring: HashRing<String, _> = HashRing::default();
+    /// let node = "10.0.0.1:1234".to_string();
+    ///
+    /// assert_eq!(ring.set_weight(&node, 10), None);
+    /// assert!(ring.is_empty());
+    ///
+    /// ring.insert(node.clone(), 10);
+    /// let positions = ring.positions_of(&node);
+    /// assert_eq!(ring.set_weight(&node, 20), Some(10));
+    /// assert!(positions.iter().all(|position| ring.positions_of(&node).contains(position)));
+    /// ```
+    pub fn set_weight(&mut self, node: &N, weight: u64) -> Option<u64>
+    where
+        N: Clone,
+    {
+        if !self.contains_node(node) {
+            return None;
+        }
+        self.upsert(node.clone(), weight)
+    }
+
+    /// Replaces a node with another node occupying exactly the same positions, so no key is remapped.
+    ///
+    /// This swaps the value of a node whose fields not taken into account by its [Hash](Hash) implementation changed,
+    /// e.g. rotated credentials stored next to the address of a node. The new node must have the same positions
+    /// as the old one, i.e. the same hash for the default strategy.
+    /// The old node is returned unless an [upgraded Weak](HashRing::weak_of) reference to it is alive.
+    ///
+    /// Swapping the hashed part of a node, e.g. an IP address for a hostname, is always rejected, as the positions
+    /// are derived from it. To swap such values without remapping keys, insert the nodes as [Keyed](Keyed) nodes,
+    /// whose positions only depend on a separate identity.
+    ///
+    /// # Errors
+    ///
+    /// Returns the new node if the old node is not part of the ring or if the positions of the nodes differ.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::hash::{Hash, Hasher};
+    /// use hulahoop::HashRing;
+    ///
+    /// #[derive(Debug, PartialEq)]
+    /// struct Node {
+    ///     address: &'static str,
+    ///     token: &'static str,
+    /// }
+    ///
+    /// impl Hash for Node {
+    ///     fn hash<H: Hasher>(&self, state: &mut H) {
+    ///         self.address.hash(state);
+    ///     }
     /// }
+    ///
+    /// let mut ring: HashRing<Node, _> = HashRing::default();
+    /// let old = Node { address: "10.0.0.1:1234", token: "old" };
+    /// ring.insert(Node { address: "10.0.0.1:1234", token: "old" }, 10);
+    ///
+    /// let new = Node { address: "10.0.0.1:1234", token: "new" };
+    /// assert_eq!(ring.replace(&old, new), Ok(Some(old)));
+    /// assert_eq!(ring.get("Some key").map(|node| node.token), Some("new"));
+    ///
+    /// let other = Node { address: "10.0.0.2:1234", token: "new" };
+    /// assert!(ring.replace(&Node { address: "10.0.0.1:1234", token: "new" }, other).is_err());
     /// ```
-    pub fn iter(&self) -> Iter<'_, N> {
-        let mut map: Vec<_> = self
-            .virtual_nodes
-            .values()
-            .map(|node| {
-                let mut hasher = self.hash_builder.build_hasher();
-                node.node.hash(&mut hasher);
-                (hasher.finish(), (&node.node, node.weight.get()))
+    pub fn replace(&mut self, old_node: &N, new_node: N) -> Result<Option<N>, N> {
+        let Some(old_master_node) = self.get_master_node(old_node).cloned() else {
+            return Err(new_node);
+        };
+        let requested_weight = old_master_node.requested_weight;
+        if self.compute_virtual_node_hashes(&old_master_node.node, requested_weight)
+            != self.compute_virtual_node_hashes(&new_node, requested_weight)
+        {
+            return Err(new_node);
+        }
+        let new_master_node = Arc::new(MasterNode {
+            node: new_node,
+            id: old_master_node.id,
+            weight: old_master_node.weight,
+            requested_weight,
+            reassigned_positions: old_master_node.reassigned_positions.clone(),
+        });
+        for position in self.master_node_positions(&old_master_node) {
+            if self.is_owned_by(&position, &old_master_node) {
+                self.virtual_nodes.insert(position, new_master_node.clone());
+            }
+        }
+        self.version += 1;
+        self.unindex_master_node(&old_master_node);
+        self.index_master_node(&new_master_node);
+        Ok(Arc::try_unwrap(old_master_node)
+            .ok()
+            .map(|master_node| master_node.node))
+    }
+
+    /// Changes the weights of all nodes to the weights returned by `new_weight`, like calling [upsert](HashRing::upsert)
+    /// for every node.
+    ///
+    /// Only the virtual nodes making up the difference between the old and the new weight of a node are added or removed,
+    /// so keys routed to a node whose weight merely changed are very likely still routed to it.
+    /// Nodes with a new weight of 0 are removed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut ring: HashRing<&str, _> = HashRing::default();
+    ///
+    /// ring.insert("10.0.0.1:1234", 10);
+    /// ring.insert("10.0.0.2:1234", 10);
+    /// ring.insert("10.0.0.3:1234", 10);
+    ///
+    /// ring.reweight_all(|node| match *node {
+    ///     "10.0.0.1:1234" => 20,
+    ///     "10.0.0.2:1234" => 0,
+    ///     _ => 10,
+    /// });
+    /// assert_eq!(ring.len(), 2);
+    /// assert_eq!(ring.to_weights_map().get("10.0.0.1:1234"), Some(&20));
+    /// ```
+    pub fn reweight_all<F>(&mut self, new_weight: F)
+    where
+        F: Fn(&N) -> u64,
+        N: Clone,
+    {
+        let new_weights = self
+            .master_nodes()
+            .into_iter()
+            .filter_map(|master_node| {
+                let weight = new_weight(&master_node.node);
+                (weight != master_node.requested_weight.get())
+                    .then(|| (master_node.node.clone(), weight))
             })
             .collect::<Vec<_>>();
-        map.sort_unstable_by_key(|n| n.0);
-        map.dedup_by(|x, y| x.0 == y.0);
+        for (node, weight) in new_weights {
+            self.upsert(node, weight);
+        }
+    }
+
+    /// Computes the positions of the virtual nodes `0..weight` of the node according to the collision policy,
+    /// together with the identifiers and positions of all virtual nodes not placed at their computed position.
+    ///
+    /// If the node is already part of the ring, the positions of its `existing` virtual nodes are kept.
+    /// Positions owned by the `vacated` master node are considered free.
+    fn virtual_node_positions(
+        &self,
+        node: &N,
+        weight: NonZeroU64,
+        existing: Option<&Arc<MasterNode<N>>>,
+        vacated: Option<*const MasterNode<N>>,
+    ) -> (HashSet<u64>, Vec<(u64, u64)>) {
+        if self.collision_policy == CollisionPolicy::Replace {
+            return (self.compute_virtual_node_hashes(node, weight), Vec::new());
+        }
+        let mut positions = HashSet::new();
+        let mut reassigned_positions = Vec::new();
+        for virtual_node_identifier in 0..weight.get() {
+            let computed_position = self.virtual_node_hash(node, virtual_node_identifier);
+            let existing_position = existing
+                .filter(|master_node| virtual_node_identifier < master_node.requested_weight.get())
+                .map(|master_node| {
+                    (
+                        master_node,
+                        master_node.position(virtual_node_identifier, computed_position),
+                    )
+                })
+                .filter(|(master_node, position)| {
+                    self.is_owned_by(position, master_node) && !positions.contains(position)
+                })
+                .map(|(_, position)| position);
+            let position = existing_position.unwrap_or_else(|| {
+                let mut position = computed_position;
+                // Probe forward until neither this nor any other node occupies the position
+                while positions.contains(&position)
+                    || self
+                        .virtual_nodes
+                        .get(&position)
+                        .is_some_and(|owner| Some(Arc::as_ptr(owner)) != vacated)
+                {
+                    position = position.wrapping_add(1);
+                }
+                position
+            });
+            if position != computed_position {
+                reassigned_positions.push((virtual_node_identifier, position));
+            }
+            positions.insert(position);
+        }
+        (positions, reassigned_positions)
+    }
+
+    fn insert_master_node(
+        &mut self,
+        node: N,
+        requested_weight: NonZeroU64,
+        virtual_node_hashes: HashSet<u64>,
+        reassigned_positions: Vec<(u64, u64)>,
+        replaced_master_node: Option<&Arc<MasterNode<N>>>,
+    ) -> Arc<MasterNode<N>> {
+        let weight = virtual_node_hashes.len() as u64;
+        if let Some(collision_callback) = self
+            .collision_callback
+            .as_ref()
+            .filter(|_| weight < requested_weight.get())
+        {
+            collision_callback(&node, requested_weight.get(), weight);
+        }
+        let master_node = Arc::new(MasterNode {
+            id: self.virtual_node_hash(&node, 0),
+            node,
+            // It's guaranteed that at least one element is present
+            weight: NonZeroU64::new(virtual_node_hashes.len() as u64).unwrap(),
+            requested_weight,
+            reassigned_positions,
+        });
+
+        let mut evicted_master_nodes = Vec::new();
+        for virtual_node_hash in virtual_node_hashes.into_iter() {
+            if let Some(evicted_master_node) = self
+                .virtual_nodes
+                .insert(virtual_node_hash, master_node.clone())
+            {
+                evicted_master_nodes.push(evicted_master_node);
+            }
+        }
+        self.node_count = self.node_count + 1
+            - self.evict_master_nodes(evicted_master_nodes, replaced_master_node);
+        self.version += 1;
+        self.index_master_node(&master_node);
+        master_node
+    }
+
+    /// Reports the evicted master nodes to the collision callback with the number of virtual nodes they have left,
+    /// returning the number of those which lost their last virtual node and are no longer part of the ring.
+    /// The master node replaced by a new one of the same node is not reported.
+    fn evict_master_nodes(
+        &self,
+        mut evicted_master_nodes: Vec<Arc<MasterNode<N>>>,
+        replaced_master_node: Option<&Arc<MasterNode<N>>>,
+    ) -> usize {
+        evicted_master_nodes
+            .sort_unstable_by_key(|master_node| (master_node.id, Arc::as_ptr(master_node)));
+        evicted_master_nodes.dedup_by(|x, y| Arc::ptr_eq(x, y));
+        evicted_master_nodes
+            .iter()
+            .filter(|evicted_master_node| {
+                let weight = self
+                    .master_node_positions(evicted_master_node)
+                    .iter()
+                    .filter(|position| self.is_owned_by(position, evicted_master_node))
+                    .count() as u64;
+                if let Some(collision_callback) = self.collision_callback.as_ref().filter(|_| {
+                    replaced_master_node
+                        .is_none_or(|replaced| !Arc::ptr_eq(replaced, evicted_master_node))
+                }) {
+                    collision_callback(
+                        &evicted_master_node.node,
+                        evicted_master_node.requested_weight.get(),
+                        weight,
+                    );
+                }
+                weight == 0
+            })
+            .count()
+    }
+
+    fn budgeted_weight(&self, node: &N, weight: u64) -> u64 {
+        match self.total_virtual_node_budget {
+            Some(_) => {
+                let current_weight = self
+                    .get_master_node(node)
+                    .map_or(0, |master_node| master_node.weight.get());
+                weight.min(self.remaining_budget() + current_weight)
+            }
+            None => weight,
+        }
+    }
+
+    fn is_owned_by(&self, virtual_node_hash: &u64, master_node: &Arc<MasterNode<N>>) -> bool {
+        self.virtual_nodes
+            .get(virtual_node_hash)
+            .is_some_and(|owner| Arc::ptr_eq(owner, master_node))
+    }
+
+    /// Returns a reference to the node of the first virtual node at or after the hash of the key, wrapping around
+    /// to the first virtual node of the ring.
+    ///
+    /// The selection is fully determined by the positions of the virtual nodes:
+    /// * A virtual node positioned exactly at the hash of the key is selected, i.e. every virtual node owns the keys
+    ///   between the preceding virtual node (exclusive) and itself (inclusive).
+    /// * Every position is owned by exactly one node. If virtual nodes of several nodes collide, the position belongs
+    ///   to the node inserted last with the [Replace](CollisionPolicy::Replace) policy, and to the node inserted first
+    ///   with the [Reassign](CollisionPolicy::Reassign) policy, which moves the virtual nodes of later nodes to the next
+    ///   free position. [deduplicate_positions](HashRing::deduplicate_positions) and [build_from](HashRing::build_from)
+    ///   give the position to the node with the lowest [node_base_hash](HashRing::node_base_hash).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut ring: HashRing<&str, _> = HashRing::default();
+    ///
+    /// ring.insert("10.0.0.1:1234", 1);
+    /// assert_eq!(ring.get("Some key"), Some(&"10.0.0.1:1234"));
+    /// assert_eq!(ring.get(12345), Some(&"10.0.0.1:1234"));
+    /// ```
+    ///
+    /// A virtual node at the hash of the key owns the key:
+    ///
+    /// ```
+    /// use std::hash::BuildHasher;
+    /// use hulahoop::HashRing;
+    ///
+    /// let hash_builder = std::collections::hash_map::RandomState::new();
+    /// let key_hash = hash_builder.hash_one("Some key");
+    /// let mut ring = HashRing::with_virtual_node_fn(hash_builder, move |_, node: &&str, _| match *node {
+    ///     "10.0.0.1:1234" => key_hash.wrapping_sub(1),
+    ///     _ => key_hash,
+    /// });
+    ///
+    /// ring.insert("10.0.0.1:1234", 1);
+    /// ring.insert("10.0.0.2:1234", 1);
+    /// assert_eq!(ring.get("Some key"), Some(&"10.0.0.2:1234"));
+    /// ```
+    #[inline]
+    pub fn get<K>(&self, key: K) -> Option<&N>
+    where
+        K: Hash,
+    {
+        self.get_by_hash(self.hash_builder.hash_one(key))
+    }
+
+    /// Returns references to the nodes of all keys, in the order of the keys, like calling [get](HashRing::get)
+    /// for every key.
+    ///
+    /// All keys are hashed and sorted first and then resolved in a single ordered pass over the virtual nodes,
+    /// which is faster than looking up every key on its own for large batches of keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut ring: HashRing<&str, _> = HashRing::default();
+    ///
+    /// ring.insert("10.0.0.1:1234", 10);
+    /// ring.insert("10.0.0.2:1234", 10);
+    ///
+    /// let keys = ["Some key", "Another key"];
+    /// assert_eq!(ring.get_many(keys), keys.map(|key| ring.get(key)));
+    /// ```
+    pub fn get_many<K, I>(&self, keys: I) -> Vec<Option<&N>>
+    where
+        K: Hash,
+        I: IntoIterator<Item = K>,
+    {
+        let mut key_hashes = keys
+            .into_iter()
+            .map(|key| self.hash_builder.hash_one(key))
+            .enumerate()
+            .map(|(index, key_hash)| (key_hash, index))
+            .collect::<Vec<_>>();
+        key_hashes.sort_unstable();
+
+        let mut nodes = vec![None; key_hashes.len()];
+        let first_node = self
+            .virtual_nodes
+            .values()
+            .next()
+            .map(|master_node| &master_node.node);
+        let mut virtual_nodes = self.virtual_nodes.iter().peekable();
+        for (key_hash, index) in key_hashes {
+            while virtual_nodes
+                .next_if(|(position, _)| **position < key_hash)
+                .is_some()
+            {}
+            nodes[index] = virtual_nodes
+                .peek()
+                .map(|(_, master_node)| &master_node.node)
+                // Keys beyond the last virtual node wrap around to the first one
+                .or(first_node);
+        }
+        nodes
+    }
+
+    #[inline]
+    pub(crate) fn get_by_hash(&self, key_hash: u64) -> Option<&N> {
+        self.get_master_node_for_key_hash(key_hash)
+            .map(|master_node| master_node.node.borrow())
+    }
+
+    #[inline]
+    fn get_master_node_for_key_hash(&self, key_hash: u64) -> Option<&Arc<MasterNode<N>>> {
+        match self.virtual_nodes.range(key_hash..).next() {
+            Some((_, virtual_node)) => Some(virtual_node),
+            None => {
+                // We couldn't find any node greater than the key hash,
+                // so let's loop around and take the first one in the HashRing if available.
+                self.virtual_nodes
+                    .iter()
+                    .next()
+                    .map(|(_, virtual_node)| virtual_node)
+            }
+        }
+    }
+
+    /// Returns a reference to the node with a hash closest to the hash of the key together with its actual weight.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut ring: HashRing<&str, _> = HashRing::default();
+    /// assert_eq!(ring.get_with_weight("Some key"), None);
+    ///
+    /// ring.insert("10.0.0.1:1234", 10);
+    /// assert_eq!(ring.get_with_weight("Some key"), Some((&"10.0.0.1:1234", 10)));
+    /// ```
+    pub fn get_with_weight<K>(&self, key: K) -> Option<(&N, u64)>
+    where
+        K: Hash,
+    {
+        self.get_master_node_for_key_hash(self.hash_builder.hash_one(key))
+            .map(|master_node| (master_node.node.borrow(), master_node.weight.get()))
+    }
+
+    /// Returns a reference to the node with a hash closest to the hash of the key.
+    ///
+    /// # Errors
+    ///
+    /// Returns [EmptyRing](HulahoopError::EmptyRing) if the ring does not contain any nodes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::{HashRing, HulahoopError};
+    ///
+    /// let mut ring: HashRing<&str, _> = HashRing::default();
+    /// assert_eq!(ring.try_get("Some key"), Err(HulahoopError::EmptyRing));
+    ///
+    /// ring.insert("10.0.0.1:1234", 1);
+    /// assert_eq!(ring.try_get("Some key"), Ok(&"10.0.0.1:1234"));
+    /// ```
+    pub fn try_get<K>(&self, key: K) -> Result<&N, HulahoopError>
+    where
+        K: Hash,
+    {
+        self.get(key).ok_or(HulahoopError::EmptyRing)
+    }
+
+    /// Returns a clone of the node with a hash closest to the hash of the key.
+    ///
+    /// Unlike [get](HashRing::get), the result does not borrow the ring, so the ring can be modified while holding it,
+    /// e.g. across an `.await`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut ring: HashRing<String, _> = HashRing::default();
+    ///
+    /// ring.insert("10.0.0.1:1234".to_string(), 1);
+    /// let node = ring.get_cloned("Some key");
+    /// ring.remove(&"10.0.0.1:1234".to_string());
+    /// assert_eq!(node, Some("10.0.0.1:1234".to_string()));
+    /// ```
+    pub fn get_cloned<K>(&self, key: K) -> Option<N>
+    where
+        K: Hash,
+        N: Clone,
+    {
+        self.get(key).cloned()
+    }
+
+    /// Returns references to up to `n` distinct nodes, walking the ring clockwise starting with the node of the key.
+    ///
+    /// The first node is the one returned by [get](HashRing::get). Virtual nodes of already selected nodes are skipped.
+    /// Fewer than `n` nodes are returned if the ring contains fewer nodes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut ring: HashRing<&str, _> = HashRing::default();
+    ///
+    /// ring.insert("10.0.0.1:1234", 10);
+    /// ring.insert("10.0.0.2:1234", 10);
+    /// ring.insert("10.0.0.3:1234", 10);
+    ///
+    /// let nodes = ring.get_n("Some key", 2);
+    /// assert_eq!(nodes.len(), 2);
+    /// assert_eq!(nodes.first().copied(), ring.get("Some key"));
+    /// assert_eq!(ring.get_n("Some key", 5).len(), 3);
+    /// ```
+    pub fn get_n<K>(&self, key: K, n: usize) -> Vec<&N>
+    where
+        K: Hash,
+    {
+        self.distinct_nodes_from(self.hash_builder.hash_one(key))
+            .take(n)
+            .collect()
+    }
+
+    /// Returns references to up to `count` distinct nodes which are not `down`, walking the ring clockwise
+    /// starting with the node of the key.
+    ///
+    /// This is [get_n](HashRing::get_n) skipping all nodes in `down`, e.g. to build a read quorum during a partial outage.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::HashSet;
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut ring: HashRing<&str, _> = HashRing::default();
+    ///
+    /// ring.insert("10.0.0.1:1234", 10);
+    /// ring.insert("10.0.0.2:1234", 10);
+    /// ring.insert("10.0.0.3:1234", 10);
+    ///
+    /// let primary = *ring.get("Some key").unwrap();
+    /// let read_set = ring.get_read_set("Some key", 2, &HashSet::from([&primary]));
+    /// assert_eq!(read_set.len(), 2);
+    /// assert!(!read_set.contains(&&primary));
+    /// ```
+    pub fn get_read_set<K>(&self, key: K, count: usize, down: &HashSet<&N>) -> Vec<&N>
+    where
+        K: Hash,
+        N: Eq,
+    {
+        self.distinct_nodes_from(self.hash_builder.hash_one(key))
+            .filter(|node| !down.contains(node))
+            .take(count)
+            .collect()
+    }
+
+    /// Returns a reference to the first node accepted by `predicate`, walking the ring clockwise starting with the
+    /// node of the key.
+    ///
+    /// This skips nodes without removing them from the ring, e.g. nodes a health checker currently considers down.
+    /// Returns `None` if no node is accepted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut ring: HashRing<&str, _> = HashRing::default();
+    ///
+    /// ring.insert("10.0.0.1:1234", 10);
+    /// ring.insert("10.0.0.2:1234", 10);
+    ///
+    /// let primary = *ring.get("Some key").unwrap();
+    /// let fallback = ring.get_filtered("Some key", |node| *node != primary);
+    /// assert!(fallback.is_some());
+    /// assert_ne!(fallback, Some(&primary));
+    /// assert_eq!(ring.get_filtered("Some key", |_| false), None);
+    /// ```
+    pub fn get_filtered<K, F>(&self, key: K, predicate: F) -> Option<&N>
+    where
+        K: Hash,
+        F: FnMut(&N) -> bool,
+    {
+        self.get_n_filtered(key, 1, predicate).pop()
+    }
+
+    /// Returns references to up to `n` distinct nodes accepted by `predicate`, walking the ring clockwise starting
+    /// with the node of the key.
+    ///
+    /// This is [get_n](HashRing::get_n) skipping all nodes not accepted by `predicate`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut ring: HashRing<&str, _> = HashRing::default();
+    ///
+    /// ring.insert("10.0.0.1:1234", 10);
+    /// ring.insert("10.0.0.2:1234", 10);
+    /// ring.insert("10.0.0.3:1234", 10);
+    ///
+    /// let nodes = ring.get_n_filtered("Some key", 3, |node| *node != "10.0.0.2:1234");
+    /// assert_eq!(nodes.len(), 2);
+    /// assert!(!nodes.contains(&&"10.0.0.2:1234"));
+    /// ```
+    pub fn get_n_filtered<K, F>(&self, key: K, n: usize, mut predicate: F) -> Vec<&N>
+    where
+        K: Hash,
+        F: FnMut(&N) -> bool,
+    {
+        self.distinct_nodes_from(self.hash_builder.hash_one(key))
+            .filter(|node| predicate(node))
+            .take(n)
+            .collect()
+    }
+
+    fn distinct_nodes_from(&self, key_hash: u64) -> impl Iterator<Item = &N> {
+        self.distinct_master_nodes_from(key_hash)
+            .map(|master_node| master_node.node.borrow())
+    }
+
+    fn distinct_master_nodes_from(
+        &self,
+        key_hash: u64,
+    ) -> impl Iterator<Item = &Arc<MasterNode<N>>> {
+        let mut seen_master_nodes = HashSet::new();
+        self.virtual_nodes
+            .range(key_hash..)
+            // Loop around to the start of the ring
+            .chain(self.virtual_nodes.range(..key_hash))
+            .map(|(_, master_node)| master_node)
+            .filter(move |master_node| seen_master_nodes.insert(Arc::as_ptr(master_node)))
+    }
+
+    /// Returns a reference to a random node among the first `spread` distinct nodes of the key.
+    ///
+    /// The candidates are the nodes returned by [get_n](HashRing::get_n), each picked with a probability proportional
+    /// to its weight. `random` must return uniformly distributed numbers. It is a closure rather than an
+    /// `rng: &mut impl RngCore`, so the crate does not depend on `rand`; pass e.g. `&mut || rng.next_u64()`.
+    ///
+    /// This sacrifices consistency to spread the load of a single extremely popular key over several nodes.
+    /// It should only be used for keys known to be hot, all other keys should be routed with [get](HashRing::get).
+    /// A `spread` of 0 or 1 is equivalent to [get](HashRing::get).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut ring: HashRing<&str, _> = HashRing::default();
+    ///
+    /// ring.insert("10.0.0.1:1234", 10);
+    /// ring.insert("10.0.0.2:1234", 10);
+    /// ring.insert("10.0.0.3:1234", 10);
+    ///
+    /// let mut state = 42u64;
+    /// let mut random = || {
+    ///     state ^= state << 13;
+    ///     state ^= state >> 7;
+    ///     state ^= state << 17;
+    ///     state
+    /// };
+    /// let node = ring.get_jittered("Hot key", 2, &mut random).unwrap();
+    /// assert!(ring.get_n("Hot key", 2).contains(&node));
+    /// ```
+    pub fn get_jittered<K, R>(&self, key: K, spread: usize, random: &mut R) -> Option<&N>
+    where
+        K: Hash,
+        R: FnMut() -> u64,
+    {
+        let candidates = self
+            .distinct_master_nodes_from(self.hash_builder.hash_one(key))
+            .take(spread.max(1))
+            .collect::<Vec<_>>();
+        let total_weight = candidates
+            .iter()
+            .map(|master_node| master_node.weight.get())
+            .sum::<u64>();
+        if candidates.len() < 2 {
+            return candidates
+                .first()
+                .map(|master_node| master_node.node.borrow());
+        }
+        // Scales the random number down to `0..total_weight` without the bias of a modulo
+        let mut remaining = ((u128::from(random()) * u128::from(total_weight)) >> 64) as u64;
+        candidates
+            .into_iter()
+            .find(|master_node| {
+                let found = remaining < master_node.weight.get();
+                remaining = remaining.saturating_sub(master_node.weight.get());
+                found
+            })
+            .map(|master_node| master_node.node.borrow())
+    }
+
+    /// Returns references to the replicas of the key, i.e. [get_n](HashRing::get_n) with the configured
+    /// [replication factor](HashRingBuilder::replication_factor).
+    ///
+    /// Returns all nodes if the ring contains fewer nodes than the replication factor.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::num::NonZeroUsize;
+    /// use hulahoop::HashRingBuilder;
+    ///
+    /// let mut ring = HashRingBuilder::default()
+    ///     .replication_factor(NonZeroUsize::new(2).unwrap())
+    ///     .build();
+    ///
+    /// ring.insert("10.0.0.1:1234", 10);
+    /// ring.insert("10.0.0.2:1234", 10);
+    /// ring.insert("10.0.0.3:1234", 10);
+    ///
+    /// assert_eq!(ring.get_replicas("Some key"), ring.get_n("Some key", 2));
+    /// ```
+    pub fn get_replicas<K>(&self, key: K) -> Vec<&N>
+    where
+        K: Hash,
+    {
+        self.get_n(key, self.replication_factor.get())
+    }
+
+    /// Returns the number of replicas returned by [get_replicas](HashRing::get_replicas).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRing;
+    ///
+    /// let ring: HashRing<&str, _> = HashRing::default();
+    /// assert_eq!(ring.replication_factor().get(), 1);
+    /// ```
+    pub fn replication_factor(&self) -> NonZeroUsize {
+        self.replication_factor
+    }
+
+    /// Returns the [CollisionPolicy](CollisionPolicy) of the ring.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::{CollisionPolicy, HashRing};
+    ///
+    /// let ring: HashRing<&str, _> = HashRing::default();
+    /// assert_eq!(ring.collision_policy(), CollisionPolicy::Replace);
+    /// ```
+    pub fn collision_policy(&self) -> CollisionPolicy {
+        self.collision_policy
+    }
+
+    /// Returns a reference to the node closest to the hash of the key mixed with `salt`.
+    ///
+    /// The same `salt` and key are always routed to the same node, but different salts route the same key
+    /// independently of each other. This gives every tenant of a shared ring its own view of the ring, so hot keys of
+    /// different tenants do not pile up on the same node.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut ring: HashRing<&str, _> = HashRing::default();
+    ///
+    /// ring.insert("10.0.0.1:1234", 10);
+    /// ring.insert("10.0.0.2:1234", 10);
+    ///
+    /// let tenant = 42;
+    /// assert_eq!(ring.get_salted(tenant, "Some key"), ring.get_salted(tenant, "Some key"));
+    /// ```
+    pub fn get_salted<K>(&self, salt: u64, key: K) -> Option<&N>
+    where
+        K: Hash,
+    {
+        let key_hash = self.hash_builder.hash_one(key);
+        self.get_by_hash(self.hash_builder.hash_one((salt, key_hash)))
+    }
+
+    /// Returns a reference to the `previous` node if it is still part of the ring, otherwise the node of the key.
+    ///
+    /// This keeps keys on the node that served them before for as long as possible, e.g. for sticky sessions,
+    /// even if consistent hashing would remap them after nodes were added.
+    /// Returns `None` only if the ring is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut ring: HashRing<&str, _> = HashRing::default();
+    ///
+    /// ring.insert("10.0.0.1:1234", 10);
+    /// ring.insert("10.0.0.2:1234", 10);
+    ///
+    /// assert_eq!(ring.get_sticky("Some key", Some(&"10.0.0.2:1234")), Some(&"10.0.0.2:1234"));
+    /// assert_eq!(ring.get_sticky("Some key", Some(&"10.0.0.3:1234")), ring.get("Some key"));
+    /// assert_eq!(ring.get_sticky("Some key", None), ring.get("Some key"));
+    /// ```
+    pub fn get_sticky<K>(&self, key: K, previous: Option<&N>) -> Option<&N>
+    where
+        K: Hash,
+        N: Eq,
+    {
+        previous
+            .and_then(|previous| self.get_master_node(previous))
+            .filter(|master_node| Some(&master_node.node) == previous)
+            .map(|master_node| &master_node.node)
+            .or_else(|| self.get(key))
+    }
+
+    /// Returns a reference to the node closest to the hash of the key, ignoring virtual nodes at the `excluded` positions.
+    ///
+    /// This behaves as if the virtual nodes at the `excluded` positions were removed from the ring,
+    /// without modifying it. It can be used to simulate the failure of specific virtual nodes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::HashSet;
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut ring: HashRing<&str, _> = HashRing::default();
+    ///
+    /// ring.insert("10.0.0.1:1234", 1);
+    /// ring.insert("10.0.0.2:1234", 1);
+    ///
+    /// let positions = ring.ownership_map().iter().map(|arc| arc.1).collect::<HashSet<_>>();
+    /// assert_eq!(ring.get_excluding_positions("Some key", &HashSet::new()), ring.get("Some key"));
+    /// assert_eq!(ring.get_excluding_positions("Some key", &positions), None);
+    /// ```
+    pub fn get_excluding_positions<K>(&self, key: K, excluded: &HashSet<u64>) -> Option<&N>
+    where
+        K: Hash,
+    {
+        let key_hash = self.hash_builder.hash_one(key);
+        self.virtual_nodes
+            .range(key_hash..)
+            // Loop around if no node after the key hash is left
+            .chain(self.virtual_nodes.range(..key_hash))
+            .find(|(position, _)| !excluded.contains(position))
+            .map(|(_, virtual_node)| virtual_node.node.borrow())
+    }
+
+    /// Returns an iterator over all distinct nodes, walking the ring counter-clockwise starting with the node of the key.
+    ///
+    /// The first node is the one returned by [get](HashRing::get). Afterwards, the nodes of the preceding virtual nodes
+    /// are returned, wrapping around at the start of the ring. Every node is returned once.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut ring: HashRing<&str, _> = HashRing::default();
+    ///
+    /// ring.insert("10.0.0.1:1234", 10);
+    /// ring.insert("10.0.0.2:1234", 10);
+    ///
+    /// let nodes = ring.get_reverse_cycle("Some key").collect::<Vec<_>>();
+    /// assert_eq!(nodes.len(), 2);
+    /// assert_eq!(nodes.first().copied(), ring.get("Some key"));
+    /// ```
+    pub fn get_reverse_cycle<K>(&self, key: K) -> impl Iterator<Item = &N> + '_
+    where
+        K: Hash,
+    {
+        self.get_reverse_cycle_by_hash(self.hash_builder.hash_one(key))
+    }
+
+    fn get_reverse_cycle_by_hash(&self, key_hash: u64) -> impl Iterator<Item = &N> + '_ {
+        let position = self
+            .virtual_nodes
+            .range(key_hash..)
+            .next()
+            .or_else(|| self.virtual_nodes.iter().next())
+            .map(|(position, _)| *position);
+        let mut seen_master_nodes = HashSet::new();
+        position
+            .into_iter()
+            .flat_map(move |position| {
+                self.virtual_nodes
+                    .range(..=position)
+                    .rev()
+                    // Loop around to the end of the ring
+                    .chain(
+                        self.virtual_nodes
+                            .range((Bound::Excluded(position), Bound::Unbounded))
+                            .rev(),
+                    )
+            })
+            .filter(move |(_, master_node)| seen_master_nodes.insert(Arc::as_ptr(master_node)))
+            .map(|(_, master_node)| master_node.node.borrow())
+    }
+
+    /// Returns a reference to the successor of the node of the key, the first other node walking the ring clockwise.
+    ///
+    /// This is the node the key is routed to once its current node leaves the ring, i.e. the node taking over the key
+    /// during a data handoff. Returns `None` if the ring contains fewer than two nodes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut ring: HashRing<&str, _> = HashRing::default();
+    ///
+    /// ring.insert("10.0.0.1:1234", 10);
+    /// assert_eq!(ring.next_node_after("Some key"), None);
+    ///
+    /// ring.insert("10.0.0.2:1234", 10);
+    /// let successor = *ring.next_node_after("Some key").unwrap();
+    /// let node = *ring.get("Some key").unwrap();
+    /// ring.remove(&node);
+    /// assert_eq!(ring.get("Some key"), Some(&successor));
+    /// ```
+    pub fn next_node_after<K>(&self, key: K) -> Option<&N>
+    where
+        K: Hash,
+    {
+        self.next_node_after_hash(self.hash_builder.hash_one(key))
+    }
+
+    /// Returns a reference to the successor of the node of the already hashed key, see
+    /// [next_node_after](HashRing::next_node_after).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::hash::BuildHasher;
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut ring: HashRing<&str, _> = HashRing::default();
+    ///
+    /// ring.insert("10.0.0.1:1234", 10);
+    /// ring.insert("10.0.0.2:1234", 10);
+    ///
+    /// let key_hash = ring.hasher().hash_one("Some key");
+    /// assert_eq!(ring.next_node_after_hash(key_hash), ring.next_node_after("Some key"));
+    /// ```
+    pub fn next_node_after_hash(&self, key_hash: u64) -> Option<&N> {
+        self.distinct_nodes_from(key_hash).nth(1)
+    }
+
+    /// Returns a reference to the predecessor of the node of the key, the first other node walking the ring
+    /// counterclockwise.
+    ///
+    /// Keys between the last virtual node of the predecessor and the key are routed to the same node as the key,
+    /// so this marks where the range of the node containing the key begins.
+    /// Returns `None` if the ring contains fewer than two nodes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut ring: HashRing<&str, _> = HashRing::default();
+    ///
+    /// ring.insert("10.0.0.1:1234", 10);
+    /// assert_eq!(ring.prev_node_before("Some key"), None);
+    ///
+    /// ring.insert("10.0.0.2:1234", 10);
+    /// assert!(ring.prev_node_before("Some key").is_some());
+    /// assert_ne!(ring.prev_node_before("Some key"), ring.get("Some key"));
+    /// ```
+    pub fn prev_node_before<K>(&self, key: K) -> Option<&N>
+    where
+        K: Hash,
+    {
+        self.prev_node_before_hash(self.hash_builder.hash_one(key))
+    }
+
+    /// Returns a reference to the predecessor of the node of the already hashed key, see
+    /// [prev_node_before](HashRing::prev_node_before).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::hash::BuildHasher;
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut ring: HashRing<&str, _> = HashRing::default();
+    ///
+    /// ring.insert("10.0.0.1:1234", 10);
+    /// ring.insert("10.0.0.2:1234", 10);
+    ///
+    /// let key_hash = ring.hasher().hash_one("Some key");
+    /// assert_eq!(ring.prev_node_before_hash(key_hash), ring.prev_node_before("Some key"));
+    /// ```
+    pub fn prev_node_before_hash(&self, key_hash: u64) -> Option<&N> {
+        self.get_reverse_cycle_by_hash(key_hash).nth(1)
+    }
+
+    /// Returns `true` if `self` and `other` route every key in `keys` to the same node.
+    ///
+    /// This is useful to verify that a new ring places keys exactly like an old one, e.g. after a config change.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut ring: HashRing<&str, _> = HashRing::default();
+    /// ring.insert("10.0.0.1:1234", 10);
+    /// ring.insert("10.0.0.2:1234", 10);
+    ///
+    /// let mut other: HashRing<&str, _> = HashRing::default();
+    /// other.insert("10.0.0.1:1234", 10);
+    ///
+    /// assert!(ring.agrees_with(&ring, &["Some key", "Another key"]));
+    /// assert!(!ring.agrees_with(&other, &["Some key", "Another key"]));
+    /// ```
+    pub fn agrees_with<K>(&self, other: &HashRing<N, B>, keys: &[K]) -> bool
+    where
+        K: Hash,
+        N: PartialEq,
+    {
+        keys.iter().all(|key| self.get(key) == other.get(key))
+    }
+
+    /// Returns the number of nodes in the Hashring.
+    ///
+    /// It does not return the number of virtual nodes (as specified with `weight` in the [insert](HashRing::insert) method).
+    /// The number of nodes is tracked as the ring changes, so this takes constant time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut ring: HashRing<&str, _> = HashRing::default();
+    ///
+    /// ring.insert("10.0.0.1:1234", 10);
+    /// ring.insert("10.0.0.2:1234", 10);
+    /// assert_eq!(ring.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.node_count
+    }
+
+    /// Returns the sum of the actual weights of all nodes.
+    ///
+    /// The actual weight of a node may be lower than the `weight` provided when inserting it in case of hash collisions.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut ring: HashRing<&str, _> = HashRing::default();
+    /// assert_eq!(ring.total_weight(), 0);
+    ///
+    /// ring.insert("10.0.0.1:1234", 10);
+    /// ring.insert("10.0.0.2:1234", 5);
+    /// assert_eq!(ring.total_weight(), 15);
+    /// ```
+    pub fn total_weight(&self) -> u64 {
+        self.master_nodes()
+            .into_iter()
+            .map(|master_node| master_node.weight.get())
+            .sum()
+    }
+
+    /// Returns the number of virtual nodes the node currently owns, or 0 if it is not part of the ring.
+    ///
+    /// Contrary to the weight returned by [iter](HashRing::iter), this also accounts for virtual nodes taken over by
+    /// nodes inserted later, so comparing it with the requested weight shows how much collisions eroded the weight.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut ring: HashRing<&str, _> = HashRing::default();
+    /// assert_eq!(ring.virtual_node_count(&"10.0.0.1:1234"), 0);
+    ///
+    /// ring.insert("10.0.0.1:1234", 10);
+    /// assert_eq!(ring.virtual_node_count(&"10.0.0.1:1234"), 10);
+    /// ```
+    pub fn virtual_node_count(&self, node: &N) -> u64 {
+        self.positions_of_iter(node).count() as u64
+    }
+
+    /// Returns the number of virtual nodes of all nodes, i.e. the number of positions on the ring.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut ring: HashRing<&str, _> = HashRing::default();
+    ///
+    /// ring.insert("10.0.0.1:1234", 10);
+    /// ring.insert("10.0.0.2:1234", 5);
+    /// assert_eq!(ring.total_virtual_nodes(), 15);
+    /// ```
+    pub fn total_virtual_nodes(&self) -> usize {
+        self.virtual_nodes.len()
+    }
+
+    /// Returns `true` if every key is routed to some node, i.e. the ring contains at least one virtual node.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut ring: HashRing<&str, _> = HashRing::default();
+    /// assert!(!ring.covers_all());
+    ///
+    /// ring.insert("10.0.0.1:1234", 10);
+    /// assert!(ring.covers_all());
+    /// ```
+    pub fn covers_all(&self) -> bool {
+        !self.virtual_nodes.is_empty()
+    }
+
+    /// Returns references to all nodes with a positive weight which do not own any of their own virtual node positions.
+    ///
+    /// Keys can never be routed to such nodes by their own positions. This is always empty for a consistent ring
+    /// and can be used to verify the ring after surgical modifications.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut ring: HashRing<&str, _> = HashRing::default();
+    ///
+    /// ring.insert("10.0.0.1:1234", 10);
+    /// ring.insert("10.0.0.2:1234", 10);
+    /// ring.deduplicate_positions();
+    /// assert!(ring.unreachable_nodes().is_empty());
+    /// ```
+    pub fn unreachable_nodes(&self) -> Vec<&N> {
+        self.master_nodes()
+            .into_iter()
+            .filter(|master_node| {
+                !self
+                    .master_node_positions(master_node)
+                    .iter()
+                    .any(|virtual_node_hash| self.is_owned_by(virtual_node_hash, master_node))
+            })
+            .map(|master_node| master_node.node.borrow())
+            .collect()
+    }
+
+    /// Suggests new weights for all nodes whose share of the key space deviates from their share of the total weight
+    /// by more than `target_tolerance`, relative to the latter.
+    ///
+    /// The share of a node is the length of all arcs of the ring ending at its virtual nodes. Since the share of
+    /// a node grows roughly proportionally with its weight, the suggested weight is the current weight scaled by the
+    /// ratio of the ideal to the current share. Only nodes whose suggested weight differs from their current weight
+    /// are returned, ordered by their [node_base_hash](HashRing::node_base_hash).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut ring: HashRing<&str, _> = HashRing::default();
+    ///
+    /// ring.insert("10.0.0.1:1234", 10);
+    /// ring.insert("10.0.0.2:1234", 10);
+    /// for (node, weight) in ring.suggest_rebalance(0.1) {
+    ///     assert!(ring.iter().any(|(other, current_weight)| other == node && current_weight != weight));
+    /// }
+    /// ```
+    pub fn suggest_rebalance(&self, target_tolerance: f64) -> Vec<(&N, u64)> {
+        let total_weight = self.total_weight() as f64;
+        let key_space_shares = self.key_space_shares();
+        let mut suggestions = self
+            .master_nodes()
+            .into_iter()
+            .filter_map(|master_node| {
+                let weight = master_node.weight.get();
+                let ideal_share = weight as f64 / total_weight;
+                let share = key_space_shares[&Arc::as_ptr(master_node)] as f64 / KEY_SPACE as f64;
+                if (share / ideal_share - 1.0).abs() <= target_tolerance {
+                    return None;
+                }
+                let suggested_weight =
+                    ((weight as f64 * ideal_share / share).round() as u64).max(1);
+                (suggested_weight != weight)
+                    .then_some((master_node.node.borrow(), suggested_weight))
+            })
+            .collect::<Vec<_>>();
+        suggestions.sort_by_key(|(node, _)| self.node_base_hash(node));
+        suggestions
+    }
+
+    /// Returns all nodes together with their share of the key space, ordered by descending share.
+    ///
+    /// The share of a node is the length of all arcs of the ring ending at its virtual nodes, as a fraction of the
+    /// whole key space. Nodes with the same share are ordered by their [node_base_hash](HashRing::node_base_hash).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut ring: HashRing<&str, _> = HashRing::default();
+    ///
+    /// ring.insert("10.0.0.1:1234", 10);
+    /// ring.insert("10.0.0.2:1234", 10);
+    ///
+    /// let shares = ring.nodes_by_share();
+    /// assert_eq!(shares.len(), 2);
+    /// assert!(shares[0].1 >= shares[1].1);
+    /// assert!((shares[0].1 + shares[1].1 - 1.0).abs() < 1e-9);
+    /// ```
+    pub fn nodes_by_share(&self) -> Vec<(&N, f64)> {
+        let key_space_shares = self.key_space_shares();
+        let mut shares = self
+            .master_nodes()
+            .into_iter()
+            .map(|master_node| {
+                (
+                    key_space_shares[&Arc::as_ptr(master_node)],
+                    self.node_base_hash(&master_node.node),
+                    master_node.node.borrow(),
+                )
+            })
+            .collect::<Vec<_>>();
+        shares.sort_unstable_by_key(|(share, node_hash, _)| (Reverse(*share), *node_hash));
+        shares
+            .into_iter()
+            .map(|(share, _, node)| (node, share as f64 / KEY_SPACE as f64))
+            .collect()
+    }
+
+    /// Returns `true` if the load of any node exceeds its fair share of the total load by more than `tolerance`,
+    /// relative to its fair share.
+    ///
+    /// The fair share of a node is the total load of all nodes multiplied by its share of the total weight.
+    /// Unlike the [balance_factor](HashRing::balance_factor), this reflects the actual load, including skew caused by
+    /// keys of varying popularity.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut ring: HashRing<&str, _> = HashRing::default();
+    ///
+    /// ring.insert("10.0.0.1:1234", 10);
+    /// ring.insert("10.0.0.2:1234", 30);
+    ///
+    /// let load_of = |node: &&str| if *node == "10.0.0.1:1234" { 30 } else { 70 };
+    /// // The fair share of the first node is 25
+    /// assert!(ring.any_overloaded(load_of, 0.1));
+    /// assert!(!ring.any_overloaded(load_of, 0.2));
+    /// ```
+    pub fn any_overloaded<F>(&self, load_of: F, tolerance: f64) -> bool
+    where
+        F: Fn(&N) -> u64,
+    {
+        let master_nodes = self.master_nodes();
+        let loads = master_nodes
+            .iter()
+            .map(|master_node| load_of(&master_node.node))
+            .collect::<Vec<_>>();
+        let total_load = loads.iter().map(|load| *load as f64).sum::<f64>();
+        let total_weight = self.total_weight() as f64;
+        master_nodes.iter().zip(loads).any(|(master_node, load)| {
+            let fair_share = total_load * master_node.weight.get() as f64 / total_weight;
+            load as f64 > fair_share * (1.0 + tolerance)
+        })
+    }
+
+    /// Returns `true` if the ring contains no elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut ring: HashRing<&str, _> = HashRing::default();
+    /// assert!(ring.is_empty());
+    ///
+    /// ring.insert("10.0.0.1:1234", 10);
+    /// assert!(!ring.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.virtual_nodes.is_empty()
+    }
+
+    /// Returns `true` if the ring contains the specified node.
+    ///
+    /// The node may be any borrowed form of the node type, but [Hash](Hash) on the borrowed form must match
+    /// that of the node type, like for the keys of a [HashMap](HashMap). Nodes are matched by their hashes, see
+    /// [contains_node_exact](HashRing::contains_node_exact) to compare them for equality as well.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut ring: HashRing<String, _> = HashRing::default();
+    ///
+    /// ring.insert("10.0.0.1:1234".to_string(), 10);
+    /// assert_eq!(ring.contains_node("10.0.0.1:1234"), true);
+    /// assert_eq!(ring.contains_node("10.0.0.2:1234"), false);
+    /// ```
+    pub fn contains_node<Q>(&self, node: &Q) -> bool
+    where
+        N: Borrow<Q>,
+        Q: Hash + ?Sized,
+    {
+        self.find_master_node(node).is_some()
+    }
+
+    /// Returns `true` if the ring contains a node equal to the specified node.
+    ///
+    /// Unlike [contains_node](HashRing::contains_node), which only compares hashes, this also compares the node
+    /// with the node stored in the ring, so a different node with colliding hashes is not mistaken for it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut ring: HashRing<String, _> = HashRing::default();
+    ///
+    /// ring.insert("10.0.0.1:1234".to_string(), 10);
+    /// assert_eq!(ring.contains_node_exact("10.0.0.1:1234"), true);
+    /// assert_eq!(ring.contains_node_exact("10.0.0.2:1234"), false);
+    /// ```
+    pub fn contains_node_exact<Q>(&self, node: &Q) -> bool
+    where
+        N: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.find_master_node_exact(node).is_some()
+    }
+
+    /// Returns a [Weak](Weak) reference to the [MasterNode](MasterNode) of the specified node.
+    ///
+    /// Unlike a strong reference, holding a `Weak` reference does not prevent the ring from
+    /// reclaiming the owned node when it is removed. Once the node is removed, the reference can no longer be upgraded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut ring: HashRing<&str, _> = HashRing::default();
+    ///
+    /// ring.insert("10.0.0.1:1234", 10);
+    /// let weak = ring.weak_of(&"10.0.0.1:1234").unwrap();
+    /// assert_eq!(weak.upgrade().map(|master_node| master_node.weight()), Some(10));
+    ///
+    /// ring.remove(&"10.0.0.1:1234");
+    /// assert!(weak.upgrade().is_none());
+    /// ```
+    pub fn weak_of(&self, node: &N) -> Option<Weak<MasterNode<N>>> {
+        self.get_master_node(node).map(Arc::downgrade)
+    }
+
+    /// Returns the index of the node the key is routed to.
+    ///
+    /// Every node is assigned a dense index in `0..len()` in the order of their [node_base_hash](HashRing::node_base_hash),
+    /// e.g. to be used as offset into an array. Distinct nodes with the same hash are ordered by the smallest position
+    /// of their virtual nodes. The indices are stable as long as no node is inserted or removed, any change of the nodes
+    /// may renumber all of them. The indices are computed on every call, which takes time linear in the number of
+    /// virtual nodes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut ring: HashRing<&str, _> = HashRing::default();
+    /// assert_eq!(ring.get_index("Some key"), None);
+    ///
+    /// ring.insert("10.0.0.1:1234", 10);
+    /// ring.insert("10.0.0.2:1234", 10);
+    ///
+    /// let index = ring.get_index("Some key").unwrap();
+    /// assert_eq!(ring.node_at_index(index), ring.get("Some key"));
+    /// ```
+    pub fn get_index<K>(&self, key: K) -> Option<usize>
+    where
+        K: Hash,
+    {
+        let master_node = self.get_master_node_for_key_hash(self.hash_builder.hash_one(key))?;
+        self.indexed_master_nodes()
+            .into_iter()
+            .position(|indexed| Arc::ptr_eq(indexed, master_node))
+    }
+
+    /// Returns the node with the given index, see [get_index](HashRing::get_index).
+    ///
+    /// Returns `None` if the index is not smaller than the number of nodes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut ring: HashRing<&str, _> = HashRing::default();
+    ///
+    /// ring.insert("10.0.0.1:1234", 10);
+    /// assert_eq!(ring.node_at_index(0), Some(&"10.0.0.1:1234"));
+    /// assert_eq!(ring.node_at_index(1), None);
+    /// ```
+    pub fn node_at_index(&self, index: usize) -> Option<&N> {
+        self.indexed_master_nodes()
+            .get(index)
+            .map(|master_node| master_node.node.borrow())
+    }
+
+    /// Returns all master nodes ordered by their index, see [get_index](HashRing::get_index).
+    fn indexed_master_nodes(&self) -> Vec<&Arc<MasterNode<N>>> {
+        // Visiting the positions in order keeps the smallest position of every node
+        let mut smallest_positions = HashMap::new();
+        for (position, master_node) in &self.virtual_nodes {
+            smallest_positions
+                .entry(Arc::as_ptr(master_node))
+                .or_insert((*position, master_node));
+        }
+        let mut master_nodes = smallest_positions
+            .into_values()
+            .map(|(position, master_node)| {
+                (
+                    (self.node_base_hash(&master_node.node), position),
+                    master_node,
+                )
+            })
+            .collect::<Vec<_>>();
+        master_nodes.sort_unstable_by_key(|(order, _)| *order);
+        master_nodes
+            .into_iter()
+            .map(|(_, master_node)| master_node)
+            .collect()
+    }
+
+    /// Returns `true` if the hasher of the ring produces the same hash for the same input every time.
+    ///
+    /// The ring assumes that every [Hasher](Hasher) built by its [BuildHasher](BuildHasher) starts in the same state.
+    /// A `BuildHasher` returning hashers with shared or randomized state places virtual nodes and routes keys
+    /// inconsistently. This hashes a known input with several freshly built hashers and compares the results.
+    /// Rings check this with a debug assertion when inserting nodes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRing;
+    ///
+    /// let ring: HashRing<&str, _> = HashRing::default();
+    /// assert!(ring.verify_hasher_determinism());
+    /// ```
+    pub fn verify_hasher_determinism(&self) -> bool {
+        let empty_hash = self.hash_builder.build_hasher().finish();
+        let known_hash = self.hash_builder.hash_one("hulahoop");
+        (0..2).all(|_| {
+            self.hash_builder.build_hasher().finish() == empty_hash
+                && self.hash_builder.hash_one("hulahoop") == known_hash
+        })
+    }
+
+    /// Returns the hash of the node itself, computed once with the ring's hasher.
+    ///
+    /// This hash is used to order nodes, e.g. to break ties in [remove_lightest](HashRing::remove_lightest)
+    /// or [deduplicate_positions](HashRing::deduplicate_positions).
+    /// The default virtual node positions are computed by feeding the node and then the index of the virtual node
+    /// into one hasher, so they cannot be derived from this hash alone. Use [positions_of](HashRing::positions_of)
+    /// to verify the placement of a node.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::hash::BuildHasher;
+    /// use hulahoop::HashRing;
+    ///
+    /// let hash_builder = std::hash::BuildHasherDefault::<std::collections::hash_map::DefaultHasher>::default();
+    /// let ring: HashRing<&str, _> = HashRing::with_hasher(hash_builder.clone());
+    /// assert_eq!(ring.node_base_hash(&"10.0.0.1:1234"), hash_builder.hash_one(&"10.0.0.1:1234"));
+    /// ```
+    pub fn node_base_hash(&self, node: &N) -> u64 {
+        self.hash_builder.hash_one(node)
+    }
+
+    /// Returns the positions of all virtual nodes of the specified node in ascending order.
+    ///
+    /// Returns an empty `Vec` if the node is not part of the ring.
+    /// See [positions_of_iter](HashRing::positions_of_iter) for a lazy variant.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut ring: HashRing<&str, _> = HashRing::default();
+    ///
+    /// ring.insert("10.0.0.1:1234", 10);
+    /// assert_eq!(ring.positions_of(&"10.0.0.1:1234").len(), 10);
+    /// assert!(ring.positions_of(&"10.0.0.2:1234").is_empty());
+    /// ```
+    pub fn positions_of(&self, node: &N) -> Vec<u64> {
+        self.positions_of_iter(node).collect()
+    }
+
+    /// An iterator visiting the positions of all virtual nodes of the specified node in ascending order.
+    ///
+    /// Contrary to [positions_of](HashRing::positions_of), the positions are not collected,
+    /// which saves memory for nodes with a high weight.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut ring: HashRing<&str, _> = HashRing::default();
+    ///
+    /// ring.insert("10.0.0.1:1234", 10);
+    /// ring.insert("10.0.0.2:1234", 10);
+    ///
+    /// let mut positions = ring.positions_of_iter(&"10.0.0.1:1234");
+    /// let lowest_position = positions.next();
+    /// assert!(positions.all(|position| Some(position) > lowest_position));
+    /// ```
+    pub fn positions_of_iter<'a>(&'a self, node: &N) -> impl Iterator<Item = u64> + 'a {
+        let master_node = self.get_master_node(node);
+        self.virtual_nodes
+            .iter()
+            .filter(move |(_, owner)| {
+                master_node.is_some_and(|master_node| Arc::ptr_eq(owner, master_node))
+            })
+            .map(|(position, _)| *position)
+    }
+
+    /// An iterator visiting all node-weight pairs in arbitrary order. The iterator element type is `(&'a N, u64)`.
+    ///
+    /// The weight is the actual number of virtual nodes. It may be lower than the `weight` provided when inserting
+    /// a node in case of hash collisions.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut ring: HashRing<&str, _> = HashRing::default();
+    ///
+    /// ring.insert("10.0.0.1:1234", 10);
+    /// ring.insert("10.0.0.2:1234", 5);
+    ///
+    /// for (node, weight) in ring.iter() {
+    ///     println!("node: {node} weight: {weight}");
+    /// }
+    /// ```
+    pub fn iter(&self) -> Iter<'_, N> {
+        let mut map: Vec<_> = self
+            .virtual_nodes
+            .values()
+            .map(|node| {
+                (
+                    self.hash_builder.hash_one(&node.node),
+                    (&node.node, node.weight.get()),
+                )
+            })
+            .collect::<Vec<_>>();
+        map.sort_unstable_by_key(|n| n.0);
+        map.dedup_by(|x, y| x.0 == y.0);
+
+        Iter::new(map.into_iter())
+    }
+
+    /// Returns a map of all nodes and the weights they were inserted with.
+    ///
+    /// This is the inverse of [from_weights](HashRing::from_weights).
+    /// Contrary to [iter](HashRing::iter), the weights are the ones provided when inserting a node,
+    /// regardless of virtual nodes lost to hash collisions.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut ring: HashRing<&str, _> = HashRing::default();
+    ///
+    /// ring.insert("10.0.0.1:1234", 10);
+    /// assert_eq!(ring.to_weights_map().get("10.0.0.1:1234"), Some(&10));
+    /// ```
+    pub fn to_weights_map(&self) -> HashMap<N, u64>
+    where
+        N: Clone + Eq,
+    {
+        self.master_nodes()
+            .into_iter()
+            .map(|master_node| (master_node.node.clone(), master_node.requested_weight.get()))
+            .collect()
+    }
+
+    /// Returns a ring containing all nodes of this ring and the `other` ring.
+    ///
+    /// Nodes part of both rings get the larger of their two weights. Weights are the ones the nodes were inserted with.
+    /// The returned ring has the same configuration as this ring.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut ring: HashRing<&str, _> = HashRing::default();
+    /// ring.insert("10.0.0.1:1234", 10);
+    /// ring.insert("10.0.0.2:1234", 10);
+    ///
+    /// let mut other: HashRing<&str, _> = HashRing::default();
+    /// other.insert("10.0.0.2:1234", 20);
+    /// other.insert("10.0.0.3:1234", 10);
+    ///
+    /// let union = ring.union(&other);
+    /// assert_eq!(union.len(), 3);
+    /// assert_eq!(union.to_weights_map().get("10.0.0.2:1234"), Some(&20));
+    /// ```
+    pub fn union(&self, other: &Self) -> Self
+    where
+        N: Clone + Eq,
+        B: Clone,
+    {
+        let mut union = self.clone();
+        let weights = self.to_weights_map();
+        for (node, weight) in other.to_weights_map() {
+            if weights
+                .get(&node)
+                .is_none_or(|own_weight| *own_weight < weight)
+            {
+                union.upsert(node, weight);
+            }
+        }
+        union
+    }
+
+    /// Returns a ring containing only the nodes part of both this ring and the `other` ring.
+    ///
+    /// Every node gets the smaller of its two weights. Weights are the ones the nodes were inserted with.
+    /// The returned ring has the same configuration as this ring.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut desired: HashRing<&str, _> = HashRing::default();
+    /// desired.insert("10.0.0.1:1234", 10);
+    /// desired.insert("10.0.0.2:1234", 10);
+    ///
+    /// let mut healthy: HashRing<&str, _> = HashRing::default();
+    /// healthy.insert("10.0.0.2:1234", 5);
+    /// healthy.insert("10.0.0.3:1234", 10);
+    ///
+    /// let intersection = desired.intersection(&healthy);
+    /// assert_eq!(intersection.len(), 1);
+    /// assert_eq!(intersection.to_weights_map().get("10.0.0.2:1234"), Some(&5));
+    /// ```
+    pub fn intersection(&self, other: &Self) -> Self
+    where
+        N: Clone + Eq,
+        B: Clone,
+    {
+        let mut intersection = self.clone();
+        let other_weights = other.to_weights_map();
+        for (node, weight) in self.to_weights_map() {
+            match other_weights.get(&node) {
+                Some(other_weight) if *other_weight < weight => {
+                    intersection.upsert(node, *other_weight);
+                }
+                Some(_) => {}
+                None => {
+                    intersection.remove(&node);
+                }
+            }
+        }
+        intersection
+    }
+
+    /// Returns an iterator over all virtual nodes as `(position, node)` pairs, in ascending order of their positions.
+    ///
+    /// A key is routed to the first virtual node at or after its hash, wrapping around to the first virtual node.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut ring: HashRing<&str, _> = HashRing::default();
+    ///
+    /// ring.insert("10.0.0.1:1234", 10);
+    /// ring.insert("10.0.0.2:1234", 10);
+    ///
+    /// let virtual_nodes = ring.virtual_nodes().collect::<Vec<_>>();
+    /// assert_eq!(virtual_nodes.len(), 20);
+    /// assert!(virtual_nodes.windows(2).all(|pair| pair[0].0 < pair[1].0));
+    /// ```
+    pub fn virtual_nodes(&self) -> impl Iterator<Item = (u64, &N)> + '_ {
+        self.virtual_nodes
+            .iter()
+            .map(|(position, master_node)| (*position, &master_node.node))
+    }
+
+    /// Returns every contiguous arc `(start, end, node)` of the hash space in ascending order.
+    ///
+    /// All keys with a hash between `start` and `end` (both inclusive) are routed to `node`.
+    /// Adjacent arcs owned by the same node are coalesced into one entry.
+    /// The arcs cover the whole `u64` space, so the map can be binary searched to route keys without the ring.
+    /// Because keys beyond the last virtual node wrap around, the first and the last arc belong to the same node
+    /// unless a virtual node is positioned at `u64::MAX`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut ring: HashRing<&str, _> = HashRing::default();
+    ///
+    /// ring.insert("10.0.0.1:1234", 10);
+    /// ring.insert("10.0.0.2:1234", 10);
+    ///
+    /// let ownership_map = ring.ownership_map();
+    /// assert_eq!(ownership_map.first().map(|arc| arc.0), Some(0));
+    /// assert_eq!(ownership_map.last().map(|arc| arc.1), Some(u64::MAX));
+    /// ```
+    pub fn ownership_map(&self) -> Vec<(u64, u64, &N)> {
+        let mut arcs: Vec<(u64, u64, &Arc<MasterNode<N>>)> = Vec::new();
+        let mut start = Some(0);
+        for (&position, master_node) in self.virtual_nodes.iter() {
+            if let Some(arc_start) = start {
+                arcs.push((arc_start, position, master_node));
+            }
+            start = position.checked_add(1);
+        }
+        if let (Some(arc_start), Some((_, first_master_node))) =
+            (start, self.virtual_nodes.iter().next())
+        {
+            // Keys beyond the last virtual node wrap around to the first one
+            arcs.push((arc_start, u64::MAX, first_master_node));
+        }
+        arcs.dedup_by(|next, previous| {
+            let same_owner = Arc::ptr_eq(next.2, previous.2);
+            if same_owner {
+                previous.1 = next.1;
+            }
+            same_owner
+        });
+        arcs.into_iter()
+            .map(|(start, end, master_node)| (start, end, &master_node.node))
+            .collect()
+    }
+
+    /// Returns a digest of the [ownership_map](HashRing::ownership_map), hashing every arc with the hash of its node.
+    ///
+    /// Two rings using the same hasher produce the same digest if they route all keys to the same nodes,
+    /// even if their virtual nodes are laid out differently. This makes it cheap to check whether peers agree on routing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut ring: HashRing<&str, _> = HashRing::default();
+    /// ring.insert("10.0.0.1:1234", 10);
+    ///
+    /// let mut other: HashRing<&str, _> = HashRing::default();
+    /// other.insert("10.0.0.1:1234", 1);
+    /// assert_eq!(ring.coalesced_digest(), other.coalesced_digest());
+    ///
+    /// other.insert("10.0.0.2:1234", 1);
+    /// assert_ne!(ring.coalesced_digest(), other.coalesced_digest());
+    /// ```
+    pub fn coalesced_digest(&self) -> u64 {
+        let arcs = self
+            .ownership_map()
+            .into_iter()
+            .map(|(start, end, node)| (start, end, self.node_base_hash(node)))
+            .collect::<Vec<_>>();
+        self.hash_builder.hash_one(arcs)
+    }
+
+    /// Merges all nodes of the given rings into this ring, one ring after the other.
+    ///
+    /// Every node is inserted with the weight it was originally inserted with, like with [insert](HashRing::insert).
+    /// The positions of its virtual nodes are computed by this ring, so the source rings may use other hashers.
+    /// If the same node is part of several rings, the weight from the ring merged last is used.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut shard_1: HashRing<&str, _> = HashRing::default();
+    /// shard_1.insert("10.0.0.1:1234", 10);
+    /// shard_1.insert("10.0.0.2:1234", 10);
+    ///
+    /// let mut shard_2: HashRing<&str, _> = HashRing::default();
+    /// shard_2.insert("10.0.0.2:1234", 5);
+    ///
+    /// let mut ring = HashRing::default();
+    /// ring.merge_all([shard_1, shard_2]);
+    /// assert_eq!(ring.len(), 2);
+    /// assert_eq!(ring.to_weights_map().get("10.0.0.2:1234"), Some(&5));
+    /// ```
+    pub fn merge_all<I, C>(&mut self, rings: I)
+    where
+        N: Clone,
+        I: IntoIterator<Item = HashRing<N, C>>,
+    {
+        for ring in rings {
+            for master_node in Self::into_master_nodes(ring.virtual_nodes) {
+                self.insert(master_node.node, master_node.requested_weight.get());
+            }
+        }
+    }
+
+    /// Returns the number of maximal runs of consecutive virtual nodes belonging to the same node.
+    ///
+    /// The runs at the start and the end of the ring are merged if they belong to the same node.
+    /// Ideally, virtual nodes of different nodes interleave well, resulting in a number of runs close to the number of virtual nodes.
+    /// Far fewer runs indicate that virtual nodes of the same node cluster together.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut ring: HashRing<&str, _> = HashRing::default();
+    /// assert_eq!(ring.ownership_runs(), 0);
+    ///
+    /// ring.insert("10.0.0.1:1234", 10);
+    /// assert_eq!(ring.ownership_runs(), 1);
+    ///
+    /// ring.insert("10.0.0.2:1234", 10);
+    /// assert!(ring.ownership_runs() > 1);
+    /// ```
+    pub fn ownership_runs(&self) -> usize {
+        let master_nodes = self.virtual_nodes.values().collect::<Vec<_>>();
+        let (first, last) = match (master_nodes.first(), master_nodes.last()) {
+            (Some(first), Some(last)) => (first, last),
+            _ => return 0,
+        };
+        let runs = 1 + master_nodes
+            .windows(2)
+            .filter(|pair| !Arc::ptr_eq(pair[0], pair[1]))
+            .count();
+        if runs > 1 && Arc::ptr_eq(first, last) {
+            // The ring wraps around, so the first and the last run are one
+            runs - 1
+        } else {
+            runs
+        }
+    }
+
+    /// Returns the first of the `candidate_keys` which is routed to `node`.
+    ///
+    /// Instead of routing every candidate through the whole ring, only the arcs owned by `node` are searched.
+    /// Returns `None` if the node is not part of the ring or none of the candidates is routed to it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut ring: HashRing<&str, _> = HashRing::default();
+    ///
+    /// ring.insert("10.0.0.1:1234", 10);
+    /// ring.insert("10.0.0.2:1234", 10);
+    ///
+    /// let candidates = (0..1000).map(|i| format!("key-{i}")).collect::<Vec<_>>();
+    /// let key = ring
+    ///     .find_key_for(&"10.0.0.2:1234", candidates.iter().map(|key| key.as_bytes()))
+    ///     .unwrap();
+    /// assert_eq!(ring.get(key.as_slice()), Some(&"10.0.0.2:1234"));
+    /// ```
+    pub fn find_key_for<'a>(
+        &self,
+        node: &N,
+        mut candidate_keys: impl Iterator<Item = &'a [u8]>,
+    ) -> Option<Vec<u8>> {
+        let master_node = self.get_master_node(node)?;
+        let owned_arcs = self
+            .ownership_map()
+            .into_iter()
+            .filter(|(_, _, owner)| std::ptr::eq(*owner, &master_node.node))
+            .map(|(start, end, _)| (start, end))
+            .collect::<Vec<_>>();
+        candidate_keys
+            .find(|key| {
+                let key_hash = self.hash_builder.hash_one(key);
+                let index = owned_arcs.partition_point(|(_, end)| *end < key_hash);
+                owned_arcs
+                    .get(index)
+                    .is_some_and(|(start, _)| *start <= key_hash)
+            })
+            .map(|key| key.to_vec())
+    }
+
+    /// Builds a [LookupTable](LookupTable) with `2^bits` buckets routing keys in constant time.
+    ///
+    /// Every bucket is assigned the node owning its first hash, so keys close to the position of a virtual node
+    /// may be routed differently than by [get](HashRing::get). More `bits` increase the accuracy,
+    /// but the table requires `2^bits` references of memory. The table must be rebuilt whenever the ring changes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bits` exceeds [LookupTable::MAX_BITS](LookupTable::MAX_BITS).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut ring: HashRing<&str, _> = HashRing::default();
+    ///
+    /// ring.insert("10.0.0.1:1234", 10);
+    /// ring.insert("10.0.0.2:1234", 10);
+    ///
+    /// let table = ring.build_lookup_table(16);
+    /// let matching = (0..1000).filter(|key| table.get(key) == ring.get(key)).count();
+    /// assert!(matching > 990);
+    /// ```
+    pub fn build_lookup_table(&self, bits: u32) -> LookupTable<'_, N, B> {
+        LookupTable::new(self, bits)
+    }
+
+    /// Returns the buckets of a lookup table with `2^bits` buckets whose node differs between the `old` ring and this ring,
+    /// together with their node in this ring.
+    ///
+    /// The buckets are assigned like in [build_lookup_table](HashRing::build_lookup_table), so a table of nodes
+    /// maintained by the caller can be patched instead of being rebuilt whenever the ring changes slightly.
+    /// Buckets without a node in this ring, i.e. if this ring is empty, are not returned.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bits` exceeds [LookupTable::MAX_BITS](LookupTable::MAX_BITS).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut ring: HashRing<&str, _> = HashRing::default();
+    /// ring.insert("10.0.0.1:1234", 10);
+    ///
+    /// let mut table: Vec<&str> = ring.build_lookup_table(8).buckets().iter().map(|node| **node).collect();
+    /// let old = ring.clone();
+    ///
+    /// ring.insert("10.0.0.2:1234", 10);
+    /// for (bucket, node) in ring.lookup_table_delta(&old, 8) {
+    ///     table[bucket] = *node;
+    /// }
+    /// assert!(table.iter().eq(ring.build_lookup_table(8).buckets().iter().copied()));
+    /// ```
+    pub fn lookup_table_delta(&self, old: &HashRing<N, B>, bits: u32) -> Vec<(usize, &N)>
+    where
+        N: PartialEq,
+    {
+        assert!(
+            bits <= LookupTable::<N, B>::MAX_BITS,
+            "a lookup table supports at most {} bits",
+            LookupTable::<N, B>::MAX_BITS
+        );
+        (0..1u64 << bits)
+            .filter_map(|bucket| {
+                let bucket_start = LookupTable::<N, B>::bucket_start(bucket, bits);
+                let node = self.get_by_hash(bucket_start)?;
+                (old.get_by_hash(bucket_start) != Some(node)).then_some((bucket as usize, node))
+            })
+            .collect()
+    }
+
+    /// Returns the number of virtual nodes that can still be added before reaching the
+    /// [total virtual node budget](HashRingBuilder::total_virtual_node_budget).
+    ///
+    /// Returns `u64::MAX` if no budget is configured.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRingBuilder;
+    ///
+    /// let mut ring = HashRingBuilder::default()
+    ///     .total_virtual_node_budget(100)
+    ///     .build();
+    ///
+    /// ring.insert("10.0.0.1:1234", 10);
+    /// assert_eq!(ring.remaining_budget(), 90);
+    /// ```
+    pub fn remaining_budget(&self) -> u64 {
+        match self.total_virtual_node_budget {
+            Some(budget) => budget.saturating_sub(self.total_weight()),
+            None => u64::MAX,
+        }
+    }
+
+    fn get_master_node_by_hash(&self, hash: &u64) -> Option<&Arc<MasterNode<N>>> {
+        self.virtual_nodes.get(hash)
+    }
+
+    fn compute_virtual_node_hashes(&self, node: &N, weight: NonZeroU64) -> HashSet<u64> {
+        (0..weight.get())
+            // It could be that we have a collision here and therefore fewer virtual nodes
+            // TODO what's the distribution here?
+            .map(|virtual_node_identifier| self.virtual_node_hash(node, virtual_node_identifier))
+            .collect()
+    }
+
+    /// Returns all positions the virtual nodes of the master node were placed at, including positions since taken over
+    /// by other nodes.
+    fn master_node_positions(&self, master_node: &MasterNode<N>) -> HashSet<u64> {
+        let mut positions =
+            self.compute_virtual_node_hashes(&master_node.node, master_node.requested_weight);
+        positions.extend(
+            master_node
+                .reassigned_positions
+                .iter()
+                .map(|(_, position)| *position),
+        );
+        positions
+    }
+
+    fn virtual_node_hash(&self, node: &N, virtual_node_identifier: u64) -> u64 {
+        match &self.virtual_node_fn {
+            Some(virtual_node_fn) => {
+                virtual_node_fn.position(&self.hash_builder, node, virtual_node_identifier)
+            }
+            None => {
+                let mut hasher = self.hash_builder.build_hasher();
+                node.hash(&mut hasher);
+                hasher.write_u64(virtual_node_identifier);
+                hasher.finish()
+            }
+        }
+    }
+
+    /// Removes a node from the `HashRing`, returning the number of virtual nodes (weight) of the removed node.
+    ///
+    /// The returned number is the actual number of virtual nodes. It may be lower than the `weight` provided when inserting
+    /// a node in case of hash collisions.
+    ///
+    /// The node may be any borrowed form of the node type, see [contains_node](HashRing::contains_node).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut ring: HashRing<String, _> = HashRing::default();
+    ///
+    /// ring.insert("10.0.0.1:1234".to_string(), 10);
+    /// assert_eq!(ring.remove("10.0.0.1:1234"), 10);
+    /// assert_eq!(ring.remove("10.0.0.1:1234"), 0);
+    /// ```
+    pub fn remove<Q>(&mut self, node: &Q) -> u64
+    where
+        N: Borrow<Q>,
+        Q: Hash + ?Sized,
+    {
+        match self.find_master_node(node) {
+            Some(master_node) => self.remove_master_node(master_node.clone()).1,
+            None => 0,
+        }
+    }
+
+    /// Removes a node from the `HashRing`, returning the number of virtual nodes (weight) of the removed node.
+    ///
+    /// # Errors
+    ///
+    /// Returns [NodeAbsent](HulahoopError::NodeAbsent) if the node is not part of the ring.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::{HashRing, HulahoopError};
+    ///
+    /// let mut ring: HashRing<&str, _> = HashRing::default();
+    ///
+    /// ring.insert("10.0.0.1:1234", 10);
+    /// assert_eq!(ring.remove_checked(&"10.0.0.1:1234"), Ok(10));
+    /// assert_eq!(ring.remove_checked(&"10.0.0.1:1234"), Err(HulahoopError::NodeAbsent));
+    /// ```
+    pub fn remove_checked<Q>(&mut self, node: &Q) -> Result<u64, HulahoopError>
+    where
+        N: Borrow<Q>,
+        Q: Hash + ?Sized,
+    {
+        if !self.contains_node(node) {
+            return Err(HulahoopError::NodeAbsent);
+        }
+        Ok(self.remove(node))
+    }
+
+    /// Removes a node from the `HashRing`, returning the owned node together with its actual weight.
+    ///
+    /// Returns `None` if the node is not part of the ring. The node may be any borrowed form of the node type,
+    /// see [contains_node](HashRing::contains_node).
+    /// The node can only be returned if no [upgraded Weak](HashRing::weak_of) reference to it is alive,
+    /// otherwise it is removed from the ring nonetheless and `None` is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut ring: HashRing<String, _> = HashRing::default();
+    ///
+    /// ring.insert("10.0.0.1:1234".to_string(), 10);
+    /// assert_eq!(ring.remove_entry("10.0.0.1:1234"), Some(("10.0.0.1:1234".to_string(), 10)));
+    /// assert_eq!(ring.remove_entry("10.0.0.1:1234"), None);
+    /// ```
+    pub fn remove_entry<Q>(&mut self, node: &Q) -> Option<(N, u64)>
+    where
+        N: Borrow<Q>,
+        Q: Hash + ?Sized,
+    {
+        let master_node = self.find_master_node(node)?.clone();
+        self.remove_with_weight(master_node)
+    }
+
+    /// Removes a node equal to the specified node from the `HashRing`, returning the number of its virtual nodes.
+    ///
+    /// Unlike [remove](HashRing::remove), which only compares hashes, this also compares the node with the node
+    /// stored in the ring, so a different node with colliding hashes is never removed instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut ring: HashRing<String, _> = HashRing::default();
+    ///
+    /// ring.insert("10.0.0.1:1234".to_string(), 10);
+    /// assert_eq!(ring.remove_exact("10.0.0.1:1234"), 10);
+    /// assert_eq!(ring.remove_exact("10.0.0.1:1234"), 0);
+    /// ```
+    pub fn remove_exact<Q>(&mut self, node: &Q) -> u64
+    where
+        N: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        match self.find_master_node_exact(node) {
+            Some(master_node) => self.remove_master_node(master_node.clone()).1,
+            None => 0,
+        }
+    }
+
+    /// Removes all nodes from the `HashRing`, keeping its configuration.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut ring: HashRing<&str, _> = HashRing::default();
+    ///
+    /// ring.insert("10.0.0.1:1234", 10);
+    /// ring.clear();
+    /// assert!(ring.is_empty());
+    /// assert_eq!(ring.len(), 0);
+    /// ```
+    pub fn clear(&mut self) {
+        if !self.virtual_nodes.is_empty() {
+            self.version += 1;
+        }
+        let removed_master_nodes = self.removed_master_nodes(|_| true);
+        self.virtual_nodes.clear();
+        self.master_nodes_by_hash.clear();
+        self.node_count = 0;
+        self.notify_all_removed(removed_master_nodes);
+    }
+
+    /// Retains only the nodes specified by the predicate, which is called with every node and its actual weight.
+    ///
+    /// All nodes for which `f` returns `false` are removed in a single pass over the virtual nodes,
+    /// which is faster than calling [remove](HashRing::remove) for each of them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut ring: HashRing<&str, _> = HashRing::default();
+    ///
+    /// ring.insert("10.0.0.1:1234", 10);
+    /// ring.insert("10.0.0.2:1234", 5);
+    /// ring.insert("10.0.0.3:1234", 1);
+    ///
+    /// ring.retain(|_, weight| weight >= 5);
+    /// assert_eq!(ring.len(), 2);
+    /// assert!(!ring.contains_node(&"10.0.0.3:1234"));
+    /// ```
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&N, u64) -> bool,
+    {
+        let removed_master_nodes = self
+            .master_nodes()
+            .into_iter()
+            .filter(|master_node| !f(&master_node.node, master_node.weight.get()))
+            .map(Arc::as_ptr)
+            .collect::<HashSet<_>>();
+        if removed_master_nodes.is_empty() {
+            return;
+        }
+        let notified_master_nodes = self.removed_master_nodes(|master_node| {
+            removed_master_nodes.contains(&Arc::as_ptr(master_node))
+        });
+        self.virtual_nodes
+            .retain(|_, master_node| !removed_master_nodes.contains(&Arc::as_ptr(master_node)));
+        self.node_count -= removed_master_nodes.len();
+        self.version += 1;
+        self.notify_all_removed(notified_master_nodes);
+    }
+
+    /// Collects the master nodes matching `f` together with their positions, if an event callback is configured.
+    fn removed_master_nodes<F>(&self, f: F) -> Vec<(Arc<MasterNode<N>>, Vec<u64>)>
+    where
+        F: Fn(&Arc<MasterNode<N>>) -> bool,
+    {
+        if self.event_callback.is_none() {
+            return Vec::new();
+        }
+        let mut master_nodes = self
+            .master_nodes()
+            .into_iter()
+            .filter(|master_node| f(master_node))
+            .map(|master_node| (master_node.clone(), self.owned_positions(master_node)))
+            .collect::<Vec<_>>();
+        master_nodes.sort_unstable_by_key(|(master_node, _)| master_node.id);
+        master_nodes
+    }
+
+    fn notify_all_removed(&self, removed_master_nodes: Vec<(Arc<MasterNode<N>>, Vec<u64>)>) {
+        for (master_node, positions) in removed_master_nodes {
+            self.notify_removed(&master_node, positions);
+        }
+    }
+
+    /// Resolves positions claimed by several nodes due to hash collisions, returning by how much the total weight was reduced.
+    ///
+    /// If the virtual nodes of different nodes collide, the node inserted last takes over the position while the
+    /// weight of the other node is not updated. This assigns every such position to the node with the lowest hash
+    /// and updates the actual weights of all nodes to match the number of their virtual nodes exactly.
+    /// Nodes left without any virtual node are removed from the ring. The
+    /// [event callback](HashRingBuilder::event_callback) receives a single [Batch](RingEvent::Batch) of all changes.
+    ///
+    /// The returned number is the number of virtual nodes which were counted in the weights, but did not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut ring: HashRing<&str, _> = HashRing::default();
+    ///
+    /// ring.insert("10.0.0.1:1234", 10);
+    /// ring.insert("10.0.0.2:1234", 10);
+    /// assert_eq!(ring.deduplicate_positions(), 0);
+    /// assert_eq!(ring.total_weight(), 20);
+    /// ```
+    pub fn deduplicate_positions(&mut self) -> u64
+    where
+        N: Clone,
+    {
+        let master_nodes = self.master_nodes().into_iter().cloned().collect::<Vec<_>>();
+        let total_weight = self.total_weight();
+        // The changes are reported as a single batch once all positions are reassigned
+        let old_master_nodes = match self.event_callback {
+            Some(_) => master_nodes
+                .iter()
+                .map(|master_node| (master_node.clone(), self.owned_positions(master_node)))
+                .collect(),
+            None => Vec::new(),
+        };
+        let mut claims: HashMap<u64, (u64, usize)> = HashMap::new();
+        for (index, master_node) in master_nodes.iter().enumerate() {
+            let node_hash = self.hash_builder.hash_one(&master_node.node);
+            for virtual_node_hash in self.master_node_positions(master_node) {
+                if !self.virtual_nodes.contains_key(&virtual_node_hash) {
+                    continue;
+                }
+                let claim = claims
+                    .entry(virtual_node_hash)
+                    .or_insert((node_hash, index));
+                *claim = (*claim).min((node_hash, index));
+            }
+        }
+
+        let mut weights = vec![0u64; master_nodes.len()];
+        for (_, index) in claims.values() {
+            weights[*index] += 1;
+        }
+        self.node_count = weights.iter().filter(|weight| **weight > 0).count();
+        // Drop all references to the master nodes held by the virtual nodes, so their weights can be updated
+        self.virtual_nodes.clear();
+        let master_nodes = master_nodes
+            .into_iter()
+            .zip(&weights)
+            .map(|(master_node, weight)| match NonZeroU64::new(*weight) {
+                Some(weight) if weight != master_node.weight => {
+                    // Master nodes still referenced elsewhere are copied, so their weights are updated regardless
+                    let mut master_node = Arc::unwrap_or_clone(master_node);
+                    master_node.weight = weight;
+                    Arc::new(master_node)
+                }
+                _ => master_node,
+            })
+            .collect::<Vec<_>>();
+        self.virtual_nodes = claims
+            .into_iter()
+            .map(|(position, (_, index))| (position, master_nodes[index].clone()))
+            .collect();
+        self.master_nodes_by_hash.clear();
+        for master_node in &master_nodes {
+            self.index_master_node(master_node);
+        }
+        let reduction = total_weight.saturating_sub(self.total_weight());
+        if reduction > 0 {
+            self.version += 1;
+        }
+        if self.event_callback.is_some() {
+            let mut changes = old_master_nodes
+                .into_iter()
+                .zip(master_nodes.into_iter().zip(weights))
+                .map(|((old, old_positions), (new, weight))| {
+                    (Some(old), (weight > 0).then_some(new), old_positions)
+                })
+                .collect::<Vec<_>>();
+            changes.sort_unstable_by_key(|(old, _, _)| old.as_ref().map(|old| old.id));
+            self.notify_batch(changes);
+        }
+        reduction
+    }
+
+    /// Removes the node with the lowest actual weight, returning it together with its weight.
+    ///
+    /// If several nodes have the lowest weight, the one with the lowest hash is removed.
+    /// Returns `None` if the ring is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut ring: HashRing<&str, _> = HashRing::default();
+    ///
+    /// ring.insert("10.0.0.1:1234", 10);
+    /// ring.insert("10.0.0.2:1234", 5);
+    /// assert_eq!(ring.remove_lightest(), Some(("10.0.0.2:1234", 5)));
+    /// assert_eq!(ring.remove_lightest(), Some(("10.0.0.1:1234", 10)));
+    /// assert_eq!(ring.remove_lightest(), None);
+    /// ```
+    pub fn remove_lightest(&mut self) -> Option<(N, u64)> {
+        let master_node = self
+            .master_nodes()
+            .into_iter()
+            .min_by_key(|master_node| {
+                (
+                    master_node.weight,
+                    self.hash_builder.hash_one(&master_node.node),
+                )
+            })?
+            .clone();
+        self.remove_with_weight(master_node)
+    }
+
+    /// Removes the node with the highest actual weight, returning it together with its weight.
+    ///
+    /// If several nodes have the highest weight, the one with the lowest hash is removed.
+    /// Returns `None` if the ring is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut ring: HashRing<&str, _> = HashRing::default();
+    ///
+    /// ring.insert("10.0.0.1:1234", 10);
+    /// ring.insert("10.0.0.2:1234", 5);
+    /// assert_eq!(ring.remove_heaviest(), Some(("10.0.0.1:1234", 10)));
+    /// assert_eq!(ring.remove_heaviest(), Some(("10.0.0.2:1234", 5)));
+    /// assert_eq!(ring.remove_heaviest(), None);
+    /// ```
+    pub fn remove_heaviest(&mut self) -> Option<(N, u64)> {
+        let master_node = self
+            .master_nodes()
+            .into_iter()
+            .max_by_key(|master_node| {
+                (
+                    master_node.weight,
+                    Reverse(self.hash_builder.hash_one(&master_node.node)),
+                )
+            })?
+            .clone();
+        self.remove_with_weight(master_node)
+    }
+
+    fn remove_with_weight(&mut self, master_node: Arc<MasterNode<N>>) -> Option<(N, u64)> {
+        let weight = master_node.weight.get();
+        self.remove_master_node(master_node)
+            .0
+            .map(|node| (node, weight))
+    }
+
+    fn remove_inner(&mut self, node: &N) -> (Option<N>, u64) {
+        match self.get_master_node(node) {
+            Some(master_node) => self.remove_master_node(master_node.clone()),
+            None => (None, 0),
+        }
+    }
+
+    fn remove_master_node(&mut self, master_node: Arc<MasterNode<N>>) -> (Option<N>, u64) {
+        let mut removed_positions = Vec::new();
+        for virtual_node_hash in self.master_node_positions(&master_node) {
+            // Positions taken over by other nodes due to hash collisions must be kept
+            if self.is_owned_by(&virtual_node_hash, &master_node) {
+                self.virtual_nodes.remove(&virtual_node_hash);
+                removed_positions.push(virtual_node_hash);
+            }
+        }
+        let number_of_removed_virtual_nodes = removed_positions.len() as u64;
+        self.unindex_master_node(&master_node);
+        if number_of_removed_virtual_nodes > 0 {
+            self.node_count -= 1;
+            self.version += 1;
+            self.notify_removed(&master_node, removed_positions);
+        }
+        // There should be no other references to the master node left, so we should be able to get the node out of Arc.
+        let removed_node = Arc::try_unwrap(master_node)
+            .ok()
+            .map(|master_node| master_node.node);
+        (removed_node, number_of_removed_virtual_nodes)
+    }
+
+    fn master_nodes(&self) -> Vec<&Arc<MasterNode<N>>> {
+        let mut master_nodes = self.virtual_nodes.values().collect::<Vec<_>>();
+        master_nodes.sort_unstable_by_key(|master_node| Arc::as_ptr(master_node));
+        master_nodes.dedup_by(|x, y| Arc::ptr_eq(x, y));
+        master_nodes
+    }
+
+    /// Finds the master node of a borrowed form of a node.
+    ///
+    /// A custom strategy can only compute positions of the node type itself, so the master nodes with the same hash
+    /// are looked up instead.
+    fn find_master_node<Q>(&self, node: &Q) -> Option<&Arc<MasterNode<N>>>
+    where
+        N: Borrow<Q>,
+        Q: Hash + ?Sized,
+    {
+        self.find_master_node_matching(node, |_| true)
+    }
+
+    fn find_master_node_exact<Q>(&self, node: &Q) -> Option<&Arc<MasterNode<N>>>
+    where
+        N: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.find_master_node_matching(node, |master_node| master_node.node.borrow() == node)
+    }
+
+    fn find_master_node_matching<Q, F>(&self, node: &Q, matches: F) -> Option<&Arc<MasterNode<N>>>
+    where
+        N: Borrow<Q>,
+        Q: Hash + ?Sized,
+        F: Fn(&MasterNode<N>) -> bool,
+    {
+        match &self.virtual_node_fn {
+            Some(_) => self
+                .indexed_master_nodes_of(node)
+                .find(|master_node| matches(master_node)),
+            None => {
+                let mut hasher = self.hash_builder.build_hasher();
+                node.hash(&mut hasher);
+                hasher.write_u64(0);
+                self.master_node_with_id(node, hasher.finish(), matches)
+            }
+        }
+    }
+
+    /// Returns the master node with the id which matches, whether or not it still owns the position of its id.
+    fn master_node_with_id<Q, F>(
+        &self,
+        node: &Q,
+        id: u64,
+        matches: F,
+    ) -> Option<&Arc<MasterNode<N>>>
+    where
+        Q: Hash + ?Sized,
+        F: Fn(&MasterNode<N>) -> bool,
+    {
+        self.get_master_node_by_hash(&id)
+            .filter(|master_node| master_node.id == id && matches(master_node))
+            // The position of the first virtual node may be held by another node
+            .or_else(|| {
+                self.indexed_master_nodes_of(node)
+                    .find(|master_node| master_node.id == id && matches(master_node))
+            })
+    }
+
+    /// Returns the master node with the id, given the hash of its node, like `master_node_with_id`.
+    fn master_node_with_id_and_hash(&self, id: u64, node_hash: u64) -> Option<&Arc<MasterNode<N>>> {
+        self.get_master_node_by_hash(&id)
+            .filter(|master_node| master_node.id == id)
+            .or_else(|| {
+                self.indexed_master_nodes_by_hash(node_hash)
+                    .find(|master_node| master_node.id == id)
+            })
+    }
+
+    /// Returns the master nodes of the ring with the same hash as the node, see `master_nodes_by_hash`.
+    fn indexed_master_nodes_of<'a, Q>(
+        &'a self,
+        node: &Q,
+    ) -> impl Iterator<Item = &'a Arc<MasterNode<N>>> + 'a
+    where
+        Q: Hash + ?Sized,
+    {
+        self.indexed_master_nodes_by_hash(self.hash_builder.hash_one(node))
+    }
+
+    /// Returns the master nodes of the ring whose node has the hash, see `master_nodes_by_hash`.
+    fn indexed_master_nodes_by_hash(
+        &self,
+        node_hash: u64,
+    ) -> impl Iterator<Item = &Arc<MasterNode<N>>> + '_ {
+        self.master_nodes_by_hash
+            .get(&node_hash)
+            .into_iter()
+            .flatten()
+            .filter_map(Weak::upgrade)
+            .filter_map(|master_node| {
+                // A master node is part of the ring as long as it owns a position, usually the one of its id
+                if self.is_owned_by(&master_node.id, &master_node) {
+                    return self.virtual_nodes.get(&master_node.id);
+                }
+                self.master_node_positions(&master_node)
+                    .into_iter()
+                    .find(|position| self.is_owned_by(position, &master_node))
+                    .and_then(|position| self.virtual_nodes.get(&position))
+            })
+    }
+
+    fn index_master_node(&mut self, master_node: &Arc<MasterNode<N>>) {
+        let master_nodes = self
+            .master_nodes_by_hash
+            .entry(self.hash_builder.hash_one(&master_node.node))
+            .or_default();
+        master_nodes.retain(|master_node| master_node.strong_count() > 0);
+        master_nodes.push(Arc::downgrade(master_node));
+    }
+
+    fn unindex_master_node(&mut self, master_node: &Arc<MasterNode<N>>) {
+        let node_hash = self.hash_builder.hash_one(&master_node.node);
+        if let Some(master_nodes) = self.master_nodes_by_hash.get_mut(&node_hash) {
+            master_nodes.retain(|indexed| {
+                indexed.strong_count() > 0 && indexed.as_ptr() != Arc::as_ptr(master_node)
+            });
+            if master_nodes.is_empty() {
+                self.master_nodes_by_hash.remove(&node_hash);
+            }
+        }
+    }
+
+    fn get_master_node(&self, node: &N) -> Option<&Arc<MasterNode<N>>> {
+        self.master_node_with_id(node, self.virtual_node_hash(node, 0), |_| true)
+    }
+}
+
+/// Clones the ring including its generation.
+///
+/// The nodes are cloned as well, so the clone does not share any node with the original ring.
+impl<N, B> Clone for HashRing<N, B>
+where
+    N: Clone,
+    B: Clone,
+{
+    fn clone(&self) -> Self {
+        let mut cloned_master_nodes = HashMap::new();
+        let virtual_nodes = self
+            .virtual_nodes
+            .iter()
+            .map(|(position, master_node)| {
+                let cloned_master_node = cloned_master_nodes
+                    .entry(Arc::as_ptr(master_node))
+                    .or_insert_with(|| {
+                        Arc::new(MasterNode {
+                            node: master_node.node.clone(),
+                            id: master_node.id,
+                            weight: master_node.weight,
+                            requested_weight: master_node.requested_weight,
+                            reassigned_positions: master_node.reassigned_positions.clone(),
+                        })
+                    });
+                (*position, cloned_master_node.clone())
+            })
+            .collect();
+        let master_nodes_by_hash = self
+            .master_nodes_by_hash
+            .iter()
+            .map(|(node_hash, master_nodes)| {
+                let master_nodes = master_nodes
+                    .iter()
+                    .filter_map(|master_node| cloned_master_nodes.get(&master_node.as_ptr()))
+                    .map(Arc::downgrade)
+                    .collect();
+                (*node_hash, master_nodes)
+            })
+            .collect();
+        Self {
+            virtual_nodes,
+            master_nodes_by_hash,
+            hash_builder: self.hash_builder.clone(),
+            virtual_node_fn: self.virtual_node_fn.clone(),
+            total_virtual_node_budget: self.total_virtual_node_budget,
+            replication_factor: self.replication_factor,
+            collision_policy: self.collision_policy,
+            collision_callback: self.collision_callback.clone(),
+            event_callback: self.event_callback.clone(),
+            node_count: self.node_count,
+            generation: self.generation,
+            version: self.version,
+        }
+    }
+}
+
+/// Compares the nodes and virtual nodes of two rings.
+///
+/// Two rings are equal if the same nodes with the same weights occupy the same positions, so they route every key
+/// to equal nodes. Their configuration, e.g. the hasher or the generation, is not compared.
+///
+/// # Examples
+///
+/// ```
+/// use hulahoop::HashRing;
+///
+/// let mut ring: HashRing<&str, _> = HashRing::default();
+/// ring.insert("10.0.0.1:1234", 10);
+///
+/// let mut snapshot = ring.clone();
+/// assert_eq!(ring, snapshot);
+///
+/// snapshot.insert("10.0.0.2:1234", 10);
+/// assert_ne!(ring, snapshot);
+/// ```
+impl<N, B> PartialEq for HashRing<N, B>
+where
+    N: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.virtual_nodes.len() == other.virtual_nodes.len()
+            && self
+                .virtual_nodes
+                .iter()
+                .zip(other.virtual_nodes.iter())
+                .all(
+                    |((position, master_node), (other_position, other_master_node))| {
+                        position == other_position
+                            && master_node.node == other_master_node.node
+                            && master_node.weight == other_master_node.weight
+                            && master_node.requested_weight == other_master_node.requested_weight
+                    },
+                )
+    }
+}
+
+impl<N, B> Eq for HashRing<N, B> where N: Eq {}
+
+/// Consumes the ring into all node-weight pairs in arbitrary order, like [iter](HashRing::iter) with owned nodes.
+///
+/// The weight is the actual number of virtual nodes. Nodes still referenced by an upgraded
+/// [Weak](HashRing::weak_of) reference cannot be moved out of the ring, so they are cloned instead.
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::HashMap;
+/// use hulahoop::HashRing;
+///
+/// let mut ring: HashRing<String, _> = HashRing::default();
+///
+/// ring.insert("10.0.0.1:1234".to_string(), 10);
+/// ring.insert("10.0.0.2:1234".to_string(), 5);
+///
+/// let weights = ring.into_iter().collect::<HashMap<_, _>>();
+/// assert_eq!(weights.get("10.0.0.1:1234"), Some(&10));
+/// assert_eq!(weights.get("10.0.0.2:1234"), Some(&5));
+/// ```
+impl<N, B> IntoIterator for HashRing<N, B>
+where
+    N: Clone,
+{
+    type Item = (N, u64);
+    type IntoIter = IntoIter<N>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        let nodes = Self::into_master_nodes(self.virtual_nodes)
+            .map(|master_node| (master_node.node, master_node.weight.get()))
+            .collect::<Vec<_>>();
+        IntoIter {
+            inner: nodes.into_iter(),
+        }
+    }
+}
+
+/// Creates a ring from nodes and their weights like [build_from](HashRing::build_from), using the default hasher.
+///
+/// # Examples
+///
+/// ```
+/// use hulahoop::HashRing;
+///
+/// let endpoints = ["10.0.0.1:1234", "10.0.0.2:1234"];
+/// let ring: HashRing<_, _> = endpoints.into_iter().map(|endpoint| (endpoint, 10)).collect();
+/// assert_eq!(ring.len(), 2);
+/// ```
+impl<N> FromIterator<(N, u64)> for HashRing<N, DefaultBuildHasher>
+where
+    N: Hash + Eq,
+{
+    fn from_iter<I: IntoIterator<Item = (N, u64)>>(iter: I) -> Self {
+        Self::build_from(iter, DefaultBuildHasher::default())
+    }
+}
+
+/// Creates a ring from nodes and their non-zero weights like [build_from](HashRing::build_from),
+/// using the default hasher.
+///
+/// # Examples
+///
+/// ```
+/// use hulahoop::{HashRing, Weight};
+///
+/// let weight = Weight::new(10).expect("weight must not be 0");
+/// let ring: HashRing<_, _> = ["10.0.0.1:1234", "10.0.0.2:1234"].map(|node| (node, weight)).into_iter().collect();
+/// assert_eq!(ring.total_weight(), 20);
+/// ```
+impl<N> FromIterator<(N, Weight)> for HashRing<N, DefaultBuildHasher>
+where
+    N: Hash + Eq,
+{
+    fn from_iter<I: IntoIterator<Item = (N, Weight)>>(iter: I) -> Self {
+        Self::build_from(
+            iter.into_iter().map(|(node, weight)| (node, weight.get())),
+            DefaultBuildHasher::default(),
+        )
+    }
+}
+
+/// Inserts all nodes with their weights, ignoring nodes with a weight of 0 like [insert](HashRing::insert).
+///
+/// Prefer extending with a [Weight](Weight) if weights are computed programmatically, e.g. as a difference that may
+/// underflow to 0: a zero weight then has to be handled explicitly when creating the `Weight` instead of silently dropping the node.
+impl<N, B> Extend<(N, u64)> for HashRing<N, B>
+where
+    N: Hash,
+    B: BuildHasher,
+{
+    fn extend<I: IntoIterator<Item = (N, u64)>>(&mut self, iter: I) {
+        for (node, weight) in iter {
+            self.insert(node, weight);
+        }
+    }
+}
+
+/// Inserts all nodes with their non-zero weights.
+///
+/// This is the type-safe alternative to extending with `(N, u64)`, as no node can be dropped because of a zero weight.
+///
+/// # Examples
+///
+/// ```
+/// use hulahoop::{HashRing, Weight};
+///
+/// let mut ring: HashRing<&str, _> = HashRing::default();
+///
+/// let weights = [("10.0.0.1:1234", 10), ("10.0.0.2:1234", 5)];
+/// ring.extend(weights.map(|(node, weight)| (node, Weight::new(weight).expect("weight must not be 0"))));
+/// assert_eq!(ring.len(), 2);
+/// ```
+impl<N, B> Extend<(N, Weight)> for HashRing<N, B>
+where
+    N: Hash,
+    B: BuildHasher,
+{
+    fn extend<I: IntoIterator<Item = (N, Weight)>>(&mut self, iter: I) {
+        for (node, weight) in iter {
+            self.insert(node, weight.get());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn adding_a_node_works() {
+        let mut ring: HashRing<&str, _> = HashRing::new();
+        let node = "10.0.0.1:12345";
+        ring.insert(node, 1);
+
+        assert_eq!(ring.virtual_nodes.len(), 1);
+    }
+
+    #[test]
+    fn adding_a_node_with_many_virtual_nodes_works() {
+        let mut ring: HashRing<&str, _> = HashRing::new();
+        let node = "10.0.0.1:12345";
+        ring.insert(node, 100);
+
+        assert_eq!(ring.virtual_nodes.len(), 100);
+    }
+
+    #[test]
+    fn adding_multiple_nodes_with_many_virtual_nodes_works() {
+        let mut ring: HashRing<&str, _> = HashRing::new();
+        let node_1 = "10.0.0.1:12345";
+        let node_2 = "20.0.0.1:12345";
+        ring.insert(node_1, 100);
+        ring.insert(node_2, 500);
+
+        assert_eq!(ring.virtual_nodes.len(), 600);
+    }
+
+    #[test]
+    fn removing_a_node_works() {
+        let mut ring: HashRing<&str, _> = HashRing::new();
+        let node = "10.0.0.1:12345";
+        ring.insert(node, 1);
+        assert_eq!(ring.virtual_nodes.len(), 1);
+
+        ring.remove(&node);
+        assert_eq!(ring.virtual_nodes.len(), 0);
+    }
+
+    #[test]
+    fn removing_a_node_with_many_virtual_nodes_works() {
+        let mut ring: HashRing<&str, _> = HashRing::new();
+        let node = "10.0.0.1:12345";
+        ring.insert(node, 100);
+        assert_eq!(ring.virtual_nodes.len(), 100);
+
+        let nodes_removed = ring.remove(&node);
+        assert_eq!(nodes_removed, 100);
+        assert_eq!(ring.virtual_nodes.len(), 0);
+    }
+
+    #[test]
+    fn removing_multiple_nodes_with_many_virtual_nodes_works() {
+        let mut ring: HashRing<&str, _> = HashRing::new();
+        let node_1 = "10.0.0.1:12345";
+        let node_2 = "20.0.0.1:12345";
+        ring.insert(node_1, 100);
+        ring.insert(node_2, 500);
+        assert_eq!(ring.virtual_nodes.len(), 600);
+        assert_eq!(ring.len(), 2);
+
+        let nodes_removed = ring.remove(&node_1);
+        assert_eq!(nodes_removed, 100);
+        assert_eq!(ring.virtual_nodes.len(), 500);
+        assert_eq!(ring.len(), 1);
+
+        let nodes_removed = ring.remove(&node_2);
+        assert_eq!(nodes_removed, 500);
+        assert_eq!(ring.len(), 0);
+    }
+
+    #[test]
+    fn adding_one_node_and_getting_works() {
+        let mut ring: HashRing<&str, _> = HashRing::new();
+        let node = "10.0.0.1:12345";
+        ring.insert(node, 1);
+
+        let node_for_val_a = ring.get("abc");
+        let node_for_val_b = ring.get(12345);
+
+        assert_eq!(node_for_val_a, Some(&node));
+        assert_eq!(node_for_val_b, Some(&node));
+    }
+
+    #[test]
+    fn adding_multiple_nodes_and_getting_works() {
+        let mut ring: HashRing<&str, _> = HashRing::new();
+        let node_1 = "10.0.0.1:12345";
+        let node_2 = "20.0.0.1:12345";
+        let node_3 = "30.0.0.1:12345";
+        ring.insert(node_1, 1);
+        ring.insert(node_2, 1);
+        ring.insert(node_3, 1);
+
+        let node_for_val_a = ring.get("hula");
+        let node_for_val_b = ring.get(12345);
+        let node_for_val_c = ring.get(54321);
+        let node_for_val_d = ring.get(b"12345");
+
+        assert_eq!(node_for_val_a, Some(&node_3));
+        assert_eq!(node_for_val_b, Some(&node_2));
+        assert_eq!(node_for_val_c, Some(&node_3));
+        assert_eq!(node_for_val_d, Some(&node_1));
+    }
+
+    #[test]
+    fn iterating_over_the_ring_works() {
+        let mut ring: HashRing<&str, _> = HashRing::new();
+        let node_1 = "10.0.0.1:12345";
+        let node_2 = "20.0.0.1:12345";
+        let node_3 = "30.0.0.1:12345";
+        ring.insert(node_1, 2);
+        ring.insert(node_2, 4);
+        ring.insert(node_3, 6);
+
+        let nodes = ring.iter().collect::<HashSet<_>>();
+        assert_eq!(nodes.len(), 3);
+        assert!(nodes.contains(&(&node_1, 2)));
+        assert!(nodes.contains(&(&node_2, 4)));
+        assert!(nodes.contains(&(&node_3, 6)));
+    }
+
+    #[test]
+    fn adding_multiple_nodes_getting_and_removing_works() {
+        let mut ring: HashRing<&str, _> = HashRing::new();
+        let node_1 = "10.0.0.1:12345";
+        let node_2 = "20.0.0.1:12345";
+        let node_3 = "30.0.0.1:12345";
+        ring.insert(node_1, 1);
+        ring.insert(node_2, 1);
+        ring.insert(node_3, 1);
+
+        let key_1 = "hula";
+        let key_2 = 12345;
+        let key_3 = 54321;
+        let key_4 = b"12345";
+
+        {
+            let node_for_key_1 = ring.get(key_1);
+            let node_for_val_2 = ring.get(key_2);
+            let node_for_key_3 = ring.get(key_3);
+            let node_for_key_4 = ring.get(key_4);
+
+            assert_eq!(node_for_key_1, Some(&node_3));
+            assert_eq!(node_for_val_2, Some(&node_2));
+            assert_eq!(node_for_key_3, Some(&node_3));
+            assert_eq!(node_for_key_4, Some(&node_1));
+        }
+
+        ring.remove(&node_1);
+
+        {
+            let node_for_key_1 = ring.get(key_1);
+            let node_for_key_2 = ring.get(key_2);
+            let node_for_key_3 = ring.get(key_3);
+            let node_for_key_4 = ring.get(key_4);
+
+            assert_eq!(node_for_key_1, Some(&node_3));
+            assert_eq!(node_for_key_2, Some(&node_2));
+            assert_eq!(node_for_key_3, Some(&node_3));
+            // Only this value got remapped
+            assert_eq!(node_for_key_4, Some(&node_2));
+        }
+    }
+
+    #[test]
+    fn creating_a_hashring_with_custom_hasher_and_adding_and_getting_works() {
+        use rustc_hash::FxHasher;
+        let mut ring: HashRing<&str, _> =
+            HashRing::with_hasher(BuildHasherDefault::<FxHasher>::default());
+        let node = "10.0.0.1:12345";
+        ring.insert(node, 1);
+
+        let node_for_val_a = ring.get("abc");
+        let node_for_val_b = ring.get(12345);
+
+        assert_eq!(node_for_val_a, Some(&node));
+        assert_eq!(node_for_val_b, Some(&node));
+    }
+
+    #[derive(Default, Debug)]
+    struct CollisionHasher;
+    impl Hasher for CollisionHasher {
+        fn finish(&self) -> u64 {
+            // To cause hash collisions
+            1
+        }
+
+        fn write(&mut self, _bytes: &[u8]) {}
+    }
+
+    #[test]
+    fn virtual_node_collisions_is_not_an_issue() {
+        let mut ring: HashRing<&str, _> =
+            HashRing::with_hasher(BuildHasherDefault::<CollisionHasher>::default());
+        let node = "10.0.0.1:12345";
+        let node_2 = "10.0.0.2:12345";
+        assert!(ring.insert(node, 3).is_none());
+        assert_eq!(ring.insert(node_2, 2), Some(node));
+
+        let node_for_val_a = ring.get("abc");
+        let node_for_val_b = ring.get(12345);
+
+        assert_eq!(node_for_val_a, Some(&node_2));
+        assert_eq!(node_for_val_b, Some(&node_2));
+
+        // Because of collisions, only 1 virtual node was added
+        assert_eq!(ring.remove(&node_2), 1);
+        assert!(ring.is_empty())
+    }
+
+    #[test]
+    fn adding_the_same_node_twice_works() {
+        let mut ring: HashRing<&str, _> = HashRing::new();
+        let node = "10.0.0.1:12345";
+        assert_eq!(ring.insert(node, 5), None);
+        assert_eq!(ring.insert(node, 3), Some(node));
+
+        assert_eq!(ring.len(), 1);
+    }
+
+    #[test]
+    fn contains_node_works() {
+        let mut ring: HashRing<&str, _> = HashRing::new();
+        let node_1 = "10.0.0.1:12345";
+        ring.insert(node_1, 5);
+        assert!(ring.contains_node(&node_1));
+
+        let node_2 = "10.0.0.2:12345";
+        ring.insert(node_2, 10);
+        assert!(ring.contains_node(&node_2));
+
+        ring.remove(&node_1);
+        assert!(!ring.contains_node(&node_1));
+        assert!(ring.contains_node(&node_2));
+
+        ring.remove(&node_2);
+        assert!(!ring.contains_node(&node_2));
+    }
+
+    #[test]
+    fn custom_virtual_node_fn_is_used_for_placement() {
+        let mut ring: HashRing<&str, _> = HashRing::with_virtual_node_fn(
+            BuildHasherDefault::<std::collections::hash_map::DefaultHasher>::default(),
+            |_, node, index| {
+                if *node == "10.0.0.1:12345" {
+                    index
+                } else {
+                    u64::MAX - index
+                }
+            },
+        );
+        ring.insert("10.0.0.1:12345", 3);
+        ring.insert("10.0.0.2:12345", 2);
+
+        assert_eq!(
+            ring.virtual_nodes.keys().copied().collect::<Vec<_>>(),
+            vec![0, 1, 2, u64::MAX - 1, u64::MAX]
+        );
+        assert_eq!(ring.len(), 2);
+        assert_eq!(ring.remove(&"10.0.0.2:12345"), 2);
+        assert_eq!(ring.virtual_nodes.len(), 3);
+    }
+
+    #[test]
+    fn agrees_with_detects_placement_drift() {
+        let keys = (0..100).collect::<Vec<_>>();
+        let mut ring: HashRing<&str, _> = HashRing::new();
+        ring.insert("10.0.0.1:12345", 10);
+        ring.insert("10.0.0.2:12345", 10);
+
+        let mut same: HashRing<&str, _> = HashRing::new();
+        same.insert("10.0.0.2:12345", 10);
+        same.insert("10.0.0.1:12345", 10);
+        assert!(ring.agrees_with(&same, &keys));
+
+        let mut reweighted: HashRing<&str, _> = HashRing::new();
+        reweighted.insert("10.0.0.1:12345", 10);
+        reweighted.insert("10.0.0.2:12345", 20);
+        assert!(!ring.agrees_with(&reweighted, &keys));
+
+        assert!(ring.agrees_with(&HashRing::new(), &[] as &[u64]));
+    }
+
+    #[test]
+    fn inserting_respects_the_total_virtual_node_budget() {
+        let mut ring = HashRingBuilder::default()
+            .total_virtual_node_budget(25)
+            .build();
+        assert_eq!(ring.remaining_budget(), 25);
+
+        ring.insert("10.0.0.1:12345", 10);
+        ring.insert("10.0.0.2:12345", 10);
+        assert_eq!(ring.remaining_budget(), 5);
+
+        // Re-inserting a node frees its own virtual nodes first
+        assert_eq!(ring.insert("10.0.0.1:12345", 14), Some("10.0.0.1:12345"));
+        assert_eq!(ring.remaining_budget(), 1);
+
+        ring.insert("10.0.0.3:12345", 10);
+        assert_eq!(ring.remaining_budget(), 0);
+        assert_eq!(ring.virtual_nodes.len(), 25);
+
+        assert_eq!(ring.insert("10.0.0.4:12345", 10), None);
+        assert!(!ring.contains_node(&"10.0.0.4:12345"));
+
+        ring.remove(&"10.0.0.2:12345");
+        assert_eq!(ring.remaining_budget(), 10);
+    }
+
+    #[test]
+    fn remaining_budget_without_a_budget_is_unbounded() {
+        let mut ring: HashRing<&str, _> = HashRing::new();
+        ring.insert("10.0.0.1:12345", 10);
+        assert_eq!(ring.remaining_budget(), u64::MAX);
+    }
+
+    #[test]
+    fn ownership_map_coalesces_arcs_and_handles_wraparound() {
+        let mut ring: HashRing<&str, _> = HashRing::with_virtual_node_fn(
+            BuildHasherDefault::<std::collections::hash_map::DefaultHasher>::default(),
+            |_, node, index| match *node {
+                "a" => 10 + index,
+                _ => 100 + index,
+            },
+        );
+        assert!(ring.ownership_map().is_empty());
+
+        ring.insert("a", 3);
+        assert_eq!(ring.ownership_map(), vec![(0, u64::MAX, &"a")]);
+
+        ring.insert("b", 2);
+        assert_eq!(
+            ring.ownership_map(),
+            vec![(0, 12, &"a"), (13, 101, &"b"), (102, u64::MAX, &"a")]
+        );
+    }
+
+    #[test]
+    fn ownership_map_with_a_virtual_node_at_the_top_of_the_ring() {
+        let mut ring: HashRing<&str, _> = HashRing::with_virtual_node_fn(
+            BuildHasherDefault::<std::collections::hash_map::DefaultHasher>::default(),
+            |_, node, _| match *node {
+                "a" => 10,
+                _ => u64::MAX,
+            },
+        );
+        ring.insert("a", 1);
+        ring.insert("b", 1);
+
+        assert_eq!(
+            ring.ownership_map(),
+            vec![(0, 10, &"a"), (11, u64::MAX, &"b")]
+        );
+    }
+
+    #[test]
+    fn weak_references_do_not_prevent_reclaiming_removed_nodes() {
+        let mut ring: HashRing<String, _> = HashRing::new();
+        let node = "10.0.0.1:12345".to_string();
+        ring.insert(node.clone(), 10);
+        assert!(ring.weak_of(&"10.0.0.2:12345".to_string()).is_none());
+
+        let weak = ring.weak_of(&node).unwrap();
+        assert_eq!(weak.upgrade().unwrap().node(), &node);
+
+        // Re-inserting the node returns the owned old node despite the weak reference
+        assert_eq!(ring.insert(node.clone(), 5), Some(node.clone()));
+        assert!(weak.upgrade().is_none());
+    }
+
+    #[test]
+    fn upserting_only_changes_the_delta_of_virtual_nodes() {
+        let mut ring: HashRing<&str, _> = HashRing::new();
+        let node_1 = "10.0.0.1:12345";
+        let node_2 = "10.0.0.2:12345";
+        assert_eq!(ring.upsert(node_1, 10), None);
+        ring.insert(node_2, 10);
+        let positions_before = ring.virtual_nodes.keys().copied().collect::<HashSet<_>>();
+
+        assert_eq!(ring.upsert(node_1, 15), Some(10));
+        let positions_after = ring.virtual_nodes.keys().copied().collect::<HashSet<_>>();
+        assert!(positions_before.is_subset(&positions_after));
+        assert_eq!(positions_after.len(), 25);
+
+        assert_eq!(ring.upsert(node_1, 5), Some(15));
+        let positions_shrunk = ring.virtual_nodes.keys().copied().collect::<HashSet<_>>();
+        assert!(positions_shrunk.is_subset(&positions_before));
+        assert_eq!(positions_shrunk.len(), 15);
+        assert!(ring.iter().any(|node| node == (&node_1, 5)));
+
+        assert_eq!(ring.remove(&node_1), 5);
+        assert_eq!(ring.virtual_nodes.len(), 10);
+    }
+
+    #[test]
+    fn removing_a_node_keeps_positions_taken_over_by_other_nodes() {
+        let mut ring: HashRing<&str, _> = HashRing::with_virtual_node_fn(
+            BuildHasherDefault::<std::collections::hash_map::DefaultHasher>::default(),
+            |_, node, index| match *node {
+                "a" => index,
+                _ => 3 - index,
+            },
+        );
+        ring.insert("a", 3);
+        ring.insert("b", 2);
+        assert_eq!(ring.virtual_nodes.len(), 4);
+
+        assert_eq!(ring.remove(&"a"), 2);
+        assert_eq!(ring.get_by_hash(0), Some(&"b"));
+        assert_eq!(ring.virtual_nodes.len(), 2);
+    }
+
+    #[test]
+    fn weights_map_round_trips_through_the_ring() {
+        let weights = HashMap::from([
+            ("10.0.0.1:12345".to_string(), 10),
+            ("10.0.0.2:12345".to_string(), 20),
+            ("10.0.0.3:12345".to_string(), 30),
+        ]);
+        let ring = HashRing::from_weights(
+            weights.clone(),
+            BuildHasherDefault::<std::collections::hash_map::DefaultHasher>::default(),
+        );
+
+        assert_eq!(ring.len(), 3);
+        assert_eq!(ring.virtual_nodes.len(), 60);
+        assert_eq!(ring.to_weights_map(), weights);
+    }
+
+    #[test]
+    fn getting_while_excluding_positions_skips_them() {
+        let mut ring: HashRing<&str, _> = HashRing::with_virtual_node_fn(
+            BuildHasherDefault::<std::collections::hash_map::DefaultHasher>::default(),
+            |_, node, index| match *node {
+                "a" => 10 + index,
+                _ => 100 + index,
+            },
+        );
+        ring.insert("a", 2);
+        ring.insert("b", 2);
+        let key = 1234;
+        let key_hash = ring.hash_builder.hash_one(key);
+        let owner = ring.get(key);
+
+        assert_eq!(ring.get_excluding_positions(key, &HashSet::new()), owner);
+        assert_eq!(ring.get_by_hash(key_hash), owner);
+        assert_eq!(
+            ring.get_excluding_positions(key, &HashSet::from([10, 11])),
+            Some(&"b")
+        );
+        assert_eq!(
+            ring.get_excluding_positions(key, &HashSet::from([100, 101])),
+            Some(&"a")
+        );
+        assert_eq!(
+            ring.get_excluding_positions(key, &HashSet::from([10, 11, 100, 101])),
+            None
+        );
+    }
+
+    #[test]
+    fn extending_the_ring_works() {
+        let mut ring: HashRing<&str, _> = HashRing::new();
+        ring.extend([("10.0.0.1:12345", 10), ("10.0.0.2:12345", 0)]);
+        assert_eq!(ring.len(), 1);
+
+        ring.extend([
+            ("10.0.0.2:12345", Weight::new(5).unwrap()),
+            ("10.0.0.3:12345", Weight::from(NonZeroU64::new(5).unwrap())),
+        ]);
+        assert_eq!(ring.len(), 3);
+        assert_eq!(ring.virtual_nodes.len(), 20);
+    }
+
+    #[test]
+    fn getting_the_reverse_cycle_walks_counter_clockwise() {
+        let mut ring: HashRing<&str, _> = HashRing::with_virtual_node_fn(
+            BuildHasherDefault::<std::collections::hash_map::DefaultHasher>::default(),
+            |_, node, index| match *node {
+                "a" => 10 + 100 * index,
+                "b" => 20 + 100 * index,
+                _ => 30 + 100 * index,
+            },
+        );
+        assert_eq!(ring.get_reverse_cycle("key").next(), None);
+
+        ring.insert("a", 2);
+        ring.insert("b", 2);
+        ring.insert("c", 1);
+
+        for (key_hash, expected) in [
+            (15, vec!["b", "a", "c"]),
+            (25, vec!["c", "b", "a"]),
+            (115, vec!["b", "a", "c"]),
+            (200, vec!["a", "b", "c"]),
+        ] {
+            assert_eq!(
+                ring.get_reverse_cycle_by_hash(key_hash)
+                    .copied()
+                    .collect::<Vec<_>>(),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn rebuilding_with_a_strategy_keeps_nodes_and_weights() {
+        let mut ring: HashRing<&str, _> = HashRing::new();
+        ring.insert("10.0.0.1:12345", 10);
+        ring.insert("10.0.0.2:12345", 20);
+
+        let ring = ring.rebuild_with(|_: &_, node: &&str, index| {
+            if *node == "10.0.0.1:12345" {
+                index
+            } else {
+                1000 + index
+            }
+        });
+
+        assert_eq!(ring.len(), 2);
+        assert_eq!(
+            ring.virtual_nodes.keys().copied().collect::<Vec<_>>(),
+            (0..10).chain(1000..1020).collect::<Vec<_>>()
+        );
+        assert_eq!(ring.get_by_hash(5), Some(&"10.0.0.1:12345"));
+        assert_eq!(ring.get_by_hash(15), Some(&"10.0.0.2:12345"));
+    }
+
+    #[test]
+    fn rebuilding_keeps_nodes_referenced_elsewhere() {
+        let mut ring: HashRing<&str, _> = HashRing::new();
+        ring.insert("10.0.0.1:12345", 10);
+        ring.insert("10.0.0.2:12345", 20);
+        let master_node = ring.weak_of(&"10.0.0.1:12345").unwrap().upgrade();
+
+        let ring = ring.rebuild_with(|_: &_, node: &&str, index| {
+            if *node == "10.0.0.1:12345" {
+                index
+            } else {
+                1000 + index
+            }
+        });
+
+        assert!(master_node.is_some());
+        assert_eq!(ring.len(), 2);
+        assert_eq!(ring.get_by_hash(5), Some(&"10.0.0.1:12345"));
+        assert_eq!(ring.get_by_hash(15), Some(&"10.0.0.2:12345"));
+    }
+
+    #[test]
+    fn consuming_keeps_nodes_referenced_elsewhere() {
+        let mut ring: HashRing<&str, _> = HashRing::new();
+        ring.insert("10.0.0.1:12345", 10);
+        ring.insert("10.0.0.2:12345", 20);
+        let master_node = ring.weak_of(&"10.0.0.1:12345").unwrap().upgrade();
+
+        let weights = ring.into_iter().collect::<HashMap<_, _>>();
+
+        assert!(master_node.is_some());
+        assert_eq!(
+            weights,
+            HashMap::from([("10.0.0.1:12345", 10), ("10.0.0.2:12345", 20)])
+        );
+    }
+
+    #[test]
+    fn merging_keeps_nodes_referenced_elsewhere() {
+        let mut shard_1: HashRing<&str, _> = HashRing::new();
+        shard_1.insert("10.0.0.1:12345", 10);
+        shard_1.insert("10.0.0.2:12345", 20);
+        let mut shard_2: HashRing<&str, _> = HashRing::new();
+        shard_2.insert("10.0.0.2:12345", 5);
+        shard_2.insert("10.0.0.3:12345", 5);
+        let master_node = shard_1.weak_of(&"10.0.0.1:12345").unwrap().upgrade();
+
+        let mut ring = HashRing::new();
+        ring.merge_all([shard_1, shard_2]);
+
+        assert!(master_node.is_some());
+        assert_eq!(
+            ring.to_weights_map(),
+            HashMap::from([
+                ("10.0.0.1:12345", 10),
+                ("10.0.0.2:12345", 5),
+                ("10.0.0.3:12345", 5)
+            ])
+        );
+    }
+
+    #[test]
+    fn finding_a_key_for_a_node_works() {
+        let mut ring: HashRing<&str, _> = HashRing::new();
+        let nodes = ["10.0.0.1:12345", "10.0.0.2:12345", "10.0.0.3:12345"];
+        for node in nodes {
+            ring.insert(node, 10);
+        }
+        let candidates = (0..1000u32)
+            .map(|i| i.to_be_bytes().to_vec())
+            .collect::<Vec<_>>();
+
+        for node in nodes {
+            let key = ring
+                .find_key_for(&node, candidates.iter().map(|key| key.as_slice()))
+                .unwrap();
+            assert_eq!(ring.get(key.as_slice()), Some(&node));
+            let first_match = candidates
+                .iter()
+                .find(|key| ring.get(key.as_slice()) == Some(&node));
+            assert_eq!(Some(&key), first_match);
+        }
+        assert_eq!(
+            ring.find_key_for(
+                &"10.0.0.4:12345",
+                candidates.iter().map(|key| key.as_slice())
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn counting_ownership_runs_merges_the_ends_of_the_ring() {
+        let mut ring: HashRing<&str, _> = HashRing::with_virtual_node_fn(
+            BuildHasherDefault::<std::collections::hash_map::DefaultHasher>::default(),
+            |_, node, index| match (*node, index) {
+                ("a", 0) => 10,
+                ("a", 1) => 20,
+                ("a", _) => 50,
+                ("b", 0) => 30,
+                ("b", _) => 40,
+                (_, _) => 60,
+            },
+        );
+        ring.insert("a", 3);
+        ring.insert("b", 2);
+        // a a b b a
+        assert_eq!(ring.ownership_runs(), 2);
+
+        ring.upsert("a", 2);
+        // a a b b
+        assert_eq!(ring.ownership_runs(), 2);
+
+        ring.insert("c", 1);
+        // a a b b c
+        assert_eq!(ring.ownership_runs(), 3);
+    }
+
+    #[test]
+    fn try_insert_rejects_invalid_weights_without_modifying_the_ring() {
+        let mut ring = HashRingBuilder::default()
+            .total_virtual_node_budget(20)
+            .build();
+        assert_eq!(ring.try_insert("10.0.0.1:12345", 10), Ok(None));
+        assert_eq!(
+            ring.try_insert("10.0.0.1:12345", 20),
+            Ok(Some("10.0.0.1:12345"))
+        );
+        assert_eq!(
+            ring.try_insert("10.0.0.1:12345", 0),
+            Err(HulahoopError::ZeroWeight)
+        );
+        assert_eq!(
+            ring.try_insert("10.0.0.1:12345", u64::MAX),
+            Err(HulahoopError::WeightTooLarge {
+                requested: u64::MAX,
+                max: HashRing::<&str, ()>::MAX_WEIGHT
+            })
+        );
+
+        let error = ring.try_insert("10.0.0.2:12345", 1).unwrap_err();
+        assert_eq!(
+            error,
+            HulahoopError::BudgetExceeded {
+                requested: 1,
+                remaining: 0
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "a weight of 1 exceeds the remaining virtual node budget of 0"
+        );
+        assert_eq!(ring.len(), 1);
+        assert_eq!(ring.virtual_nodes.len(), 20);
+    }
+
+    #[test]
+    fn try_insert_rejects_colliding_nodes_without_modifying_the_ring() {
+        let mut ring: HashRing<&str, _> = HashRing::with_virtual_node_fn(
+            BuildHasherDefault::<std::collections::hash_map::DefaultHasher>::default(),
+            |_, node, index| match (*node, index) {
+                ("a", _) => index,
+                ("b", 0) => 0,
+                ("c", 0) => 10,
+                ("d", 0) => 1,
+                (_, _) => index - 1,
+            },
+        );
+        assert_eq!(ring.try_insert("a", 3), Ok(None));
+        // The first virtual node of "b" lands on the first virtual node of "a", so "b" would replace "a"
+        assert_eq!(ring.try_insert("b", 1), Err(HulahoopError::NodeCollision));
+        // The first virtual node of "d" only lands on the second virtual node of "a"
+        assert_eq!(
+            ring.try_insert("d", 1),
+            Err(HulahoopError::VirtualNodeCollision { colliding: 1 })
+        );
+        // The second and third virtual node of "c" land on virtual nodes of "a"
+        let error = ring.try_insert("c", 3).unwrap_err();
+        assert_eq!(error, HulahoopError::VirtualNodeCollision { colliding: 2 });
+        assert_eq!(error.to_string(), "2 virtual nodes of the node collide");
+        assert_eq!(ring.len(), 1);
+        assert_eq!(ring.virtual_nodes.len(), 3);
+
+        // Updating a node does not collide with itself
+        assert_eq!(ring.try_insert("a", 5), Ok(Some("a")));
+        assert_eq!(ring.try_insert("c", 1), Ok(None));
+    }
+
+    #[test]
+    fn getting_sticky_prefers_the_previous_node_while_it_is_present() {
+        let mut ring: HashRing<&str, _> = HashRing::new();
+        assert_eq!(ring.get_sticky("key", Some(&"10.0.0.1:12345")), None);
+
+        ring.insert("10.0.0.1:12345", 10);
+        ring.insert("10.0.0.2:12345", 10);
+        for key in 0..100 {
+            assert_eq!(
+                ring.get_sticky(key, Some(&"10.0.0.1:12345")),
+                Some(&"10.0.0.1:12345")
+            );
+        }
+
+        ring.remove(&"10.0.0.1:12345");
+        for key in 0..100 {
+            assert_eq!(
+                ring.get_sticky(key, Some(&"10.0.0.1:12345")),
+                Some(&"10.0.0.2:12345")
+            );
+        }
+    }
+
+    #[test]
+    fn getting_the_positions_of_a_node_works() {
+        let mut ring: HashRing<&str, _> = HashRing::with_virtual_node_fn(
+            BuildHasherDefault::<std::collections::hash_map::DefaultHasher>::default(),
+            |_, node, index| match *node {
+                "a" => 30 - 10 * index,
+                "b" => 15 + 10 * index,
+                _ => 100,
+            },
+        );
+        ring.insert("a", 3);
+        ring.insert("b", 2);
+
+        assert_eq!(ring.positions_of(&"a"), vec![10, 20, 30]);
+        assert_eq!(
+            ring.positions_of_iter(&"b").collect::<Vec<_>>(),
+            vec![15, 25]
+        );
+        assert_eq!(ring.positions_of_iter(&"c").next(), None);
+    }
+
+    #[test]
+    fn cloning_copies_the_generation_and_does_not_share_nodes() {
+        let mut ring: HashRing<String, _> = HashRing::new();
+        let node = "10.0.0.1:12345".to_string();
+        ring.insert(node.clone(), 10);
+        let ring = ring.with_bumped_generation();
+
+        let mut cloned = ring.clone();
+        assert_eq!(cloned.generation(), 1);
+        assert_eq!(
+            cloned.virtual_nodes.keys().collect::<Vec<_>>(),
+            ring.virtual_nodes.keys().collect::<Vec<_>>()
+        );
+
+        // The owned node can be recovered from the clone as no master node is shared
+        assert_eq!(cloned.insert(node.clone(), 5), Some(node.clone()));
+        assert_eq!(ring.virtual_nodes.len(), 10);
+        assert_eq!(cloned.with_bumped_generation().generation(), 2);
+    }
+
+    #[test]
+    fn total_weight_sums_the_actual_weights() {
+        let mut ring: HashRing<&str, _> =
+            HashRing::with_hasher(BuildHasherDefault::<CollisionHasher>::default());
+        ring.insert("10.0.0.1:12345", 10);
+        // All virtual nodes collide
+        assert_eq!(ring.total_weight(), 1);
+
+        let mut ring: HashRing<&str, _> = HashRing::new();
+        ring.insert("10.0.0.1:12345", 10);
+        ring.insert("10.0.0.2:12345", 20);
+        assert_eq!(ring.total_weight(), 30);
+        ring.remove(&"10.0.0.2:12345");
+        assert_eq!(ring.total_weight(), 10);
+    }
+
+    #[test]
+    fn removing_the_lightest_and_heaviest_nodes_breaks_ties_by_hash() {
+        let mut ring: HashRing<&str, _> = HashRing::new();
+        let nodes = ["10.0.0.1:12345", "10.0.0.2:12345", "10.0.0.3:12345"];
+        for node in nodes {
+            ring.insert(node, 10);
+        }
+        ring.insert("10.0.0.4:12345", 1);
+        ring.insert("10.0.0.5:12345", 20);
+        let mut nodes_by_hash = nodes.to_vec();
+        nodes_by_hash.sort_by_key(|node| ring.hash_builder.hash_one(node));
+
+        assert_eq!(ring.remove_lightest(), Some(("10.0.0.4:12345", 1)));
+        assert_eq!(ring.remove_heaviest(), Some(("10.0.0.5:12345", 20)));
+        assert_eq!(ring.remove_lightest(), Some((nodes_by_hash[0], 10)));
+        assert_eq!(ring.remove_heaviest(), Some((nodes_by_hash[1], 10)));
+        assert_eq!(ring.len(), 1);
+        assert_eq!(ring.virtual_nodes.len(), 10);
+    }
+
+    #[test]
+    fn getting_salted_decorrelates_tenants() {
+        use rustc_hash::FxHasher;
+        use std::collections::hash_map::DefaultHasher;
+
+        fn assert_decorrelated<B: BuildHasher>(mut ring: HashRing<&str, B>) {
+            assert_eq!(ring.get_salted(1, "key"), None);
+            ring.insert("10.0.0.1:12345", 10);
+            ring.insert("10.0.0.2:12345", 10);
+
+            let keys = 0..1000;
+            let differently_routed = keys
+                .clone()
+                .filter(|key| ring.get_salted(1, key) != ring.get_salted(2, key))
+                .count();
+            // Both tenants are routed independently, so roughly half of the keys land on different nodes
+            assert!(differently_routed > 300 && differently_routed < 700);
+            assert!(keys
+                .into_iter()
+                .all(|key| ring.get_salted(1, key) == ring.get_salted(1, key)));
+        }
+
+        assert_decorrelated(HashRing::with_hasher(
+            BuildHasherDefault::<DefaultHasher>::default(),
+        ));
+        assert_decorrelated(HashRing::with_hasher(
+            BuildHasherDefault::<FxHasher>::default(),
+        ));
+    }
+
+    #[test]
+    fn deduplicating_positions_assigns_them_to_the_lowest_node_hash() {
+        let mut ring: HashRing<&str, _> = HashRing::with_virtual_node_fn(
+            BuildHasherDefault::<std::collections::hash_map::DefaultHasher>::default(),
+            |_, node, index| match (*node, index) {
+                ("a", _) => 10 + index,
+                ("b", 0) => 100,
+                ("b", _) => 10 + index,
+                (_, _) => 200,
+            },
+        );
+        ring.insert("a", 3);
+        ring.insert("b", 3);
+        // b took over the positions 11 and 12 of a, but the weight of a was not updated
+        assert_eq!(ring.total_weight(), 6);
+        assert_eq!(ring.virtual_nodes.len(), 4);
+
+        let (winner, loser) = if ring.hash_builder.hash_one("a") < ring.hash_builder.hash_one("b") {
+            ("a", "b")
+        } else {
+            ("b", "a")
+        };
+        assert_eq!(ring.deduplicate_positions(), 2);
+        assert_eq!(ring.get_by_hash(11), Some(&winner));
+        assert_eq!(ring.get_by_hash(12), Some(&winner));
+        assert!(ring.iter().any(|node| node == (&winner, 3)));
+        assert!(ring.iter().any(|node| node == (&loser, 1)));
+        assert_eq!(ring.total_weight(), 4);
+
+        assert_eq!(ring.deduplicate_positions(), 0);
+        assert_eq!(ring.total_weight(), 4);
+        assert_eq!(ring.remove(&loser), 1);
+        assert_eq!(ring.remove(&winner), 3);
+        assert!(ring.is_empty());
+    }
+
+    #[test]
+    fn deduplicating_positions_updates_weights_of_nodes_referenced_elsewhere() {
+        let mut ring: HashRing<&str, _> = HashRing::with_virtual_node_fn(
+            BuildHasherDefault::<std::collections::hash_map::DefaultHasher>::default(),
+            |_, node, index| match (*node, index) {
+                ("b", 0) => 100,
+                (_, _) => 10 + index,
+            },
+        );
+        ring.insert("a", 3);
+        ring.insert("b", 3);
+        let _weak_a = ring.weak_of(&"a").unwrap();
+        let _weak_b = ring.weak_of(&"b").unwrap();
+
+        assert_eq!(ring.deduplicate_positions(), 2);
+        assert_eq!(ring.total_weight(), 4);
+        assert_eq!(ring.iter().map(|(_, weight)| weight).sum::<u64>(), 4);
+    }
+
+    #[test]
+    fn getting_n_nodes_walks_clockwise_and_skips_selected_nodes() {
+        let mut ring = HashRingBuilder::with_hasher(BuildHasherDefault::<
+            std::collections::hash_map::DefaultHasher,
+        >::default())
+        .virtual_node_fn(|_, node: &&str, index| match *node {
+            "a" => 10 + 100 * index,
+            "b" => 20 + 100 * index,
+            _ => 30 + 100 * index,
+        })
+        .replication_factor(NonZeroUsize::new(2).unwrap())
+        .build();
+        assert!(ring.get_replicas("key").is_empty());
+
+        ring.insert("a", 2);
+        ring.insert("b", 2);
+        ring.insert("c", 1);
+        // 10 a, 20 b, 30 c, 110 a, 120 b
+        let key = 1234;
+        let expected = match ring.hash_builder.hash_one(key) {
+            0..=10 | 121.. => vec![&"a", &"b", &"c"],
+            11..=20 => vec![&"b", &"c", &"a"],
+            21..=30 => vec![&"c", &"a", &"b"],
+            31..=110 => vec![&"a", &"b", &"c"],
+            _ => vec![&"b", &"a", &"c"],
+        };
+        assert_eq!(ring.get_n(key, 3), expected);
+        assert_eq!(ring.get_n(key, 10), expected);
+        assert_eq!(ring.get_replicas(key), expected[..2].to_vec());
+        assert!(ring.get_n(key, 0).is_empty());
+    }
+
+    #[test]
+    fn rings_with_colliding_nodes_have_no_unreachable_nodes() {
+        let mut ring = HashRing::with_virtual_node_fn(
+            BuildHasherDefault::<std::collections::hash_map::DefaultHasher>::default(),
+            |_, node: &&str, index| match (*node, index) {
+                ("a", _) => 10 + index,
+                (_, 0) => 100,
+                (_, _) => 10 + index,
+            },
+        );
+        assert!(!ring.covers_all());
+
+        ring.insert("a", 3);
+        ring.insert("b", 3);
+        // All but the first position of "b" are shared with "a"
+        ring.deduplicate_positions();
+        assert!(ring.covers_all());
+        assert!(ring.unreachable_nodes().is_empty());
+
+        ring.remove(&"a");
+        assert!(ring.covers_all());
+        assert!(ring.unreachable_nodes().is_empty());
+    }
+
+    #[test]
+    fn increasing_weight_only_moves_keys_to_new_positions() {
+        let mut ring: HashRing<&str, _> = HashRing::default();
+        ring.insert("10.0.0.1:1234", 10);
+        ring.insert("10.0.0.2:1234", 10);
+        ring.insert("10.0.0.3:1234", 10);
+        let keys = (0..1000).collect::<Vec<_>>();
+        let before = keys
+            .iter()
+            .map(|key| *ring.get(key).unwrap())
+            .collect::<Vec<_>>();
+        let old_positions = ring.positions_of(&"10.0.0.1:1234");
+
+        assert_eq!(ring.upsert("10.0.0.1:1234", 20), Some(10));
+        let new_positions = ring
+            .positions_of(&"10.0.0.1:1234")
+            .into_iter()
+            .filter(|position| !old_positions.contains(position))
+            .collect::<HashSet<_>>();
+        assert_eq!(
+            old_positions.len() + new_positions.len(),
+            ring.positions_of(&"10.0.0.1:1234").len()
+        );
+
+        let mut moved = 0;
+        for (key, node) in keys.iter().zip(before) {
+            let after = *ring.get(key).unwrap();
+            if node == "10.0.0.1:1234" {
+                assert_eq!(after, node);
+            } else if after != node {
+                moved += 1;
+                assert_eq!(after, "10.0.0.1:1234");
+                let key_hash = ring.hash_builder.hash_one(key);
+                let position = ring
+                    .virtual_nodes
+                    .range(key_hash..)
+                    .chain(ring.virtual_nodes.range(..key_hash))
+                    .map(|(position, _)| *position)
+                    .next()
+                    .unwrap();
+                assert!(new_positions.contains(&position));
+            }
+        }
+        assert!(moved > 0);
+    }
+
+    #[test]
+    fn debug_output_summarises_the_ring() {
+        let mut ring = HashRing::with_virtual_node_fn(
+            BuildHasherDefault::<std::collections::hash_map::DefaultHasher>::default(),
+            |_, node: &&str, index| match *node {
+                "a" => 1 << 62,
+                _ => (3 << 62) + index,
+            },
+        );
+        assert!(format!("{ring:?}").contains("nodes: 0, virtual_nodes: 0, balance_factor: 0.0"));
+
+        ring.insert("a", 1);
+        assert!(format!("{ring:?}").contains("nodes: 1, virtual_nodes: 1, balance_factor: 1.0"));
+
+        ring.insert("b", 100);
+        // "a" owns half of the key space
+        let debug = format!("{ring:?}");
+        assert!(debug.contains("nodes: 2, virtual_nodes: 101, balance_factor: 1.0,"));
+        assert!(!debug.contains("positions"));
+        assert!(!debug.contains("\"b\""));
+        assert!(format!("{ring:#?}").contains("\"b\""));
+    }
+
+    #[test]
+    fn suggesting_rebalance_scales_weights_by_key_share() {
+        let mut ring = HashRing::with_virtual_node_fn(
+            BuildHasherDefault::<std::collections::hash_map::DefaultHasher>::default(),
+            |_, node: &&str, _| match *node {
+                "a" => u64::MAX,
+                _ => 1 << 62,
+            },
+        );
+        assert!(ring.suggest_rebalance(0.0).is_empty());
+
+        ring.insert("a", 1);
+        ring.insert("b", 1);
+        // "a" owns three quarters of the key space, "b" one quarter
+        assert_eq!(ring.suggest_rebalance(0.1), vec![(&"b", 2)]);
+        assert!(ring.suggest_rebalance(0.5).is_empty());
+    }
+
+    #[test]
+    fn getting_read_set_skips_down_nodes() {
+        let mut ring = HashRing::with_virtual_node_fn(
+            BuildHasherDefault::<std::collections::hash_map::DefaultHasher>::default(),
+            |_, node: &&str, index| match *node {
+                "a" => 10 + 100 * index,
+                "b" => 20 + 100 * index,
+                _ => 30 + 100 * index,
+            },
+        );
+        ring.insert("a", 2);
+        ring.insert("b", 2);
+        ring.insert("c", 1);
+        // 10 a, 20 b, 30 c, 110 a, 120 b
+        let key = 1234;
+        let expected = ring.get_n(key, 3);
+        assert_eq!(ring.get_read_set(key, 3, &HashSet::new()), expected);
+        assert_eq!(
+            ring.get_read_set(key, 3, &HashSet::from([expected[0]])),
+            expected[1..].to_vec()
+        );
+        assert_eq!(
+            ring.get_read_set(key, 1, &HashSet::from([expected[0]])),
+            vec![expected[1]]
+        );
+        assert!(ring
+            .get_read_set(key, 3, &HashSet::from([&"a", &"b", &"c"]))
+            .is_empty());
+    }
+
+    #[test]
+    fn building_from_nodes_matches_inserting_and_deduplicating() {
+        let hash_builder =
+            BuildHasherDefault::<std::collections::hash_map::DefaultHasher>::default();
+        let nodes = [("a", 10), ("b", 20), ("c", 5), ("d", 0), ("c", 30)];
+        let ring = HashRing::build_from(nodes, hash_builder.clone());
+        let mut inserted = HashRing::with_hasher(hash_builder.clone());
+        inserted.extend(nodes);
+        assert_eq!(ring.to_weights_map(), inserted.to_weights_map());
+        assert!(ring.virtual_nodes.keys().eq(inserted.virtual_nodes.keys()));
+
+        let colliding_nodes = [("a", 3), ("b", 3)];
+        let virtual_node_fn = |_: &_, node: &&str, index| match (*node, index) {
+            ("a", _) => 10 + index,
+            (_, 0) => 100,
+            (_, _) => 10 + index,
+        };
+        let mut built = HashRing::with_virtual_node_fn(hash_builder.clone(), virtual_node_fn);
+        built.insert_many(colliding_nodes);
+        for nodes in [colliding_nodes, [colliding_nodes[1], colliding_nodes[0]]] {
+            let mut ring = HashRing::with_virtual_node_fn(hash_builder.clone(), virtual_node_fn);
+            ring.extend(nodes);
+            ring.deduplicate_positions();
+            assert_eq!(
+                ring.iter().collect::<HashMap<_, _>>(),
+                built.iter().collect::<HashMap<_, _>>()
+            );
+            for (position, master_node) in &ring.virtual_nodes {
+                assert_eq!(built.virtual_nodes[position].node, master_node.node);
+            }
+        }
+    }
+
+    #[test]
+    fn building_from_nodes_keeps_distinct_nodes_with_colliding_hashes() {
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        struct Node(&'static str);
+
+        impl Hash for Node {
+            fn hash<H: Hasher>(&self, state: &mut H) {
+                0.hash(state);
+            }
+        }
+
+        let nodes = [(Node("a"), 3), (Node("bb"), 3), (Node("a"), 2)];
+        let ring = HashRing::build_from(
+            nodes.clone(),
+            BuildHasherDefault::<std::collections::hash_map::DefaultHasher>::default(),
+        );
+        // Both nodes compute the same positions, which are given to the node given first
+        assert_eq!(ring.len(), 2);
+        assert_eq!(ring.total_weight(), 3);
+
+        let virtual_node_fn = |_: &_, node: &Node, index| node.0.len() as u64 * 100 + index;
+        let mut built = HashRing::with_virtual_node_fn(
+            BuildHasherDefault::<std::collections::hash_map::DefaultHasher>::default(),
+            virtual_node_fn,
+        );
+        built.insert_many(nodes.clone());
+        let mut inserted = HashRing::with_virtual_node_fn(
+            BuildHasherDefault::<std::collections::hash_map::DefaultHasher>::default(),
+            virtual_node_fn,
+        );
+        inserted.extend(nodes);
+        assert_eq!(built.len(), 2);
+        assert_eq!(built.total_weight(), 5);
+        for (position, master_node) in &inserted.virtual_nodes {
+            assert_eq!(built.virtual_nodes[position].node, master_node.node);
+        }
+    }
+
+    #[test]
+    fn key_share_metrics_reflect_arc_lengths() {
+        let mut ring = HashRing::with_virtual_node_fn(
+            BuildHasherDefault::<std::collections::hash_map::DefaultHasher>::default(),
+            |_, node: &&str, _| match *node {
+                "a" => u64::MAX,
+                "b" => 1 << 62,
+                _ => 1 << 63,
+            },
+        );
+        ring.insert("a", 1);
+        ring.insert("b", 1);
+        // "a" owns three quarters of the key space, "b" one quarter
+        assert_eq!(ring.balance_factor(), 1.5);
+        assert_eq!(ring.key_share_stddev(), 0.25);
+
+        ring.insert("c", 1);
+        // "a" and "c" own half and a quarter of the key space now
+        assert_eq!(ring.balance_factor(), 1.5);
+        assert!((ring.key_share_stddev() - (1.0f64 / 72.0).sqrt()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn collision_callback_reports_nodes_losing_virtual_nodes() {
+        let collisions = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorded = collisions.clone();
+        let mut ring = HashRingBuilder::with_hasher(BuildHasherDefault::<
+            std::collections::hash_map::DefaultHasher,
+        >::default())
+        .virtual_node_fn(|_: &_, node: &&str, index| match (*node, index) {
+            ("b", 0) => 100,
+            ("c", 0) => 200,
+            ("c", _) => 10,
+            (_, _) => 10 + index,
+        })
+        .collision_callback(move |node: &&str, requested, achieved| {
+            recorded.lock().unwrap().push((*node, requested, achieved));
+        })
+        .build();
+
+        ring.insert("a", 3);
+        // "b" takes over the positions 11 and 12 of "a"
+        ring.insert("b", 3);
+        assert_eq!(
+            collisions.lock().unwrap().drain(..).collect::<Vec<_>>(),
+            vec![("a", 3, 1)]
+        );
+
+        // Changing the weight of "b" does not report "b" itself
+        ring.set_weight(&"b", 2);
+        assert!(collisions.lock().unwrap().is_empty());
+
+        // "c" takes over the last position of "a"
+        ring.insert("c", 2);
+        assert_eq!(
+            collisions.lock().unwrap().drain(..).collect::<Vec<_>>(),
+            vec![("a", 3, 0)]
+        );
+        assert!(!ring.contains_node(&"a"));
+    }
+
+    #[test]
+    fn reassigning_collisions_keeps_full_weight_with_disjoint_positions() {
+        let virtual_node_fn = |_: &_, node: &&str, index| match (*node, index) {
+            ("a", _) => 10 + index,
+            (_, 0) => 100,
+            (_, _) => 10 + index,
+        };
+        let mut ring = HashRingBuilder::with_hasher(BuildHasherDefault::<
+            std::collections::hash_map::DefaultHasher,
+        >::default())
+        .virtual_node_fn(virtual_node_fn)
+        .collision_policy(CollisionPolicy::Reassign)
+        .build();
+
+        ring.insert("a", 3);
+        ring.insert("b", 3);
+        // Positions 11 and 12 of "b" are owned by "a"
+        assert_eq!(ring.positions_of(&"a"), vec![10, 11, 12]);
+        assert_eq!(ring.positions_of(&"b"), vec![13, 14, 100]);
+        assert_eq!(ring.total_weight(), 6);
+        assert!(ring.unreachable_nodes().is_empty());
+
+        // Existing positions are kept, new ones are probed
+        assert_eq!(ring.upsert("b", 4), Some(3));
+        assert_eq!(ring.positions_of(&"b"), vec![13, 14, 15, 100]);
+        assert_eq!(ring.upsert("b", 2), Some(4));
+        assert_eq!(ring.positions_of(&"b"), vec![13, 100]);
+
+        let cloned = ring.clone();
+        assert_eq!(ring.remove(&"b"), 2);
+        assert_eq!(ring.positions_of(&"a"), vec![10, 11, 12]);
+        assert_eq!(ring.total_weight(), 3);
+        assert_eq!(cloned.positions_of(&"b"), vec![13, 100]);
+
+        // The first virtual node still identifies a node
+        assert_eq!(ring.insert("c", 1), None);
+        assert_eq!(ring.positions_of(&"c"), vec![100]);
+    }
+
+    #[test]
+    fn getting_with_weight_returns_the_weight_of_the_routed_node() {
+        let mut ring: HashRing<&str, _> = HashRing::default();
+        ring.insert("10.0.0.1:12345", 10);
+        ring.insert("10.0.0.2:12345", 20);
+        for key in 0..100 {
+            let (node, weight) = ring.get_with_weight(key).unwrap();
+            assert_eq!(Some(node), ring.get(key));
+            assert_eq!(weight, ring.positions_of(node).len() as u64);
+        }
+    }
+
+    #[test]
+    fn coalesced_digest_ignores_virtual_node_layout() {
+        let hash_builder =
+            BuildHasherDefault::<std::collections::hash_map::DefaultHasher>::default();
+        let virtual_node_fn = |_: &_, node: &&str, index| match *node {
+            "a" => 20 - 10 * index,
+            _ => 30 + index,
+        };
+        let mut ring = HashRing::with_virtual_node_fn(hash_builder.clone(), virtual_node_fn);
+        ring.insert("a", 2);
+        ring.insert("b", 1);
+        let mut other = HashRing::with_virtual_node_fn(hash_builder, virtual_node_fn);
+        other.insert("a", 1);
+        other.insert("b", 1);
+        // Keys up to 20 and beyond 30 are routed to "a" in both rings
+        assert_ne!(ring.positions_of(&"a"), other.positions_of(&"a"));
+        assert_eq!(ring.coalesced_digest(), other.coalesced_digest());
+
+        other.upsert("b", 2);
+        assert_ne!(ring.coalesced_digest(), other.coalesced_digest());
+    }
+
+    #[test]
+    fn getting_wraps_around_at_the_top_of_the_hash_space() {
+        let mut ring = HashRing::with_virtual_node_fn(
+            BuildHasherDefault::<std::collections::hash_map::DefaultHasher>::default(),
+            |_, node: &&str, _| match *node {
+                "a" => 100,
+                "b" => 200,
+                _ => u64::MAX,
+            },
+        );
+        ring.insert("a", 1);
+        for key_hash in [0, 99, 100, 101, u64::MAX - 1, u64::MAX] {
+            assert_eq!(ring.get_by_hash(key_hash), Some(&"a"));
+        }
+
+        ring.insert("b", 1);
+        assert_eq!(ring.get_by_hash(0), Some(&"a"));
+        assert_eq!(ring.get_by_hash(100), Some(&"a"));
+        assert_eq!(ring.get_by_hash(101), Some(&"b"));
+        assert_eq!(ring.get_by_hash(200), Some(&"b"));
+        assert_eq!(ring.get_by_hash(201), Some(&"a"));
+        assert_eq!(ring.get_by_hash(u64::MAX), Some(&"a"));
+
+        ring.insert("c", 1);
+        assert_eq!(ring.get_by_hash(201), Some(&"c"));
+        assert_eq!(ring.get_by_hash(u64::MAX), Some(&"c"));
+        assert_eq!(ring.get_by_hash(0), Some(&"a"));
+
+        ring.remove(&"a");
+        ring.remove(&"b");
+        for key_hash in [0, 100, u64::MAX - 1, u64::MAX] {
+            assert_eq!(ring.get_by_hash(key_hash), Some(&"c"));
+        }
+        assert_eq!(ring.ownership_map(), vec![(0, u64::MAX, &"c")]);
+    }
+
+    #[test]
+    fn getting_jittered_picks_candidates_by_weight() {
+        let mut ring = HashRing::with_virtual_node_fn(
+            BuildHasherDefault::<std::collections::hash_map::DefaultHasher>::default(),
+            |_, node: &&str, index| match *node {
+                "a" => 10 + 100 * index,
+                "b" => 20 + 100 * index,
+                _ => 30 + 100 * index,
+            },
+        );
+        ring.insert("a", 1);
+        ring.insert("b", 3);
+        ring.insert("c", 1);
+        assert_eq!(
+            ring.get_jittered("Hot key", 3, &mut || 0),
+            ring.get("Hot key")
+        );
+        assert_eq!(
+            ring.get_jittered("Hot key", 0, &mut || 4),
+            ring.get("Hot key")
+        );
 
-        Iter::new(map.into_iter())
+        // Evenly spaced over the whole range of `u64`
+        let step = u64::MAX / 500 + 1;
+        let mut counter = 0u64;
+        let mut random = || {
+            counter += 1;
+            (counter - 1) * step
+        };
+        let mut picks: HashMap<&str, u64> = HashMap::new();
+        for _ in 0..500 {
+            *picks
+                .entry(ring.get_jittered("Hot key", 3, &mut random).unwrap())
+                .or_default() += 1;
+        }
+        // "b" has three times the weight of the others
+        assert_eq!(picks, HashMap::from([("a", 100), ("b", 300), ("c", 100)]));
     }
 
-    fn get_master_node_by_hash(&self, hash: &u64) -> Option<&MasterNode<N>> {
-        self.virtual_nodes.get(hash).map(|node| node.as_ref())
+    #[test]
+    fn nodes_by_share_are_ordered_by_descending_share() {
+        let mut ring = HashRing::with_virtual_node_fn(
+            BuildHasherDefault::<std::collections::hash_map::DefaultHasher>::default(),
+            |_, node: &&str, _| match *node {
+                "a" => 1 << 62,
+                "b" => 1 << 63,
+                _ => u64::MAX,
+            },
+        );
+        assert!(ring.nodes_by_share().is_empty());
+        ring.insert("a", 1);
+        ring.insert("b", 1);
+        ring.insert("c", 1);
+        // "c" owns half of the key space, "a" and "b" a quarter each
+        let shares = ring.nodes_by_share();
+        assert_eq!(shares[0], (&"c", 0.5));
+        let (first, second) = if ring.node_base_hash(&"a") < ring.node_base_hash(&"b") {
+            (&"a", &"b")
+        } else {
+            (&"b", &"a")
+        };
+        assert_eq!(shares[1..], [(first, 0.25), (second, 0.25)]);
     }
 
-    fn compute_virtual_node_hashes(&self, node: &N, weight: NonZeroU64) -> HashSet<u64> {
-        (0..weight.get())
-            .into_iter()
-            .map(|virtual_node_identifier| {
-                let mut hasher = self.hash_builder.build_hasher();
-                node.hash(&mut hasher);
-                hasher.write_u64(virtual_node_identifier);
-                // It could be that we have a collision here and therefore fewer virtual nodes
-                // TODO what's the distribution here?
-                hasher.finish()
+    #[test]
+    fn inserting_if_balanced_simulates_the_resulting_balance() {
+        for collision_policy in [CollisionPolicy::Replace, CollisionPolicy::Reassign] {
+            let mut ring = HashRingBuilder::with_hasher(BuildHasherDefault::<
+                std::collections::hash_map::DefaultHasher,
+            >::default())
+            .virtual_node_fn(|_, node: &&str, index| match (*node, index) {
+                ("a", _) => 10 + 20 * index,
+                ("b", 0) => 1000,
+                ("b", _) => 10 + 20 * index,
+                ("c", _) => 1000 + index,
+                (_, _) => 500 + 7 * index,
             })
-            .collect()
-    }
+            .collision_policy(collision_policy)
+            .build();
+            ring.insert("a", 5);
+            ring.insert("b", 5);
 
-    /// Removes a node from the `HashRing`, returning the number of virtual nodes (weight) of the removed node.
-    ///
-    /// The returned number is the actual number of virtual nodes. It may be lower than the `weight` provided when inserting
-    /// a node in case of hash collisions.
-    ///
-    /// # Examples
-    ///
-    /// ```
-    /// use hulahoop::HashRing;
-    ///
-    /// let mut ring: HashRing<&str, _> = HashRing::default();
-    ///
-    /// ring.insert("10.0.0.1:1234", 10);
-    /// assert_eq!(ring.remove(&"10.0.0.1:1234"), 10);
-    /// assert_eq!(ring.remove(&"10.0.0.1:1234"), 0);
-    /// ```
-    pub fn remove(&mut self, node: &N) -> u64 {
-        self.remove_inner(node).1
-    }
+            // "c" evicts "b"
+            for (node, weight) in [("d", 3), ("c", 2), ("a", 1)] {
+                let rejected = ring.insert_if_balanced(node, weight, 0.0).unwrap_err();
+                assert_eq!(rejected.clone().into_node(), node);
+                let mut expected = ring.clone();
+                expected.insert(node, weight);
+                assert_eq!(rejected.balance_factor(), expected.balance_factor());
 
-    fn remove_inner(&mut self, node: &N) -> (Option<N>, u64) {
-        match self.get_master_node(node) {
-            Some(master_node) => {
-                let mut number_of_removed_virtual_nodes = 0;
-                let mut removed_node = None;
-                let mut virtual_node_hashes = self
-                    .compute_virtual_node_hashes(&master_node.node, master_node.weight)
-                    .into_iter()
-                    .peekable();
-                while let Some(virtual_node_hash) = virtual_node_hashes.next() {
-                    if let Some(node) = self.virtual_nodes.remove(&virtual_node_hash) {
-                        number_of_removed_virtual_nodes += 1;
-                        if virtual_node_hashes.peek().is_none() {
-                            // Last item in iterator, there should be no other references to the master node and we should be able to get the node out of Arc.
-                            let removed_node_result = Arc::try_unwrap(node);
-                            removed_node =
-                                removed_node_result.ok().map(|master_node| master_node.node);
-                        }
-                    };
-                }
-                (removed_node, number_of_removed_virtual_nodes)
+                assert!(ring
+                    .insert_if_balanced(node, weight, rejected.balance_factor())
+                    .is_ok());
+                assert_eq!(ring.ownership_map(), expected.ownership_map());
             }
-            None => (None, 0),
         }
     }
 
-    fn get_master_node(&self, node: &N) -> Option<&MasterNode<N>> {
-        // At least one node should exist
-        let virtual_node_hashes =
-            self.compute_virtual_node_hashes(node, NonZeroU64::new(1).unwrap());
-        virtual_node_hashes
-            .iter()
-            .next()
-            .and_then(|hash| self.get_master_node_by_hash(hash))
+    #[test]
+    fn indices_are_dense_and_follow_node_hashes() {
+        let mut ring: HashRing<&str, _> = HashRing::default();
+        let nodes = ["10.0.0.1:12345", "10.0.0.2:12345", "10.0.0.3:12345"];
+        for node in nodes {
+            ring.insert(node, 10);
+        }
+        let mut expected = nodes.to_vec();
+        expected.sort_by_key(|node| ring.node_base_hash(node));
+        for (index, node) in expected.iter().enumerate() {
+            assert_eq!(ring.node_at_index(index), Some(node));
+        }
+        assert_eq!(ring.node_at_index(3), None);
+        for key in 0..100 {
+            let index = ring.get_index(key).unwrap();
+            assert_eq!(ring.node_at_index(index), ring.get(key));
+        }
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
 
     #[test]
-    fn adding_a_node_works() {
-        let mut ring: HashRing<&str, _> = HashRing::new();
-        let node = "10.0.0.1:12345";
-        ring.insert(node, 1);
+    fn indices_of_nodes_with_colliding_hashes_are_distinct() {
+        #[derive(Debug, PartialEq, Eq)]
+        struct Node(u64);
 
-        assert_eq!(ring.virtual_nodes.len(), 1);
+        impl Hash for Node {
+            fn hash<H: Hasher>(&self, state: &mut H) {
+                0.hash(state);
+            }
+        }
+
+        let mut ring = HashRing::with_virtual_node_fn(
+            BuildHasherDefault::<std::collections::hash_map::DefaultHasher>::default(),
+            |_, node: &Node, index| node.0 * 100 + index,
+        );
+        ring.insert(Node(2), 10);
+        ring.insert(Node(1), 10);
+
+        assert_eq!(ring.node_at_index(0), Some(&Node(1)));
+        assert_eq!(ring.node_at_index(1), Some(&Node(2)));
+        for key in 0..100 {
+            let index = ring.get_index(key).unwrap();
+            assert_eq!(ring.node_at_index(index), ring.get(key));
+        }
     }
 
     #[test]
-    fn adding_a_node_with_many_virtual_nodes_works() {
-        let mut ring: HashRing<&str, _> = HashRing::new();
-        let node = "10.0.0.1:12345";
-        ring.insert(node, 100);
+    fn union_and_intersection_combine_nodes_and_weights() {
+        let mut ring: HashRing<&str, _> = HashRing::default();
+        ring.insert("a", 10);
+        ring.insert("b", 10);
+        ring.insert("c", 30);
+        let mut other: HashRing<&str, _> = HashRing::default();
+        other.insert("b", 20);
+        other.insert("c", 5);
+        other.insert("d", 10);
 
-        assert_eq!(ring.virtual_nodes.len(), 100);
+        assert_eq!(
+            ring.union(&other).to_weights_map(),
+            HashMap::from([("a", 10), ("b", 20), ("c", 30), ("d", 10)])
+        );
+        assert_eq!(
+            ring.intersection(&other).to_weights_map(),
+            HashMap::from([("b", 10), ("c", 5)])
+        );
+        assert_eq!(
+            ring.union(&other).ownership_map(),
+            other.union(&ring).ownership_map()
+        );
+        assert!(ring.intersection(&HashRing::default()).is_empty());
+        assert_eq!(ring.to_weights_map().len(), 3);
+    }
+
+    #[derive(Default, Debug)]
+    struct CountingBuildHasher {
+        built: std::cell::Cell<u64>,
+    }
+
+    impl BuildHasher for CountingBuildHasher {
+        type Hasher = std::collections::hash_map::DefaultHasher;
+
+        fn build_hasher(&self) -> Self::Hasher {
+            // Every hasher starts in a different state
+            self.built.set(self.built.get() + 1);
+            let mut hasher = std::collections::hash_map::DefaultHasher::new();
+            hasher.write_u64(self.built.get());
+            hasher
+        }
     }
 
     #[test]
-    fn adding_multiple_nodes_with_many_virtual_nodes_works() {
-        let mut ring: HashRing<&str, _> = HashRing::new();
-        let node_1 = "10.0.0.1:12345";
-        let node_2 = "20.0.0.1:12345";
-        ring.insert(node_1, 100);
-        ring.insert(node_2, 500);
+    fn verifying_hasher_determinism_detects_stateful_hashers() {
+        let ring: HashRing<&str, _> =
+            HashRing::with_hasher(BuildHasherDefault::<CollisionHasher>::default());
+        assert!(ring.verify_hasher_determinism());
 
-        assert_eq!(ring.virtual_nodes.len(), 600);
+        let ring: HashRing<&str, _> = HashRing::with_hasher(CountingBuildHasher::default());
+        assert!(!ring.verify_hasher_determinism());
     }
 
     #[test]
-    fn removing_a_node_works() {
-        let mut ring: HashRing<&str, _> = HashRing::new();
-        let node = "10.0.0.1:12345";
-        ring.insert(node, 1);
-        assert_eq!(ring.virtual_nodes.len(), 1);
+    fn keys_hashing_to_a_position_select_its_node() {
+        let mut ring = HashRing::with_virtual_node_fn(
+            BuildHasherDefault::<std::collections::hash_map::DefaultHasher>::default(),
+            |_, node: &&str, index| match (*node, index) {
+                ("a", _) => 100 + 100 * index,
+                ("b", 0) => 150,
+                (_, _) => 200,
+            },
+        );
+        ring.insert("a", 2);
+        ring.insert("b", 2);
+        // The position 200 collides, the node inserted last owns it
+        assert_eq!(ring.get_by_hash(100), Some(&"a"));
+        assert_eq!(ring.get_by_hash(150), Some(&"b"));
+        assert_eq!(ring.get_by_hash(151), Some(&"b"));
+        assert_eq!(ring.get_by_hash(200), Some(&"b"));
+        assert_eq!(ring.get_by_hash(201), Some(&"a"));
 
-        ring.remove(&node);
-        assert_eq!(ring.virtual_nodes.len(), 0);
+        // After deduplication, the node with the lowest hash owns it
+        ring.deduplicate_positions();
+        let lowest = if ring.node_base_hash(&"a") < ring.node_base_hash(&"b") {
+            &"a"
+        } else {
+            &"b"
+        };
+        assert_eq!(ring.get_by_hash(200), Some(lowest));
+        assert_eq!(ring.get_by_hash(150), Some(&"b"));
     }
 
     #[test]
-    fn removing_a_node_with_many_virtual_nodes_works() {
-        let mut ring: HashRing<&str, _> = HashRing::new();
-        let node = "10.0.0.1:12345";
-        ring.insert(node, 100);
-        assert_eq!(ring.virtual_nodes.len(), 100);
+    fn stats_match_the_metrics_of_the_ring() {
+        let mut ring = HashRing::with_virtual_node_fn(
+            BuildHasherDefault::<std::collections::hash_map::DefaultHasher>::default(),
+            |_, node: &&str, index| match *node {
+                "a" => (1 << 62) + index,
+                "b" => 1 << 63,
+                _ => u64::MAX,
+            },
+        );
+        assert!(ring.stats().is_empty());
+        assert_eq!(ring.stats().balance_factor(), 0.0);
 
-        let nodes_removed = ring.remove(&node);
-        assert_eq!(nodes_removed, 100);
-        assert_eq!(ring.virtual_nodes.len(), 0);
+        ring.insert("a", 2);
+        ring.insert("b", 1);
+        ring.insert("c", 1);
+        let stats = ring.stats();
+        assert_eq!(stats.len(), 3);
+        assert_eq!(stats.total_weight(), 4);
+        assert_eq!(stats.balance_factor(), 1.5);
+        assert_eq!(stats.key_share_stddev(), ring.key_share_stddev());
+        assert_eq!(stats.key_share(&"c"), Some(0.5));
+        assert_eq!(stats.key_share(&"d"), None);
+        let mut nodes = stats.iter().collect::<Vec<_>>();
+        nodes.sort_by(|x, y| x.0.cmp(y.0));
+        assert_eq!(
+            nodes,
+            vec![(&"a", 2, 0.25), (&"b", 1, 0.25), (&"c", 1, 0.5)]
+        );
     }
 
     #[test]
-    fn removing_multiple_nodes_with_many_virtual_nodes_works() {
-        let mut ring: HashRing<&str, _> = HashRing::new();
-        let node_1 = "10.0.0.1:12345";
-        let node_2 = "20.0.0.1:12345";
-        ring.insert(node_1, 100);
-        ring.insert(node_2, 500);
-        assert_eq!(ring.virtual_nodes.len(), 600);
-        assert_eq!(ring.len(), 2);
+    fn reweighting_all_nodes_remaps_few_keys() {
+        let mut ring: HashRing<String, _> = HashRing::default();
+        for i in 0..10 {
+            ring.insert(format!("10.0.0.{i}:12345"), 100);
+        }
+        let keys = 0..10_000;
+        let before = keys
+            .clone()
+            .map(|key| ring.get(key).unwrap().clone())
+            .collect::<Vec<_>>();
 
-        let nodes_removed = ring.remove(&node_1);
-        assert_eq!(nodes_removed, 100);
-        assert_eq!(ring.virtual_nodes.len(), 500);
-        assert_eq!(ring.len(), 1);
+        ring.reweight_all(|node| match node.as_str() {
+            "10.0.0.0:12345" => 150,
+            "10.0.0.1:12345" => 50,
+            _ => 100,
+        });
+        assert_eq!(ring.total_weight(), 1000);
 
-        let nodes_removed = ring.remove(&node_2);
-        assert_eq!(nodes_removed, 500);
-        assert_eq!(ring.len(), 0);
+        let mut remapped = 0;
+        for (key, node) in keys.zip(before) {
+            let after = ring.get(key).unwrap();
+            if *after != node {
+                remapped += 1;
+                // Keys only move away from the node losing weight or to the node gaining weight
+                assert!(node == "10.0.0.1:12345" || after == "10.0.0.0:12345");
+            }
+        }
+        // About 5% of the key space moves from the node losing and to the node gaining weight each,
+        // rebuilding the ring with other positions would remap about 90% of the keys
+        let remapped_fraction = remapped as f64 / 10_000.0;
+        assert!(remapped_fraction < 0.2, "{remapped_fraction}");
     }
 
     #[test]
-    fn adding_one_node_and_getting_works() {
-        let mut ring: HashRing<&str, _> = HashRing::new();
-        let node = "10.0.0.1:12345";
-        ring.insert(node, 1);
+    fn len_tracks_nodes_losing_all_virtual_nodes() {
+        // The first virtual nodes do not collide, as that would replace the whole node
+        let virtual_node_fn = |_: &_, node: &&str, index| match (*node, index) {
+            ("b", 0) => 10,
+            ("c", 0) => 20,
+            ("c", 4) => 10,
+            _ => index,
+        };
+        let mut ring = HashRing::with_virtual_node_fn(
+            BuildHasherDefault::<std::collections::hash_map::DefaultHasher>::default(),
+            virtual_node_fn,
+        );
+        let assert_len = |ring: &HashRing<&str, _>, len| {
+            assert_eq!(ring.len(), len);
+            assert_eq!(ring.master_nodes().len(), len);
+        };
 
-        let node_for_val_a = ring.get("abc");
-        let node_for_val_b = ring.get(12345);
+        ring.insert("a", 4);
+        assert_len(&ring, 1);
+        // "b" takes over the position 1 of "a"
+        ring.insert("b", 2);
+        assert_len(&ring, 2);
+        // "c" takes over all positions of "b"
+        ring.insert("c", 5);
+        assert_len(&ring, 2);
+        ring.upsert("c", 2);
+        assert_len(&ring, 2);
+        ring.remove(&"c");
+        assert_len(&ring, 1);
+        ring.remove(&"a");
+        assert_len(&ring, 0);
 
-        assert_eq!(node_for_val_a, Some(&node));
-        assert_eq!(node_for_val_b, Some(&node));
+        ring.insert("a", 2);
+        ring.insert("b", 2);
+        assert_eq!(ring.deduplicate_positions(), 1);
+        assert_len(&ring, 2);
+        let ring = ring.clone();
+        assert_len(&ring, 2);
+        let ring = ring.rebuild_with(virtual_node_fn);
+        assert_len(&ring, 2);
     }
 
     #[test]
-    fn adding_multiple_nodes_and_getting_works() {
+    fn inserting_many_nodes_matches_inserting_each_node() {
         let mut ring: HashRing<&str, _> = HashRing::new();
-        let node_1 = "10.0.0.1:12345";
-        let node_2 = "20.0.0.1:12345";
-        let node_3 = "30.0.0.1:12345";
-        ring.insert(node_1, 1);
-        ring.insert(node_2, 1);
-        ring.insert(node_3, 1);
-
-        let node_for_val_a = ring.get("hula");
-        let node_for_val_b = ring.get(12345);
-        let node_for_val_c = ring.get(54321);
-        let node_for_val_d = ring.get(b"12345");
+        ring.insert("a", 10);
+        ring.insert("b", 10);
+        let mut inserted = ring.clone();
 
-        assert_eq!(node_for_val_a, Some(&node_3));
-        assert_eq!(node_for_val_b, Some(&node_2));
-        assert_eq!(node_for_val_c, Some(&node_3));
-        assert_eq!(node_for_val_d, Some(&node_1));
+        let nodes = [("b", 5), ("c", 20), ("d", 0), ("e", 1)];
+        ring.insert_many(nodes);
+        inserted.extend(nodes);
+        assert_eq!(ring.len(), 4);
+        assert_eq!(ring.to_weights_map(), inserted.to_weights_map());
+        assert!(ring.virtual_nodes.keys().eq(inserted.virtual_nodes.keys()));
+        for (position, master_node) in &ring.virtual_nodes {
+            assert_eq!(inserted.virtual_nodes[position].node, master_node.node);
+        }
     }
 
     #[test]
-    fn iterating_over_the_ring_works() {
-        let mut ring: HashRing<&str, _> = HashRing::new();
-        let node_1 = "10.0.0.1:12345";
-        let node_2 = "20.0.0.1:12345";
-        let node_3 = "30.0.0.1:12345";
-        ring.insert(node_1, 2);
-        ring.insert(node_2, 4);
-        ring.insert(node_3, 6);
+    fn removing_a_borrowed_node_works_with_a_custom_strategy() {
+        let mut ring = HashRing::with_virtual_node_fn(
+            BuildHasherDefault::<std::collections::hash_map::DefaultHasher>::default(),
+            |hash_builder: &_, node: &String, index| hash_builder.hash_one((node, index)),
+        );
+        ring.insert("10.0.0.1:1234".to_string(), 10);
+        ring.insert("10.0.0.2:1234".to_string(), 10);
 
-        let nodes = ring.iter().collect::<HashSet<_>>();
-        assert_eq!(nodes.len(), 3);
-        assert!(nodes.contains(&(&node_1, 2)));
-        assert!(nodes.contains(&(&node_2, 4)));
-        assert!(nodes.contains(&(&node_3, 6)));
+        assert!(ring.contains_node("10.0.0.1:1234"));
+        assert!(!ring.contains_node("10.0.0.3:1234"));
+        assert_eq!(ring.remove("10.0.0.1:1234"), 10);
+        assert!(!ring.contains_node("10.0.0.1:1234"));
+        assert_eq!(
+            ring.remove_checked("10.0.0.1:1234"),
+            Err(HulahoopError::NodeAbsent)
+        );
+        assert_eq!(ring.len(), 1);
     }
 
     #[test]
-    fn adding_multiple_nodes_getting_and_removing_works() {
-        let mut ring: HashRing<&str, _> = HashRing::new();
-        let node_1 = "10.0.0.1:12345";
-        let node_2 = "20.0.0.1:12345";
-        let node_3 = "30.0.0.1:12345";
-        ring.insert(node_1, 1);
-        ring.insert(node_2, 1);
-        ring.insert(node_3, 1);
+    fn borrowed_lookups_with_a_custom_strategy_follow_changes_of_the_ring() {
+        let mut ring = HashRing::with_virtual_node_fn(
+            BuildHasherDefault::<std::collections::hash_map::DefaultHasher>::default(),
+            |_: &_, node: &String, index| match (node.as_str(), index) {
+                ("a", _) => index,
+                ("b", 0) => 10,
+                ("c", 0) => 20,
+                (_, _) => index - 1,
+            },
+        );
+        ring.insert("a".to_string(), 2);
+        // "b" takes over the position 0 of "a", which keeps the position 1
+        ring.insert("b".to_string(), 2);
+        assert!(ring.contains_node("a"));
+        assert!(ring.contains_node_exact("b"));
 
-        let key_1 = "hula";
-        let key_2 = 12345;
-        let key_3 = 54321;
-        let key_4 = b"12345";
+        let mut cloned = ring.clone();
+        // "c" takes over the last position of "a"
+        ring.insert("c".to_string(), 3);
+        assert!(!ring.contains_node("a"));
+        assert!(cloned.contains_node("a"));
+        assert_eq!(cloned.remove("a"), 1);
+        assert!(!cloned.contains_node("a"));
 
-        {
-            let node_for_key_1 = ring.get(key_1);
-            let node_for_val_2 = ring.get(key_2);
-            let node_for_key_3 = ring.get(key_3);
-            let node_for_key_4 = ring.get(key_4);
+        ring.deduplicate_positions();
+        assert!(ring.remove_exact("c") > 0);
+        assert!(!ring.contains_node("c"));
+        assert!(ring.contains_node("b"));
+        assert!(ring.remove("b") > 0);
+        assert!(ring.is_empty());
+    }
 
-            assert_eq!(node_for_key_1, Some(&node_3));
-            assert_eq!(node_for_val_2, Some(&node_2));
-            assert_eq!(node_for_key_3, Some(&node_3));
-            assert_eq!(node_for_key_4, Some(&node_1));
-        }
+    #[test]
+    fn successor_and_predecessor_of_a_key() {
+        let mut ring: HashRing<&str, _> = HashRing::with_virtual_node_fn(
+            BuildHasherDefault::<std::collections::hash_map::DefaultHasher>::default(),
+            |_, node, index| match *node {
+                "a" => 100 + index * 300,
+                "b" => 200,
+                _ => 300,
+            },
+        );
+        ring.insert("a", 2);
+        ring.insert("b", 1);
+        ring.insert("c", 1);
 
-        ring.remove(&node_1);
+        // Routed to "b" at 200, followed by "c" at 300 and preceded by "a" at 100
+        assert_eq!(ring.next_node_after_hash(150), Some(&"c"));
+        assert_eq!(ring.prev_node_before_hash(150), Some(&"a"));
+        // Routed to "a" at 400, followed by "a" at 100 and "b" at 200, preceded by "c" at 300
+        assert_eq!(ring.next_node_after_hash(350), Some(&"b"));
+        assert_eq!(ring.prev_node_before_hash(350), Some(&"c"));
+        // Wrapping around to "a" at 100
+        assert_eq!(ring.next_node_after_hash(500), Some(&"b"));
+        assert_eq!(ring.prev_node_before_hash(500), Some(&"c"));
+    }
 
-        {
-            let node_for_key_1 = ring.get(key_1);
-            let node_for_key_2 = ring.get(key_2);
-            let node_for_key_3 = ring.get(key_3);
-            let node_for_key_4 = ring.get(key_4);
+    #[test]
+    fn exact_lookups_do_not_match_nodes_with_colliding_hashes() {
+        #[derive(Debug, PartialEq, Eq)]
+        struct Node(&'static str);
 
-            assert_eq!(node_for_key_1, Some(&node_3));
-            assert_eq!(node_for_key_2, Some(&node_2));
-            assert_eq!(node_for_key_3, Some(&node_3));
-            // Only this value got remapped
-            assert_eq!(node_for_key_4, Some(&node_2));
+        impl Hash for Node {
+            fn hash<H: Hasher>(&self, state: &mut H) {
+                0.hash(state);
+            }
         }
+
+        let mut ring = HashRing::new();
+        ring.insert(Node("a"), 10);
+        assert!(ring.contains_node(&Node("b")));
+        assert!(!ring.contains_node_exact(&Node("b")));
+        assert_eq!(ring.remove_exact(&Node("b")), 0);
+        assert!(ring.contains_node_exact(&Node("a")));
+        assert_eq!(ring.remove_exact(&Node("a")), 10);
+        assert!(ring.is_empty());
     }
 
     #[test]
-    fn creating_a_hashring_with_custom_hasher_and_adding_and_getting_works() {
-        use rustc_hash::FxHasher;
-        let mut ring: HashRing<&str, _> =
-            HashRing::with_hasher(BuildHasherDefault::<FxHasher>::default());
-        let node = "10.0.0.1:12345";
-        ring.insert(node, 1);
+    fn exact_lookups_find_any_of_the_nodes_with_colliding_hashes() {
+        #[derive(Debug, PartialEq, Eq)]
+        struct Node(u64);
 
-        let node_for_val_a = ring.get("abc");
-        let node_for_val_b = ring.get(12345);
+        impl Hash for Node {
+            fn hash<H: Hasher>(&self, state: &mut H) {
+                0.hash(state);
+            }
+        }
 
-        assert_eq!(node_for_val_a, Some(&node));
-        assert_eq!(node_for_val_b, Some(&node));
+        let mut ring = HashRing::with_virtual_node_fn(
+            BuildHasherDefault::<std::collections::hash_map::DefaultHasher>::default(),
+            |_, node: &Node, index| node.0 * 100 + index,
+        );
+        ring.insert(Node(1), 10);
+        ring.insert(Node(2), 5);
+        assert!(ring.contains_node_exact(&Node(1)));
+        assert!(ring.contains_node_exact(&Node(2)));
+        assert!(!ring.contains_node_exact(&Node(3)));
+        assert_eq!(ring.remove_exact(&Node(2)), 5);
+        assert_eq!(ring.remove_exact(&Node(1)), 10);
+        assert!(ring.is_empty());
     }
 
+    /// Hashes to the sum of all bytes written, modulo 4, so positions of different nodes collide.
     #[derive(Default, Debug)]
-    struct CollisionHasher;
-    impl Hasher for CollisionHasher {
+    struct SumHasher(u64);
+    impl Hasher for SumHasher {
         fn finish(&self) -> u64 {
-            // To cause hash collisions
-            1
+            self.0 % 4
         }
 
-        fn write(&mut self, _bytes: &[u8]) {}
+        fn write(&mut self, bytes: &[u8]) {
+            self.0 += bytes.iter().map(|byte| u64::from(*byte)).sum::<u64>();
+        }
     }
 
     #[test]
-    fn virtual_node_collisions_is_not_an_issue() {
-        let mut ring: HashRing<&str, _> =
-            HashRing::with_hasher(BuildHasherDefault::<CollisionHasher>::default());
-        let node = "10.0.0.1:12345";
-        let node_2 = "10.0.0.2:12345";
-        assert!(ring.insert(node, 3).is_none());
-        assert_eq!(ring.insert(node_2, 2), Some(node));
+    fn nodes_whose_first_position_is_held_by_another_node_are_found() {
+        let mut ring: HashRing<u32, _> =
+            HashRing::with_hasher(BuildHasherDefault::<SumHasher>::default());
+        // Node 0 is placed at 0 and 1, node 3 at 3 and 0, taking over the first position of node 0
+        ring.insert(0, 2);
+        ring.insert(3, 2);
+        assert_eq!(ring.virtual_node_count(&0), 1);
+        assert!(ring.contains_node(&0));
+        assert!(ring.contains_node_exact(&0));
 
-        let node_for_val_a = ring.get("abc");
-        let node_for_val_b = ring.get(12345);
+        let mut exact = ring.clone();
+        assert_eq!(exact.remove_exact(&0), 1);
+        assert_eq!(ring.remove(&0), 1);
+        for ring in [ring, exact] {
+            assert!(!ring.contains_node(&0));
+            assert_eq!(ring.virtual_node_count(&3), 2);
+            assert_eq!(ring.len(), 1);
+        }
+    }
 
-        assert_eq!(node_for_val_a, Some(&node_2));
-        assert_eq!(node_for_val_b, Some(&node_2));
+    #[test]
+    fn virtual_node_count_reflects_positions_taken_over() {
+        let mut ring: HashRing<&str, _> = HashRing::with_virtual_node_fn(
+            BuildHasherDefault::<std::collections::hash_map::DefaultHasher>::default(),
+            |_, node, index| match (*node, index) {
+                ("a", _) => index,
+                (_, 0) => 10,
+                (_, _) => index + 1,
+            },
+        );
+        ring.insert("a", 5);
+        // "b" takes over the positions 2 and 3 of "a"
+        ring.insert("b", 3);
+        assert_eq!(ring.virtual_node_count(&"a"), 3);
+        assert_eq!(ring.virtual_node_count(&"b"), 3);
+        assert_eq!(ring.total_virtual_nodes(), 6);
+        assert_eq!(ring.total_weight(), 8);
+    }
 
-        // Because of collisions, only 1 virtual node was added
-        assert_eq!(ring.remove(&node_2), 1);
-        assert!(ring.is_empty())
+    #[test]
+    fn retaining_nodes_matches_removing_the_other_nodes() {
+        let mut ring: HashRing<String, _> = HashRing::new();
+        for i in 0..10 {
+            ring.insert(format!("10.0.0.{i}:12345"), i + 1);
+        }
+        let mut removed = ring.clone();
+
+        ring.retain(|node, _| node.ends_with("1:12345") || node.ends_with("5:12345"));
+        for i in [0, 2, 3, 4, 6, 7, 8, 9] {
+            removed.remove(&format!("10.0.0.{i}:12345"));
+        }
+        assert_eq!(ring.len(), 2);
+        assert_eq!(ring.to_weights_map(), removed.to_weights_map());
+        assert!(ring.virtual_nodes.keys().eq(removed.virtual_nodes.keys()));
     }
 
     #[test]
-    fn adding_the_same_node_twice_works() {
-        let mut ring: HashRing<&str, _> = HashRing::new();
-        let node = "10.0.0.1:12345";
-        assert_eq!(ring.insert(node, 5), None);
-        assert_eq!(ring.insert(node, 3), Some(node));
+    fn getting_many_keys_matches_getting_each_key() {
+        let mut ring: HashRing<String, _> = HashRing::new();
+        assert_eq!(ring.get_many(0..10), vec![None; 10]);
 
-        assert_eq!(ring.len(), 1);
+        for i in 0..10 {
+            ring.insert(format!("10.0.0.{i}:12345"), 10);
+        }
+        let keys = (0..10_000).rev().chain(0..100).collect::<Vec<_>>();
+        let nodes = ring.get_many(&keys);
+        assert_eq!(nodes.len(), keys.len());
+        for (key, node) in keys.iter().zip(nodes) {
+            assert_eq!(node, ring.get(key));
+        }
     }
 
     #[test]
-    fn contains_node_works() {
-        let mut ring: HashRing<&str, _> = HashRing::new();
-        let node_1 = "10.0.0.1:12345";
-        ring.insert(node_1, 5);
-        assert!(ring.contains_node(&node_1));
+    fn relative_weights_add_up_to_the_total() {
+        let hash_builder =
+            BuildHasherDefault::<std::collections::hash_map::DefaultHasher>::default();
+        let nodes = [
+            ("a", 1.0),
+            ("b", 1.0),
+            ("c", 1.0),
+            ("d", 0.0),
+            ("e", f64::NAN),
+        ];
+        let ring = HashRing::from_relative_weights(nodes, 100, hash_builder.clone());
+        let mut weights = ring.to_weights_map().into_values().collect::<Vec<_>>();
+        weights.sort_unstable();
+        assert_eq!(weights, vec![33, 33, 34]);
 
-        let node_2 = "10.0.0.2:12345";
-        ring.insert(node_2, 10);
-        assert!(ring.contains_node(&node_2));
+        let nodes = [("a", 1000.0), ("b", 1.0)];
+        let ring = HashRing::from_relative_weights(nodes, 10, hash_builder.clone());
+        assert_eq!(ring.to_weights_map(), HashMap::from([("a", 9), ("b", 1)]));
+        let nodes = [("a", 1000.0), ("b", 1.0), ("c", 1.0)];
+        let ring = HashRing::from_relative_weights(nodes, 10, hash_builder.clone());
+        assert_eq!(
+            ring.to_weights_map(),
+            HashMap::from([("a", 8), ("b", 1), ("c", 1)])
+        );
+        let ring = HashRing::from_relative_weights(nodes, 2, hash_builder.clone());
+        assert_eq!(ring.total_weight(), 3);
 
-        ring.remove(&node_1);
-        assert!(!ring.contains_node(&node_1));
-        assert!(ring.contains_node(&node_2));
+        let ring = HashRing::from_relative_weights([("a", 0.0)], 10, hash_builder);
+        assert!(ring.is_empty());
+    }
 
-        ring.remove(&node_2);
-        assert!(!ring.contains_node(&node_2));
+    #[test]
+    fn every_change_increases_the_version() {
+        let mut ring: HashRing<&str, _> = HashRing::new();
+        let mut version = ring.version();
+        let mut assert_changed = |ring: &HashRing<&str, _>, changed: bool| {
+            assert_eq!(ring.version() > version, changed);
+            version = ring.version();
+        };
+
+        ring.insert("a", 10);
+        assert_changed(&ring, true);
+        ring.insert_many([("b", 10), ("c", 10)]);
+        assert_changed(&ring, true);
+        ring.set_weight(&"a", 20);
+        assert_changed(&ring, true);
+        ring.replace(&"a", "a").unwrap();
+        assert_changed(&ring, true);
+        ring.remove(&"d");
+        assert_changed(&ring, false);
+        ring.retain(|_, _| true);
+        assert_changed(&ring, false);
+        ring.retain(|node, _| *node != "c");
+        assert_changed(&ring, true);
+        assert_eq!(ring.deduplicate_positions(), 0);
+        assert_changed(&ring, false);
+        ring.get("key");
+        assert_changed(&ring, false);
+        assert_eq!(ring.clone().version(), ring.version());
+        ring.remove(&"b");
+        assert_changed(&ring, true);
+        ring.clear();
+        assert_changed(&ring, true);
+        ring.clear();
+        assert_changed(&ring, false);
     }
 
     #[test]
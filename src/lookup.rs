@@ -0,0 +1,140 @@
+use crate::HashRing;
+use std::hash::{BuildHasher, Hash};
+
+/// A precomputed lookup table routing keys in constant time.
+///
+/// The hash space is divided into `2^bits` equally sized buckets. Every bucket is assigned the node
+/// owning the first hash of the bucket, so routing a key is a single array access using the top `bits` of its hash.
+///
+/// The table trades accuracy for speed: keys hashing into a bucket which contains the position of a virtual node
+/// may be routed to a different node than by the ring. The more buckets, the fewer keys are affected, at the cost
+/// of `2^bits` references of memory.
+/// The table borrows the ring and must be rebuilt once the ring changes.
+///
+/// This `struct` is created by the [build_lookup_table](HashRing::build_lookup_table) method on [HashRing](HashRing).
+/// See its documentation for more.
+#[derive(Debug)]
+pub struct LookupTable<'a, N, B> {
+    buckets: Vec<&'a N>,
+    bits: u32,
+    hash_builder: &'a B,
+}
+
+impl<'a, N, B> LookupTable<'a, N, B>
+where
+    B: BuildHasher,
+{
+    /// The maximum number of bits supported by a lookup table.
+    pub const MAX_BITS: u32 = 32;
+
+    pub(crate) fn new(ring: &'a HashRing<N, B>, bits: u32) -> Self
+    where
+        N: Hash,
+    {
+        assert!(
+            bits <= LookupTable::<N, B>::MAX_BITS,
+            "a lookup table supports at most {} bits",
+            LookupTable::<N, B>::MAX_BITS
+        );
+        let buckets = if ring.is_empty() {
+            Vec::new()
+        } else {
+            (0..1u64 << bits)
+                // It's guaranteed that a node exists for every hash
+                .map(|bucket| ring.get_by_hash(Self::bucket_start(bucket, bits)).unwrap())
+                .collect()
+        };
+        Self {
+            buckets,
+            bits,
+            hash_builder: ring.hasher(),
+        }
+    }
+
+    /// Returns a reference to the node of the bucket the hash of the key falls into.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut ring: HashRing<&str, _> = HashRing::default();
+    ///
+    /// ring.insert("10.0.0.1:1234", 10);
+    /// let table = ring.build_lookup_table(8);
+    /// assert_eq!(table.get("Some key"), Some(&"10.0.0.1:1234"));
+    /// ```
+    #[inline]
+    pub fn get<K>(&self, key: K) -> Option<&'a N>
+    where
+        K: Hash,
+    {
+        let key_hash = self.hash_builder.hash_one(key);
+        let bucket = key_hash.checked_shr(u64::BITS - self.bits).unwrap_or(0);
+        self.buckets.get(bucket as usize).copied()
+    }
+
+    /// Returns the number of bits of the hash used to select a bucket.
+    pub fn bits(&self) -> u32 {
+        self.bits
+    }
+
+    /// Returns the nodes of all buckets, indexed by the top [bits](LookupTable::bits) of the hash.
+    ///
+    /// Returns an empty slice if the ring was empty.
+    pub fn buckets(&self) -> &[&'a N] {
+        &self.buckets
+    }
+
+    pub(crate) fn bucket_start(bucket: u64, bits: u32) -> u64 {
+        bucket.checked_shl(u64::BITS - bits).unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::BuildHasherDefault;
+
+    #[test]
+    fn lookup_table_routes_buckets_to_the_owner_of_their_start() {
+        let mut ring: HashRing<&str, _> = HashRing::with_virtual_node_fn(
+            BuildHasherDefault::<DefaultHasher>::default(),
+            |_, node, _| match *node {
+                "a" => u64::MAX / 4,
+                _ => u64::MAX / 4 * 3,
+            },
+        );
+        assert_eq!(ring.build_lookup_table(4).get("key"), None);
+
+        ring.insert("a", 1);
+        ring.insert("b", 1);
+        let table = ring.build_lookup_table(2);
+        assert_eq!(table.bits(), 2);
+        assert_eq!(table.buckets, vec![&"a", &"b", &"b", &"a"]);
+
+        let table = ring.build_lookup_table(0);
+        assert_eq!(table.buckets, vec![&"a"]);
+        assert_eq!(table.get("key"), Some(&"a"));
+
+        let table = ring.build_lookup_table(2);
+        for key in 0..1000 {
+            let key_hash = ring.hash_builder.hash_one(key);
+            assert_eq!(
+                table.get(key),
+                table.buckets.get((key_hash >> 62) as usize).copied()
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn lookup_table_with_too_many_bits_panics() {
+        let ring: HashRing<&str, _> =
+            HashRing::with_hasher(BuildHasherDefault::<DefaultHasher>::default());
+        ring.build_lookup_table(
+            LookupTable::<&str, BuildHasherDefault<DefaultHasher>>::MAX_BITS + 1,
+        );
+    }
+}
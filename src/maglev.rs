@@ -0,0 +1,248 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{BuildHasher, BuildHasherDefault, Hash};
+
+/// A lookup table built with Maglev hashing as described in
+/// [Maglev: A Fast and Reliable Software Network Load Balancer](https://research.google/pubs/pub44824/).
+///
+/// Every node walks through its own permutation of the table entries, taking turns in proportion to its weight
+/// and claiming the next entry not claimed yet until the table is full. Looking up a key is a single array access.
+/// Building a table with a changed set of nodes moves few entries between the remaining nodes, though not as few
+/// as a [HashRing](crate::HashRing) moves.
+///
+/// The size of the table must be a prime, and should be much larger than the number of nodes to balance the keys
+/// evenly. The nodes are ordered by their hash before filling the table, so the order they are given in does not
+/// matter.
+///
+/// # Examples
+///
+/// ```
+/// use hulahoop::MaglevTable;
+///
+/// let table: MaglevTable<&str, _> = MaglevTable::new([("10.0.0.1:1234", 1), ("10.0.0.2:1234", 2)], 65537);
+///
+/// assert_eq!(table.len(), 2);
+/// assert!(table.get("Some key").is_some());
+/// ```
+#[derive(Debug, Clone)]
+pub struct MaglevTable<N, B> {
+    // Every node with its weight, ordered by hash
+    nodes: Vec<(N, u64)>,
+    // The index into `nodes` of every entry
+    entries: Vec<u32>,
+    hash_builder: B,
+}
+
+impl<N> MaglevTable<N, BuildHasherDefault<DefaultHasher>>
+where
+    N: Hash,
+{
+    /// Builds a `MaglevTable` of `size` entries for node-weight pairs with the default hasher.
+    ///
+    /// See [with_hasher](MaglevTable::with_hasher).
+    pub fn new<I>(nodes: I, size: usize) -> Self
+    where
+        I: IntoIterator<Item = (N, u64)>,
+    {
+        Self::with_hasher(nodes, size, BuildHasherDefault::default())
+    }
+}
+
+impl<N, B> MaglevTable<N, B>
+where
+    N: Hash,
+    B: BuildHasher,
+{
+    /// A prime size balancing keys well for up to a few hundred nodes.
+    pub const DEFAULT_SIZE: usize = 65537;
+
+    /// Builds a `MaglevTable` of `size` entries for node-weight pairs, using `hash_builder` to hash nodes and keys.
+    ///
+    /// Nodes with a weight of 0 are ignored. Of equal nodes, only the first one is kept.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is not a prime or less than the number of nodes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::hash_map::DefaultHasher;
+    /// use std::hash::BuildHasherDefault;
+    /// use hulahoop::MaglevTable;
+    ///
+    /// let nodes = [("10.0.0.1:1234", 1), ("10.0.0.2:1234", 0), ("10.0.0.1:1234", 2)];
+    /// let table = MaglevTable::with_hasher(nodes, 13, BuildHasherDefault::<DefaultHasher>::default());
+    ///
+    /// assert_eq!(table.iter().collect::<Vec<_>>(), vec![(&"10.0.0.1:1234", 1)]);
+    /// ```
+    pub fn with_hasher<I>(nodes: I, size: usize, hash_builder: B) -> Self
+    where
+        I: IntoIterator<Item = (N, u64)>,
+    {
+        assert!(is_prime(size), "the size of a Maglev table must be a prime");
+        let mut nodes = nodes
+            .into_iter()
+            .filter(|(_, weight)| *weight > 0)
+            .map(|(node, weight)| (hash_builder.hash_one(&node), node, weight))
+            .collect::<Vec<_>>();
+        // The sort is stable, so the first of equal nodes is kept
+        nodes.sort_by_key(|(node_hash, _, _)| *node_hash);
+        nodes.dedup_by_key(|(node_hash, _, _)| *node_hash);
+        assert!(
+            nodes.len() <= size,
+            "a Maglev table must have at least one entry per node"
+        );
+
+        let entries = Self::populate(&nodes, size, &hash_builder);
+        Self {
+            nodes: nodes
+                .into_iter()
+                .map(|(_, node, weight)| (node, weight))
+                .collect(),
+            entries,
+            hash_builder,
+        }
+    }
+
+    /// Returns a reference to the node of the entry the hash of the key falls into.
+    ///
+    /// Returns `None` if the table has no nodes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::MaglevTable;
+    ///
+    /// let table: MaglevTable<&str, _> = MaglevTable::new([], 7);
+    /// assert_eq!(table.get("Some key"), None);
+    ///
+    /// let table: MaglevTable<&str, _> = MaglevTable::new([("10.0.0.1:1234", 1)], 7);
+    /// assert_eq!(table.get("Some key"), Some(&"10.0.0.1:1234"));
+    /// ```
+    #[inline]
+    pub fn get<K>(&self, key: K) -> Option<&N>
+    where
+        K: Hash,
+    {
+        let key_hash = self.hash_builder.hash_one(key);
+        let entry = key_hash.checked_rem(self.entries.len() as u64)?;
+        let entry = self.entries[entry as usize];
+        Some(&self.nodes[entry as usize].0)
+    }
+
+    fn populate(nodes: &[(u64, N, u64)], size: usize, hash_builder: &B) -> Vec<u32> {
+        let Some(max_weight) = nodes.iter().map(|(_, _, weight)| *weight).max() else {
+            return Vec::new();
+        };
+        let size = size as u64;
+        // The permutation of every node is `offset, offset + skip, offset + 2 * skip, ...` modulo the size,
+        // which visits every entry as the size is a prime
+        let mut permutations = nodes
+            .iter()
+            .map(|(node_hash, _, _)| {
+                let offset = hash_builder.hash_one((node_hash, 0u8)) % size;
+                let skip = hash_builder.hash_one((node_hash, 1u8)) % (size - 1).max(1) + 1;
+                (offset, skip)
+            })
+            .collect::<Vec<_>>();
+        // Every node takes a turn per unit of credit, the heaviest node one turn per round
+        let mut credits = vec![0.0; nodes.len()];
+        let mut entries = vec![u32::MAX; size as usize];
+        let mut filled = 0;
+        loop {
+            for (index, ((_, _, weight), (offset, skip))) in
+                nodes.iter().zip(&mut permutations).enumerate()
+            {
+                credits[index] += *weight as f64 / max_weight as f64;
+                while credits[index] >= 1.0 {
+                    credits[index] -= 1.0;
+                    while entries[*offset as usize] != u32::MAX {
+                        *offset = (*offset + *skip) % size;
+                    }
+                    entries[*offset as usize] = index as u32;
+                    filled += 1;
+                    if filled == entries.len() {
+                        return entries;
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<N, B> MaglevTable<N, B> {
+    /// Returns the number of entries.
+    pub fn size(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns the number of nodes.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns `true` if the table contains no nodes.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// An iterator visiting all node-weight pairs in the order of their hashes.
+    pub fn iter(&self) -> impl Iterator<Item = (&N, u64)> {
+        self.nodes.iter().map(|(node, weight)| (node, *weight))
+    }
+
+    /// Returns the number of entries assigned to every node, in the order of [iter](MaglevTable::iter).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::MaglevTable;
+    ///
+    /// let table: MaglevTable<&str, _> = MaglevTable::new([("10.0.0.1:1234", 1), ("10.0.0.2:1234", 3)], 65537);
+    ///
+    /// let shares = table.entry_counts();
+    /// assert_eq!(shares.iter().sum::<usize>(), 65537);
+    /// ```
+    pub fn entry_counts(&self) -> Vec<usize> {
+        let mut counts = vec![0; self.nodes.len()];
+        for entry in &self.entries {
+            counts[*entry as usize] += 1;
+        }
+        counts
+    }
+}
+
+fn is_prime(n: usize) -> bool {
+    n >= 2
+        && (2..)
+            .take_while(|d| d * d <= n)
+            .all(|d| !n.is_multiple_of(d))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::MaglevTable;
+
+    #[test]
+    fn entries_are_shared_by_weight_and_mostly_kept_on_removal() {
+        let nodes = [("a", 1), ("b", 2), ("c", 1), ("d", 0)];
+        let table: MaglevTable<&str, _> = MaglevTable::new(nodes, 1009);
+        let weights = table.iter().map(|(_, weight)| weight).collect::<Vec<_>>();
+        for (count, weight) in table.entry_counts().into_iter().zip(weights) {
+            let expected = 1009 * weight as usize / 4;
+            assert!(count.abs_diff(expected) <= 2, "{count} != {expected}");
+        }
+
+        let removed: MaglevTable<&str, _> = MaglevTable::new([("a", 1), ("c", 1)], 1009);
+        let moved = (0..10_000)
+            .filter(|key| table.get(key) != Some(&"b") && table.get(key) != removed.get(key))
+            .count();
+        assert!(moved < 1_000, "{moved}");
+    }
+
+    #[test]
+    #[should_panic(expected = "the size of a Maglev table must be a prime")]
+    fn size_must_be_a_prime() {
+        MaglevTable::<&str, _>::new([("a", 1)], 1000);
+    }
+}
@@ -0,0 +1,208 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::BTreeMap;
+use std::hash::{BuildHasher, BuildHasherDefault, Hash};
+use std::num::NonZeroUsize;
+
+/// A ring using multi-probe consistent hashing as described by Appleton and O'Reilly in
+/// [Multi-Probe Consistent Hashing](https://arxiv.org/abs/1505.00062).
+///
+/// Every node has a single position on the ring. Instead of balancing the keys with virtual nodes, a key is hashed
+/// `probes` times and routed to the node closest to any of its probes, going clockwise.
+/// With the default of 21 probes, the load of the nodes deviates about as much as with a few hundred virtual nodes
+/// per node, at the cost of `probes` ring lookups per key. Nodes do not have a weight.
+///
+/// # Examples
+///
+/// ```
+/// use hulahoop::MultiProbeRing;
+///
+/// let mut ring: MultiProbeRing<&str, _> = MultiProbeRing::new();
+///
+/// ring.insert("10.0.0.1:1234");
+/// ring.insert("10.0.0.2:1234");
+/// assert!(ring.get("Some key").is_some());
+/// ```
+#[derive(Debug, Clone)]
+pub struct MultiProbeRing<N, B> {
+    nodes: BTreeMap<u64, N>,
+    probes: NonZeroUsize,
+    hash_builder: B,
+}
+
+impl<N> MultiProbeRing<N, BuildHasherDefault<DefaultHasher>> {
+    /// Creates a new `MultiProbeRing` with the default hasher and number of probes.
+    pub fn new() -> Self {
+        Self::with_hasher(BuildHasherDefault::default())
+    }
+}
+
+impl<N> Default for MultiProbeRing<N, BuildHasherDefault<DefaultHasher>> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<N, B> MultiProbeRing<N, B> {
+    /// The number of probes per key recommended by the paper.
+    pub const DEFAULT_PROBES: NonZeroUsize = match NonZeroUsize::new(21) {
+        Some(probes) => probes,
+        None => unreachable!(),
+    };
+
+    /// Creates a new `MultiProbeRing` which will use the given `hash_builder` to hash nodes and keys.
+    pub fn with_hasher(hash_builder: B) -> Self {
+        Self::with_probes_and_hasher(Self::DEFAULT_PROBES, hash_builder)
+    }
+
+    /// Creates a new `MultiProbeRing` hashing every key `probes` times.
+    ///
+    /// More probes balance the keys more evenly but make lookups slower.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::hash_map::DefaultHasher;
+    /// use std::hash::BuildHasherDefault;
+    /// use std::num::NonZeroUsize;
+    /// use hulahoop::MultiProbeRing;
+    ///
+    /// let hash_builder = BuildHasherDefault::<DefaultHasher>::default();
+    /// let ring: MultiProbeRing<&str, _> = MultiProbeRing::with_probes_and_hasher(NonZeroUsize::new(5).unwrap(), hash_builder);
+    /// assert_eq!(ring.probes().get(), 5);
+    /// ```
+    pub fn with_probes_and_hasher(probes: NonZeroUsize, hash_builder: B) -> Self {
+        Self {
+            nodes: BTreeMap::new(),
+            probes,
+            hash_builder,
+        }
+    }
+
+    /// Returns the number of probes per key.
+    pub fn probes(&self) -> NonZeroUsize {
+        self.probes
+    }
+
+    /// Returns the number of nodes.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns `true` if the ring contains no nodes.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// An iterator visiting all nodes in the order of their positions.
+    pub fn iter(&self) -> impl Iterator<Item = &N> {
+        self.nodes.values()
+    }
+}
+
+impl<N, B> MultiProbeRing<N, B>
+where
+    N: Hash,
+    B: BuildHasher,
+{
+    /// Inserts a node, returning the old node if the ring did have a node at the same position.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::MultiProbeRing;
+    ///
+    /// let mut ring: MultiProbeRing<&str, _> = MultiProbeRing::new();
+    ///
+    /// assert_eq!(ring.insert("10.0.0.1:1234"), None);
+    /// assert_eq!(ring.insert("10.0.0.1:1234"), Some("10.0.0.1:1234"));
+    /// assert_eq!(ring.len(), 1);
+    /// ```
+    pub fn insert(&mut self, node: N) -> Option<N> {
+        let position = self.hash_builder.hash_one(&node);
+        self.nodes.insert(position, node)
+    }
+
+    /// Returns a reference to the node closest to any probe of the key, or `None` if the ring is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::MultiProbeRing;
+    ///
+    /// let mut ring: MultiProbeRing<&str, _> = MultiProbeRing::new();
+    /// assert_eq!(ring.get("Some key"), None);
+    ///
+    /// ring.insert("10.0.0.1:1234");
+    /// assert_eq!(ring.get("Some key"), Some(&"10.0.0.1:1234"));
+    /// ```
+    pub fn get<K>(&self, key: K) -> Option<&N>
+    where
+        K: Hash,
+    {
+        let key_hash = self.hash_builder.hash_one(key);
+        (0..self.probes.get() as u64)
+            .filter_map(|probe| {
+                let probe_hash = self.hash_builder.hash_one((key_hash, probe));
+                let (position, node) = self
+                    .nodes
+                    .range(probe_hash..)
+                    .next()
+                    .or_else(|| self.nodes.iter().next())?;
+                Some((position.wrapping_sub(probe_hash), node))
+            })
+            .min_by_key(|(distance, _)| *distance)
+            .map(|(_, node)| node)
+    }
+
+    /// Removes a node, returning it or `None` if the ring did not have this node present.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::MultiProbeRing;
+    ///
+    /// let mut ring: MultiProbeRing<&str, _> = MultiProbeRing::new();
+    ///
+    /// ring.insert("10.0.0.1:1234");
+    /// assert_eq!(ring.remove(&"10.0.0.1:1234"), Some("10.0.0.1:1234"));
+    /// assert_eq!(ring.remove(&"10.0.0.1:1234"), None);
+    /// ```
+    pub fn remove(&mut self, node: &N) -> Option<N> {
+        let position = self.hash_builder.hash_one(node);
+        self.nodes.remove(&position)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::MultiProbeRing;
+    use std::collections::HashMap;
+
+    #[test]
+    fn keys_are_balanced_and_only_move_from_removed_nodes() {
+        let mut ring: MultiProbeRing<u32, _> = MultiProbeRing::new();
+        for node in 0..10 {
+            ring.insert(node);
+        }
+
+        let mut counts = HashMap::new();
+        let before = (0..10_000)
+            .map(|key| {
+                let node = *ring.get(key).unwrap();
+                *counts.entry(node).or_insert(0) += 1;
+                node
+            })
+            .collect::<Vec<_>>();
+        assert!(
+            counts.values().all(|count| (700..1_300).contains(count)),
+            "{counts:?}"
+        );
+
+        ring.remove(&3);
+        for (key, node) in (0..10_000).zip(before) {
+            if node != 3 {
+                assert_eq!(ring.get(key), Some(&node));
+            }
+        }
+    }
+}
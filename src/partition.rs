@@ -0,0 +1,181 @@
+use crate::HashRing;
+use std::hash::{BuildHasher, Hash};
+use std::num::NonZeroU32;
+
+/// A ring routing keys in two levels: keys to one of a fixed number of partitions, and partitions to nodes.
+///
+/// The partition of a key only depends on its hash and the number of partitions, so it never changes.
+/// Partitions are routed to nodes by the ring, so changing the nodes moves whole partitions between nodes,
+/// which allows migrating data in partition-sized chunks instead of per key.
+///
+/// # Examples
+///
+/// ```
+/// use std::num::NonZeroU32;
+/// use hulahoop::{HashRing, PartitionedRing};
+///
+/// let mut ring = PartitionedRing::new(HashRing::default(), NonZeroU32::new(256).unwrap());
+/// ring.ring_mut().insert("10.0.0.1:1234", 10);
+///
+/// let partition = ring.partition_of("Some key");
+/// assert!(partition < 256);
+/// assert_eq!(ring.node_of_partition(partition), Some(&"10.0.0.1:1234"));
+/// assert_eq!(ring.get("Some key"), Some(&"10.0.0.1:1234"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct PartitionedRing<N, B> {
+    ring: HashRing<N, B>,
+    partitions: NonZeroU32,
+}
+
+impl<N, B> PartitionedRing<N, B> {
+    /// Creates a `PartitionedRing` routing `partitions` partitions with the ring.
+    pub fn new(ring: HashRing<N, B>, partitions: NonZeroU32) -> Self {
+        Self { ring, partitions }
+    }
+
+    /// Returns the number of partitions.
+    pub fn partitions(&self) -> NonZeroU32 {
+        self.partitions
+    }
+
+    /// Returns a reference to the ring routing the partitions.
+    pub fn ring(&self) -> &HashRing<N, B> {
+        &self.ring
+    }
+
+    /// Returns a mutable reference to the ring routing the partitions, to add or remove nodes.
+    pub fn ring_mut(&mut self) -> &mut HashRing<N, B> {
+        &mut self.ring
+    }
+
+    /// Takes ownership of the ring routing the partitions.
+    pub fn into_ring(self) -> HashRing<N, B> {
+        self.ring
+    }
+}
+
+impl<N, B> PartitionedRing<N, B>
+where
+    N: Hash,
+    B: BuildHasher,
+{
+    /// Returns the partition of the key, in `0..partitions`.
+    ///
+    /// The key is hashed with the hasher of the ring.
+    pub fn partition_of<K>(&self, key: K) -> u32
+    where
+        K: Hash,
+    {
+        (self.ring.hasher().hash_one(key) % u64::from(self.partitions.get())) as u32
+    }
+
+    /// Returns a reference to the node of the partition.
+    ///
+    /// Returns `None` if the ring is empty or the partition does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::num::NonZeroU32;
+    /// use hulahoop::{HashRing, PartitionedRing};
+    ///
+    /// let mut ring = PartitionedRing::new(HashRing::default(), NonZeroU32::new(16).unwrap());
+    /// assert_eq!(ring.node_of_partition(0), None);
+    ///
+    /// ring.ring_mut().insert("10.0.0.1:1234", 10);
+    /// assert_eq!(ring.node_of_partition(0), Some(&"10.0.0.1:1234"));
+    /// assert_eq!(ring.node_of_partition(16), None);
+    /// ```
+    pub fn node_of_partition(&self, partition: u32) -> Option<&N> {
+        (partition < self.partitions.get())
+            .then(|| self.ring.get(partition))
+            .flatten()
+    }
+
+    /// Returns a reference to the node of the partition of the key.
+    pub fn get<K>(&self, key: K) -> Option<&N>
+    where
+        K: Hash,
+    {
+        self.node_of_partition(self.partition_of(key))
+    }
+
+    /// Returns the partitions of the node in ascending order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::num::NonZeroU32;
+    /// use hulahoop::{HashRing, PartitionedRing};
+    ///
+    /// let mut ring = PartitionedRing::new(HashRing::default(), NonZeroU32::new(16).unwrap());
+    /// ring.ring_mut().insert("10.0.0.1:1234", 10);
+    /// ring.ring_mut().insert("10.0.0.2:1234", 10);
+    ///
+    /// let partitions = ring.partitions_of(&"10.0.0.1:1234").len() + ring.partitions_of(&"10.0.0.2:1234").len();
+    /// assert_eq!(partitions, 16);
+    /// ```
+    pub fn partitions_of(&self, node: &N) -> Vec<u32>
+    where
+        N: PartialEq,
+    {
+        (0..self.partitions.get())
+            .filter(|partition| self.node_of_partition(*partition) == Some(node))
+            .collect()
+    }
+
+    /// Returns the partitions whose node differs between the `old` ring and this ring, together with their node in
+    /// this ring, i.e. the partitions to migrate.
+    ///
+    /// Partitions without a node in this ring, i.e. if this ring is empty, are not returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::num::NonZeroU32;
+    /// use hulahoop::{HashRing, PartitionedRing};
+    ///
+    /// let mut ring = PartitionedRing::new(HashRing::default(), NonZeroU32::new(16).unwrap());
+    /// ring.ring_mut().insert("10.0.0.1:1234", 10);
+    /// let old = ring.clone();
+    ///
+    /// ring.ring_mut().insert("10.0.0.2:1234", 10);
+    /// let moved = ring.moved_partitions(&old);
+    /// assert!(moved.iter().all(|(_, node)| **node == "10.0.0.2:1234"));
+    /// assert_eq!(moved.len(), ring.partitions_of(&"10.0.0.2:1234").len());
+    /// ```
+    pub fn moved_partitions(&self, old: &PartitionedRing<N, B>) -> Vec<(u32, &N)>
+    where
+        N: PartialEq,
+    {
+        (0..self.partitions.get())
+            .filter_map(|partition| {
+                let node = self.node_of_partition(partition)?;
+                (old.node_of_partition(partition) != Some(node)).then_some((partition, node))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{HashRing, PartitionedRing};
+    use std::num::NonZeroU32;
+
+    #[test]
+    fn keys_keep_their_partition_and_follow_it_to_its_node() {
+        let mut ring: PartitionedRing<&str, _> =
+            PartitionedRing::new(HashRing::new(), NonZeroU32::new(64).unwrap());
+        let partitions = (0..1_000)
+            .map(|key| ring.partition_of(key))
+            .collect::<Vec<_>>();
+
+        ring.ring_mut().insert("a", 10);
+        ring.ring_mut().insert("b", 10);
+        for (key, partition) in (0..1_000).zip(partitions) {
+            assert_eq!(ring.partition_of(key), partition);
+            assert_eq!(ring.get(key), ring.node_of_partition(partition));
+        }
+    }
+}
@@ -0,0 +1,161 @@
+use crate::HashRing;
+use std::hash::{BuildHasher, Hash};
+use std::sync::{Arc, Mutex, PoisonError};
+use tokio::sync::watch;
+
+/// The publishing side of a ring shared with async tasks, broadcasting every update as a new immutable snapshot.
+///
+/// Like [SharedHashRing](crate::SharedHashRing), writers [update](RingPublisher::update) a copy of the current snapshot,
+/// which is published at once with its [generation](HashRing::generation) bumped. The snapshots are sent over a
+/// [tokio::sync::watch] channel, so [subscribers](RingSubscriber) can look up keys on the latest snapshot without
+/// locking and await the next one.
+///
+/// # Examples
+///
+/// ```
+/// use hulahoop::{HashRing, RingPublisher};
+///
+/// let publisher: RingPublisher<&str, _> = RingPublisher::new(HashRing::default());
+/// let subscriber = publisher.subscribe();
+///
+/// publisher.update(|ring| ring.insert("10.0.0.1:1234", 10));
+/// assert_eq!(subscriber.load().get("Some key"), Some(&"10.0.0.1:1234"));
+/// ```
+#[derive(Debug)]
+pub struct RingPublisher<N, B> {
+    sender: watch::Sender<Arc<HashRing<N, B>>>,
+    // Serializes writers, so no update is lost
+    writer: Mutex<()>,
+}
+
+/// The receiving side of a [RingPublisher](RingPublisher), created with [subscribe](RingPublisher::subscribe).
+///
+/// # Examples
+///
+/// ```
+/// use hulahoop::{HashRing, RingPublisher, RingSubscriber};
+///
+/// async fn route(subscriber: &mut RingSubscriber<&'static str, std::collections::hash_map::RandomState>) {
+///     while let Some(ring) = subscriber.changed().await {
+///         println!("Some key is now routed to {:?}", ring.get("Some key"));
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct RingSubscriber<N, B> {
+    receiver: watch::Receiver<Arc<HashRing<N, B>>>,
+}
+
+impl<N, B> RingPublisher<N, B> {
+    /// Creates a `RingPublisher` publishing `ring` as its first snapshot.
+    pub fn new(ring: HashRing<N, B>) -> Self {
+        let (sender, _) = watch::channel(Arc::new(ring));
+        Self {
+            sender,
+            writer: Mutex::new(()),
+        }
+    }
+
+    /// Returns a subscriber receiving the current snapshot and all snapshots published from now on.
+    pub fn subscribe(&self) -> RingSubscriber<N, B> {
+        RingSubscriber {
+            receiver: self.sender.subscribe(),
+        }
+    }
+
+    /// Returns the current snapshot of the ring.
+    pub fn load(&self) -> Arc<HashRing<N, B>> {
+        self.sender.borrow().clone()
+    }
+
+    /// Replaces the current snapshot with `ring`, returning the previous one.
+    ///
+    /// The generation of `ring` is published as is.
+    pub fn store(&self, ring: HashRing<N, B>) -> Arc<HashRing<N, B>> {
+        let _writer = self.writer.lock().unwrap_or_else(PoisonError::into_inner);
+        self.sender.send_replace(Arc::new(ring))
+    }
+}
+
+impl<N, B> RingPublisher<N, B>
+where
+    N: Hash + Clone,
+    B: BuildHasher + Clone,
+{
+    /// Applies `f` to a copy of the current snapshot and publishes it with its generation bumped,
+    /// returning the result of `f`.
+    ///
+    /// Updates are applied one after another and every subscriber is notified of the new snapshot.
+    /// Cloning the ring takes time linear in the number of virtual nodes, so prefer applying many changes within
+    /// one update. If `f` panics, nothing is published.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::{HashRing, RingPublisher};
+    ///
+    /// let publisher: RingPublisher<&str, _> = RingPublisher::new(HashRing::default());
+    ///
+    /// publisher.update(|ring| {
+    ///     ring.insert("10.0.0.1:1234", 10);
+    ///     ring.insert("10.0.0.2:1234", 10);
+    /// });
+    /// assert_eq!(publisher.load().len(), 2);
+    /// assert_eq!(publisher.load().generation(), 1);
+    /// ```
+    pub fn update<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce(&mut HashRing<N, B>) -> R,
+    {
+        let _writer = self.writer.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut ring = HashRing::clone(&self.load());
+        let result = f(&mut ring);
+        self.sender
+            .send_replace(Arc::new(ring.with_bumped_generation()));
+        result
+    }
+}
+
+impl<N, B> RingSubscriber<N, B> {
+    /// Returns the latest snapshot of the ring.
+    ///
+    /// The snapshot does not change, even if updates are published in the meantime.
+    pub fn load(&self) -> Arc<HashRing<N, B>> {
+        self.receiver.borrow().clone()
+    }
+
+    /// Waits until a snapshot is published which this subscriber has not seen yet and returns it.
+    ///
+    /// Snapshots published in quick succession are skipped, only the latest one is returned.
+    /// Returns `None` once the publisher is dropped.
+    pub async fn changed(&mut self) -> Option<Arc<HashRing<N, B>>> {
+        self.receiver.changed().await.ok()?;
+        Some(self.receiver.borrow_and_update().clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{HashRing, RingPublisher};
+
+    #[tokio::test]
+    async fn subscribers_receive_the_latest_snapshot() {
+        let publisher: RingPublisher<u32, _> = RingPublisher::new(HashRing::new());
+        let mut subscriber = publisher.subscribe();
+        let snapshot = subscriber.load();
+
+        publisher.update(|ring| ring.insert(1, 10));
+        publisher.update(|ring| ring.insert(2, 10));
+        let ring = subscriber.changed().await.unwrap();
+        assert!(snapshot.is_empty());
+        assert_eq!(ring.len(), 2);
+        assert_eq!(ring.generation(), 2);
+
+        let previous = publisher.store(HashRing::new());
+        assert_eq!(previous.len(), 2);
+        assert!(subscriber.changed().await.unwrap().is_empty());
+
+        drop(publisher);
+        assert!(subscriber.changed().await.is_none());
+    }
+}
@@ -0,0 +1,188 @@
+//! A fixed-slot partitioner compatible with [Redis Cluster](https://redis.io/docs/reference/cluster-spec/).
+//!
+//! Redis Cluster divides the keys into [SLOTS](SLOTS) slots by the CRC16 of the key, honouring hash tags,
+//! see [key_slot](key_slot). A [SlotTable](SlotTable) assigns every slot a node of a [HashRing](HashRing),
+//! so a proxy routes the keys of a slot together, just like Redis Cluster does.
+
+use crate::HashRing;
+use std::hash::{BuildHasher, Hash};
+
+/// The number of slots of Redis Cluster.
+pub const SLOTS: u16 = 16384;
+
+/// Returns the slot of the key as computed by Redis Cluster.
+///
+/// The slot is the CRC16-XMODEM checksum of the key modulo [SLOTS](SLOTS). If the key contains a hash tag,
+/// i.e. a non-empty substring between the first `{` and the first `}` following it, only the hash tag is hashed,
+/// which places related keys into the same slot.
+///
+/// # Examples
+///
+/// ```
+/// use hulahoop::redis::key_slot;
+///
+/// assert_eq!(key_slot(b"123456789"), 12739);
+/// assert_eq!(key_slot(b"{user1000}.following"), key_slot(b"user1000"));
+/// assert_eq!(key_slot(b"foo"), 12182);
+/// // An empty hash tag is no hash tag, so the whole key is hashed
+/// assert_ne!(key_slot(b"foo{}{bar}"), key_slot(b"bar"));
+/// assert_eq!(key_slot(b"foo{}{bar}"), 8363);
+/// ```
+pub fn key_slot(key: &[u8]) -> u16 {
+    crc16(hash_tag(key).unwrap_or(key)) % SLOTS
+}
+
+fn hash_tag(key: &[u8]) -> Option<&[u8]> {
+    let start = key.iter().position(|byte| *byte == b'{')? + 1;
+    let length = key[start..].iter().position(|byte| *byte == b'}')?;
+    (length > 0).then(|| &key[start..start + length])
+}
+
+fn crc16(bytes: &[u8]) -> u16 {
+    bytes.iter().fold(0, |crc, byte| {
+        (0..8).fold(crc ^ (u16::from(*byte) << 8), |crc, _| {
+            if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            }
+        })
+    })
+}
+
+/// A table assigning every Redis Cluster slot a node of a ring.
+///
+/// Slot `s` is assigned the node the ring selects for the key `s as u16`, so the slots of a node only move to
+/// other nodes as the ring changes, like keys do.
+/// The table borrows the ring and must be rebuilt once the ring changes.
+///
+/// This `struct` is created by the [build_slot_table](HashRing::build_slot_table) method on [HashRing](HashRing).
+/// See its documentation for more.
+#[derive(Debug)]
+pub struct SlotTable<'a, N> {
+    nodes: Vec<&'a N>,
+}
+
+impl<'a, N> SlotTable<'a, N> {
+    /// Returns a reference to the node of the slot of the key.
+    ///
+    /// Returns `None` if the ring was empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut ring: HashRing<&str, _> = HashRing::default();
+    /// ring.insert("10.0.0.1:6379", 10);
+    /// ring.insert("10.0.0.2:6379", 10);
+    ///
+    /// let table = ring.build_slot_table();
+    /// assert_eq!(table.get(b"{user1000}.following"), table.get(b"{user1000}.followers"));
+    /// ```
+    pub fn get(&self, key: &[u8]) -> Option<&'a N> {
+        self.node_of_slot(key_slot(key))
+    }
+
+    /// Returns a reference to the node of the slot.
+    ///
+    /// Returns `None` if the ring was empty or the slot is not less than [SLOTS](SLOTS).
+    pub fn node_of_slot(&self, slot: u16) -> Option<&'a N> {
+        self.nodes.get(usize::from(slot)).copied()
+    }
+
+    /// Returns the node of every slot, indexed by slot.
+    ///
+    /// Returns an empty slice if the ring was empty.
+    pub fn nodes(&self) -> &[&'a N] {
+        &self.nodes
+    }
+
+    /// Returns the slots of the node as ranges of consecutive slots, like `CLUSTER SLOTS` reports them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut ring: HashRing<&str, _> = HashRing::default();
+    /// ring.insert("10.0.0.1:6379", 10);
+    ///
+    /// let table = ring.build_slot_table();
+    /// assert_eq!(table.slot_ranges(&"10.0.0.1:6379"), vec![0..=16383]);
+    /// ```
+    pub fn slot_ranges(&self, node: &N) -> Vec<std::ops::RangeInclusive<u16>>
+    where
+        N: PartialEq,
+    {
+        let mut ranges: Vec<std::ops::RangeInclusive<u16>> = Vec::new();
+        for (slot, _) in (0..SLOTS).zip(&self.nodes).filter(|(_, n)| **n == node) {
+            match ranges.last_mut() {
+                Some(range) if *range.end() + 1 == slot => *range = *range.start()..=slot,
+                _ => ranges.push(slot..=slot),
+            }
+        }
+        ranges
+    }
+}
+
+impl<N, B> HashRing<N, B>
+where
+    N: Hash,
+    B: BuildHasher,
+{
+    /// Builds a [SlotTable](SlotTable) assigning every Redis Cluster slot a node.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut ring: HashRing<&str, _> = HashRing::default();
+    /// assert_eq!(ring.build_slot_table().get(b"Some key"), None);
+    ///
+    /// ring.insert("10.0.0.1:6379", 10);
+    /// assert_eq!(ring.build_slot_table().get(b"Some key"), Some(&"10.0.0.1:6379"));
+    /// ```
+    pub fn build_slot_table(&self) -> SlotTable<'_, N> {
+        let nodes = if self.is_empty() {
+            Vec::new()
+        } else {
+            // It's guaranteed that a node exists for every key
+            (0..SLOTS).map(|slot| self.get(slot).unwrap()).collect()
+        };
+        SlotTable { nodes }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn key_slots_match_redis() {
+        assert_eq!(key_slot(b""), 0);
+        assert_eq!(key_slot(b"foo"), 12182);
+        assert_eq!(key_slot(b"{user1000}.following"), 3443);
+        // Only the first `{` opens a hash tag, and an empty hash tag hashes the whole key
+        assert_eq!(key_slot(b"foo{{bar}}zap"), key_slot(b"{bar"));
+        assert_eq!(key_slot(b"foo{}{bar}"), crc16(b"foo{}{bar}") % SLOTS);
+        assert_eq!(key_slot(b"foo{bar"), crc16(b"foo{bar") % SLOTS);
+    }
+
+    #[test]
+    fn slot_table_covers_all_slots() {
+        let mut ring: HashRing<&str, _> = HashRing::new();
+        ring.insert("a", 10);
+        ring.insert("b", 10);
+        let table = ring.build_slot_table();
+        assert_eq!(table.nodes().len(), usize::from(SLOTS));
+        let covered = [&"a", &"b"]
+            .into_iter()
+            .flat_map(|node| table.slot_ranges(node))
+            .map(|range| range.len())
+            .sum::<usize>();
+        assert_eq!(covered, usize::from(SLOTS));
+        assert_eq!(table.node_of_slot(SLOTS), None);
+    }
+}
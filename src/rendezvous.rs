@@ -0,0 +1,233 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{BuildHasher, BuildHasherDefault, Hash};
+
+/// A selector based on weighted rendezvous hashing, also known as highest random weight (HRW) hashing.
+///
+/// Every node is scored for every key and the key is routed to the node with the highest score.
+/// Scores are computed as `-weight / ln(h)` for a hash `h` of the node and the key mapped into `(0, 1)`,
+/// so keys are routed to nodes in proportion to their weights, which may be fractional.
+/// Contrary to a [HashRing](crate::HashRing), no virtual nodes are needed to balance the keys according to the
+/// weights, but every lookup takes time linear in the number of nodes. This makes it a good fit for small sets of nodes.
+/// As with a `HashRing`, removing a node only remaps the keys routed to it.
+///
+/// # Examples
+///
+/// ```
+/// use hulahoop::RendezvousRing;
+///
+/// let mut ring: RendezvousRing<&str, _> = RendezvousRing::new();
+///
+/// ring.insert("10.0.0.1:1234", 1);
+/// ring.insert("10.0.0.2:1234", 2);
+/// assert!(ring.get("Some key").is_some());
+/// ```
+#[derive(Debug, Clone)]
+pub struct RendezvousRing<N, B> {
+    // Every node with its hash and positive weight, ordered by hash
+    nodes: Vec<(u64, N, f64)>,
+    hash_builder: B,
+}
+
+impl<N> RendezvousRing<N, BuildHasherDefault<DefaultHasher>> {
+    /// Creates a new `RendezvousRing` with the default hasher.
+    pub fn new() -> Self {
+        Self::with_hasher(BuildHasherDefault::default())
+    }
+}
+
+impl<N> Default for RendezvousRing<N, BuildHasherDefault<DefaultHasher>> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<N, B> RendezvousRing<N, B> {
+    /// Creates a new `RendezvousRing` which will use the given `hash_builder` to hash nodes and keys.
+    pub fn with_hasher(hash_builder: B) -> Self {
+        Self {
+            nodes: Vec::new(),
+            hash_builder,
+        }
+    }
+
+    /// Returns the number of nodes.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns `true` if the ring contains no nodes.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// An iterator visiting all node-weight pairs in arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item = (&N, f64)> {
+        self.nodes.iter().map(|(_, node, weight)| (node, *weight))
+    }
+}
+
+impl<N, B> RendezvousRing<N, B>
+where
+    N: Hash,
+    B: BuildHasher,
+{
+    /// Inserts a node with a `weight`, returning the old node if the ring did have this node present.
+    ///
+    /// Keys are routed to nodes in proportion to their weights. A `weight` of 0 is ignored like in
+    /// [HashRing::insert](crate::HashRing::insert).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::RendezvousRing;
+    ///
+    /// let mut ring: RendezvousRing<&str, _> = RendezvousRing::new();
+    ///
+    /// assert_eq!(ring.insert("10.0.0.1:1234", 1), None);
+    /// assert_eq!(ring.insert("10.0.0.1:1234", 2), Some("10.0.0.1:1234"));
+    /// assert_eq!(ring.insert("10.0.0.2:1234", 0), None);
+    /// assert_eq!(ring.len(), 1);
+    /// ```
+    pub fn insert(&mut self, node: N, weight: u64) -> Option<N> {
+        self.insert_relative(node, weight as f64)
+    }
+
+    /// Inserts a node with a fractional `weight`, returning the old node if the ring did have this node present.
+    ///
+    /// Only the ratio of the weights matters, so a node with a weight of 1.5 receives 50% more keys than a node with
+    /// a weight of 1. A `weight` which is not positive or not finite is ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::RendezvousRing;
+    ///
+    /// let mut ring: RendezvousRing<&str, _> = RendezvousRing::new();
+    ///
+    /// assert_eq!(ring.insert_relative("10.0.0.1:1234", 0.5), None);
+    /// assert_eq!(ring.insert_relative("10.0.0.2:1234", 1.5), None);
+    /// assert_eq!(ring.insert_relative("10.0.0.3:1234", f64::NAN), None);
+    /// assert_eq!(ring.len(), 2);
+    /// ```
+    pub fn insert_relative(&mut self, node: N, weight: f64) -> Option<N> {
+        if !(weight > 0.0 && weight.is_finite()) {
+            return None;
+        }
+        let node_hash = self.hash_builder.hash_one(&node);
+        match self
+            .nodes
+            .binary_search_by_key(&node_hash, |(node_hash, _, _)| *node_hash)
+        {
+            Ok(index) => {
+                let (_, old_node, _) =
+                    std::mem::replace(&mut self.nodes[index], (node_hash, node, weight));
+                Some(old_node)
+            }
+            Err(index) => {
+                self.nodes.insert(index, (node_hash, node, weight));
+                None
+            }
+        }
+    }
+
+    /// Returns a reference to the node with the highest score for the key.
+    ///
+    /// Returns `None` if the ring is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::RendezvousRing;
+    ///
+    /// let mut ring: RendezvousRing<&str, _> = RendezvousRing::new();
+    /// assert_eq!(ring.get("Some key"), None);
+    ///
+    /// ring.insert("10.0.0.1:1234", 1);
+    /// assert_eq!(ring.get("Some key"), Some(&"10.0.0.1:1234"));
+    /// ```
+    pub fn get<K>(&self, key: K) -> Option<&N>
+    where
+        K: Hash,
+    {
+        let key_hash = self.hash_builder.hash_one(key);
+        self.nodes
+            .iter()
+            .map(|(node_hash, node, weight)| (self.score(*node_hash, key_hash, *weight), node))
+            // Equal scores are resolved in favor of the node with the higher hash
+            .max_by(|(score, _), (other_score, _)| score.total_cmp(other_score))
+            .map(|(_, node)| node)
+    }
+
+    /// Removes a node, returning its weight, or `None` if the ring did not have this node present.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::RendezvousRing;
+    ///
+    /// let mut ring: RendezvousRing<&str, _> = RendezvousRing::new();
+    ///
+    /// ring.insert("10.0.0.1:1234", 10);
+    /// assert_eq!(ring.remove(&"10.0.0.1:1234"), Some(10.0));
+    /// assert_eq!(ring.remove(&"10.0.0.1:1234"), None);
+    /// ```
+    pub fn remove(&mut self, node: &N) -> Option<f64> {
+        let node_hash = self.hash_builder.hash_one(node);
+        self.nodes
+            .binary_search_by_key(&node_hash, |(node_hash, _, _)| *node_hash)
+            .ok()
+            .map(|index| self.nodes.remove(index).2)
+    }
+
+    /// Returns the weighted score of a node for a key, `weight / -ln(u)` for `u` uniformly distributed in `(0, 1)`.
+    fn score(&self, node_hash: u64, key_hash: u64, weight: f64) -> f64 {
+        let hash = self.hash_builder.hash_one((node_hash, key_hash));
+        // Use the top 53 bits, which a f64 represents exactly, and keep `u` away from 0 and 1
+        let u = ((hash >> 11) as f64 + 0.5) / (1u64 << 53) as f64;
+        weight / -u.ln()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::RendezvousRing;
+    use std::collections::HashMap;
+
+    #[test]
+    fn keys_are_distributed_by_fractional_weight() {
+        let mut ring: RendezvousRing<&str, _> = RendezvousRing::new();
+        ring.insert_relative("a", 0.25);
+        ring.insert_relative("b", 0.75);
+
+        let a = (0..10_000)
+            .filter(|key| ring.get(key) == Some(&"a"))
+            .count();
+        assert!((2_300..2_700).contains(&a), "{a}");
+    }
+
+    #[test]
+    fn keys_are_distributed_by_weight_and_only_move_from_removed_nodes() {
+        let mut ring: RendezvousRing<&str, _> = RendezvousRing::new();
+        ring.insert("a", 1);
+        ring.insert("b", 2);
+        ring.insert("c", 1);
+
+        let mut counts = HashMap::new();
+        let before = (0..10_000)
+            .map(|key| {
+                let node = *ring.get(key).unwrap();
+                *counts.entry(node).or_insert(0) += 1;
+                node
+            })
+            .collect::<Vec<_>>();
+        assert!((2_300..2_700).contains(&counts["a"]), "{counts:?}");
+        assert!((4_700..5_300).contains(&counts["b"]), "{counts:?}");
+
+        ring.remove(&"b");
+        for (key, node) in (0..10_000).zip(before) {
+            if node != "b" {
+                assert_eq!(ring.get(key), Some(&node));
+            }
+        }
+    }
+}
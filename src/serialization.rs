@@ -0,0 +1,205 @@
+use crate::HashRing;
+use serde::de::{Deserialize, Deserializer, Error};
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+use std::any::type_name;
+use std::hash::{BuildHasher, Hash};
+
+/// Serializes the topology of the ring, i.e. every node with the weight it was inserted with.
+///
+/// The nodes are serialized in the order of their [ids](crate::NodeId) together with the type name of the
+/// `BuildHasher`, e.g. `{"hasher":"core::hash::BuildHasherDefault<…>","nodes":[["10.0.0.1:1234",10]]}` in JSON.
+/// The positions of the virtual nodes, the generation and the configuration of the ring are not serialized.
+///
+/// # Examples
+///
+/// ```
+/// use hulahoop::HashRing;
+///
+/// let mut ring: HashRing<&str, _> = HashRing::default();
+/// ring.insert("10.0.0.1:1234", 10);
+///
+/// let json = serde_json::to_string(&ring).unwrap();
+/// assert!(json.ends_with(r#""nodes":[["10.0.0.1:1234",10]]}"#));
+/// ```
+impl<N, B> Serialize for HashRing<N, B>
+where
+    N: Serialize + Hash,
+    B: BuildHasher,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut master_nodes = self.master_nodes();
+        master_nodes.sort_unstable_by_key(|master_node| master_node.id);
+        let nodes = master_nodes
+            .into_iter()
+            .map(|master_node| (&master_node.node, master_node.requested_weight.get()))
+            .collect::<Vec<_>>();
+        let mut state = serializer.serialize_struct("HashRing", 2)?;
+        state.serialize_field("hasher", type_name::<B>())?;
+        state.serialize_field("nodes", &nodes)?;
+        state.end()
+    }
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename = "HashRing")]
+struct Topology<N> {
+    hasher: String,
+    nodes: Vec<(N, u64)>,
+}
+
+/// Deserializes the topology of a ring, computing the positions of the virtual nodes with the default `BuildHasher`.
+///
+/// The nodes are inserted like with [insert_many](HashRing::insert_many), so a ring with the default configuration
+/// routes every key to the same node as the serialized ring. To restore the topology into a ring with a different
+/// configuration, deserialize a ring with the default configuration and pass its
+/// [weights](HashRing::to_weights_map) to the [insert_many](HashRing::insert_many) method of the configured ring.
+///
+/// Fails if the ring was serialized with a different type of `BuildHasher`, as the restored ring would route keys
+/// differently. Use [deserialize_ignoring_hasher](HashRing::deserialize_ignoring_hasher) to switch hashers on purpose.
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::hash_map::DefaultHasher;
+/// use std::hash::BuildHasherDefault;
+/// use hulahoop::HashRing;
+///
+/// type Ring = HashRing<String, BuildHasherDefault<DefaultHasher>>;
+///
+/// let mut ring = Ring::default();
+/// ring.insert("10.0.0.1:1234".to_string(), 10);
+/// ring.insert("10.0.0.2:1234".to_string(), 5);
+///
+/// let json = serde_json::to_string(&ring).unwrap();
+/// let restored: Ring = serde_json::from_str(&json).unwrap();
+/// assert_eq!(restored.to_weights_map(), ring.to_weights_map());
+/// assert_eq!(restored.get("Some key"), ring.get("Some key"));
+/// ```
+impl<'de, N, B> Deserialize<'de> for HashRing<N, B>
+where
+    N: Deserialize<'de> + Hash + Eq,
+    B: BuildHasher + Default,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let topology = Topology::deserialize(deserializer)?;
+        if topology.hasher != type_name::<B>() {
+            return Err(D::Error::custom(format!(
+                "the ring was serialized with the hasher {}, not {}",
+                topology.hasher,
+                type_name::<B>()
+            )));
+        }
+        Ok(HashRing::from_topology(topology))
+    }
+}
+
+impl<N, B> HashRing<N, B>
+where
+    N: Hash + Eq,
+    B: BuildHasher + Default,
+{
+    /// Deserializes the topology of a ring like its `Deserialize` implementation, but regardless of the hasher it was
+    /// serialized with.
+    ///
+    /// The positions of the virtual nodes are computed with the default `BuildHasher`, so if the ring was serialized
+    /// with a different hasher, keys are routed to different nodes than by the serialized ring.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::hash_map::DefaultHasher;
+    /// use std::hash::BuildHasherDefault;
+    /// use hulahoop::HashRing;
+    ///
+    /// #[derive(Default)]
+    /// struct OtherHasher(BuildHasherDefault<DefaultHasher>);
+    ///
+    /// impl std::hash::BuildHasher for OtherHasher {
+    ///     type Hasher = DefaultHasher;
+    ///
+    ///     fn build_hasher(&self) -> DefaultHasher {
+    ///         self.0.build_hasher()
+    ///     }
+    /// }
+    ///
+    /// let mut ring: HashRing<String, BuildHasherDefault<DefaultHasher>> = HashRing::with_hasher(BuildHasherDefault::default());
+    /// ring.insert("10.0.0.1:1234".to_string(), 10);
+    ///
+    /// let json = serde_json::to_string(&ring).unwrap();
+    /// assert!(serde_json::from_str::<HashRing<String, OtherHasher>>(&json).is_err());
+    ///
+    /// let mut deserializer = serde_json::Deserializer::from_str(&json);
+    /// let restored: HashRing<String, OtherHasher> = HashRing::deserialize_ignoring_hasher(&mut deserializer).unwrap();
+    /// assert_eq!(restored.to_weights_map(), ring.to_weights_map());
+    /// ```
+    pub fn deserialize_ignoring_hasher<'de, D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        N: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        Topology::deserialize(deserializer).map(HashRing::from_topology)
+    }
+
+    fn from_topology(topology: Topology<N>) -> Self {
+        let mut ring = HashRing::with_hasher(B::default());
+        ring.insert_many(topology.nodes);
+        ring
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{DefaultBuildHasher, HashRing};
+    use rustc_hash::FxHasher;
+    use std::any::type_name;
+    use std::collections::hash_map::RandomState;
+    use std::hash::BuildHasherDefault;
+
+    #[test]
+    fn topology_round_trips() {
+        let mut ring: HashRing<u32, _> = HashRing::new();
+        for node in 0..20 {
+            ring.insert(node, u64::from(node % 4 + 1) * 10);
+        }
+        ring.set_weight(&3, 5);
+
+        let json = serde_json::to_string(&ring).unwrap();
+        let restored: HashRing<u32, _> = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.to_weights_map(), ring.to_weights_map());
+        assert!(restored.agrees_with(&ring, &(0..1_000).collect::<Vec<_>>()));
+        assert_eq!(serde_json::to_string(&restored).unwrap(), json);
+
+        let empty: HashRing<u32, DefaultBuildHasher> = serde_json::from_str(&format!(
+            r#"{{"hasher":"{}","nodes":[]}}"#,
+            type_name::<DefaultBuildHasher>()
+        ))
+        .unwrap();
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn topologies_of_other_hashers_are_rejected() {
+        let mut ring: HashRing<u32, BuildHasherDefault<FxHasher>> =
+            HashRing::with_hasher(BuildHasherDefault::default());
+        ring.insert(1, 10);
+        let json = serde_json::to_string(&ring).unwrap();
+
+        let error = serde_json::from_str::<HashRing<u32, RandomState>>(&json).unwrap_err();
+        assert!(error.to_string().contains("FxHasher"));
+        assert!(serde_json::from_str::<HashRing<u32, RandomState>>(r#"{"nodes":[]}"#).is_err());
+
+        let restored: HashRing<u32, RandomState> =
+            HashRing::deserialize_ignoring_hasher(&mut serde_json::Deserializer::from_str(&json))
+                .unwrap();
+        assert_eq!(restored.to_weights_map(), ring.to_weights_map());
+        let restored: HashRing<u32, BuildHasherDefault<FxHasher>> =
+            serde_json::from_str(&json).unwrap();
+        assert!(restored.agrees_with(&ring, &(0..1_000).collect::<Vec<_>>()));
+    }
+}
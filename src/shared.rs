@@ -0,0 +1,146 @@
+use crate::HashRing;
+use std::hash::{BuildHasher, Hash};
+use std::sync::{Arc, Mutex, PoisonError, RwLock};
+
+/// A ring shared between many readers and writers, publishing every update as a new immutable snapshot.
+///
+/// Readers [load](SharedHashRing::load) the current snapshot and look up keys on it without any lock, for as long as
+/// they like. Writers [update](SharedHashRing::update) a copy of the current snapshot, which is published at once
+/// with its [generation](HashRing::generation) bumped, so readers never observe a partially applied update.
+/// Loading only holds a read lock while cloning an `Arc`, and is never blocked by a writer applying its changes.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+/// use std::thread;
+/// use hulahoop::{HashRing, SharedHashRing};
+///
+/// let ring: Arc<SharedHashRing<&str, _>> = Arc::new(SharedHashRing::new(HashRing::default()));
+/// ring.update(|ring| ring.insert("10.0.0.1:1234", 10));
+///
+/// let reader = {
+///     let ring = ring.clone();
+///     thread::spawn(move || ring.load().get("Some key").copied())
+/// };
+/// assert_eq!(reader.join().unwrap(), Some("10.0.0.1:1234"));
+/// ```
+#[derive(Debug)]
+pub struct SharedHashRing<N, B> {
+    current: RwLock<Arc<HashRing<N, B>>>,
+    // Serializes writers, so no update is lost
+    writer: Mutex<()>,
+}
+
+impl<N, B> SharedHashRing<N, B> {
+    /// Creates a `SharedHashRing` publishing `ring` as its first snapshot.
+    pub fn new(ring: HashRing<N, B>) -> Self {
+        Self {
+            current: RwLock::new(Arc::new(ring)),
+            writer: Mutex::new(()),
+        }
+    }
+
+    /// Returns the current snapshot of the ring.
+    ///
+    /// The snapshot does not change, even if updates are published in the meantime.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::{HashRing, SharedHashRing};
+    ///
+    /// let ring: SharedHashRing<&str, _> = SharedHashRing::new(HashRing::default());
+    /// let snapshot = ring.load();
+    ///
+    /// ring.update(|ring| ring.insert("10.0.0.1:1234", 10));
+    /// assert!(snapshot.is_empty());
+    /// assert_eq!(ring.load().len(), 1);
+    /// ```
+    pub fn load(&self) -> Arc<HashRing<N, B>> {
+        // A panicking writer never publishes, so the current snapshot is always consistent
+        self.current
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Replaces the current snapshot with `ring`, returning the previous one.
+    ///
+    /// The generation of `ring` is published as is.
+    pub fn store(&self, ring: HashRing<N, B>) -> Arc<HashRing<N, B>> {
+        let _writer = self.writer.lock().unwrap_or_else(PoisonError::into_inner);
+        self.publish(ring)
+    }
+
+    fn publish(&self, ring: HashRing<N, B>) -> Arc<HashRing<N, B>> {
+        let mut current = self.current.write().unwrap_or_else(PoisonError::into_inner);
+        std::mem::replace(&mut *current, Arc::new(ring))
+    }
+}
+
+impl<N, B> SharedHashRing<N, B>
+where
+    N: Hash + Clone,
+    B: BuildHasher + Clone,
+{
+    /// Applies `f` to a copy of the current snapshot and publishes it with its generation bumped,
+    /// returning the result of `f`.
+    ///
+    /// Updates are applied one after another. Cloning the ring takes time linear in the number of virtual nodes,
+    /// so prefer applying many changes within one update. If `f` panics, nothing is published.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::{HashRing, SharedHashRing};
+    ///
+    /// let ring: SharedHashRing<&str, _> = SharedHashRing::new(HashRing::default());
+    ///
+    /// ring.update(|ring| {
+    ///     ring.insert("10.0.0.1:1234", 10);
+    ///     ring.insert("10.0.0.2:1234", 10);
+    /// });
+    /// assert_eq!(ring.load().len(), 2);
+    /// assert_eq!(ring.load().generation(), 1);
+    /// ```
+    pub fn update<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce(&mut HashRing<N, B>) -> R,
+    {
+        let _writer = self.writer.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut ring = HashRing::clone(&self.load());
+        let result = f(&mut ring);
+        self.publish(ring.with_bumped_generation());
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{HashRing, SharedHashRing};
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn concurrent_updates_are_not_lost() {
+        let ring: Arc<SharedHashRing<u32, _>> = Arc::new(SharedHashRing::new(HashRing::new()));
+        let writers = (0..4)
+            .map(|writer| {
+                let ring = ring.clone();
+                thread::spawn(move || {
+                    for node in 0..25 {
+                        ring.update(|ring| ring.insert(writer * 25 + node, 1));
+                        let snapshot = ring.load();
+                        assert!(snapshot.get("key").is_some());
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        for writer in writers {
+            writer.join().unwrap();
+        }
+        assert_eq!(ring.load().len(), 100);
+        assert_eq!(ring.load().generation(), 100);
+    }
+}
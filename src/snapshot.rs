@@ -0,0 +1,225 @@
+use crate::{HashRing, HulahoopError, MasterNode};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{BuildHasher, Hash};
+use std::num::NonZeroU64;
+use std::sync::Arc;
+
+// Increased whenever the layout of a snapshot changes
+const FORMAT: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+struct Snapshot<N> {
+    format: u32,
+    generation: u64,
+    version: u64,
+    nodes: Vec<SnapshotNode<N>>,
+    // Every position with the index of its node, in ascending order
+    positions: Vec<(u64, u32)>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SnapshotNode<N> {
+    node: N,
+    id: u64,
+    weight: u64,
+    requested_weight: u64,
+    reassigned_positions: Vec<(u64, u64)>,
+}
+
+impl<N, B> HashRing<N, B>
+where
+    N: Hash,
+    B: BuildHasher,
+{
+    /// Encodes the exact state of the ring as a binary snapshot, including the position of every virtual node.
+    ///
+    /// Unlike the topology written by the `Serialize` implementation, which is rebuilt with the hasher of the
+    /// deserializing ring, a snapshot restores every position exactly, even if the positions were computed by a
+    /// randomly seeded hasher like [RandomState](std::collections::hash_map::RandomState). The
+    /// [generation](HashRing::generation) and [version](HashRing::version) are included, the configuration of
+    /// the ring is not. Nodes are encoded with their `Serialize` implementation in [CBOR](https://cbor.io).
+    ///
+    /// Encoding the same ring always yields the same bytes. Fails with
+    /// [InvalidSnapshot](HulahoopError::InvalidSnapshot) if a node cannot be serialized.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::hash_map::RandomState;
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut ring: HashRing<String, _> = HashRing::with_hasher(RandomState::new());
+    /// ring.insert("10.0.0.1:1234".to_string(), 10);
+    /// ring.insert("10.0.0.2:1234".to_string(), 10);
+    ///
+    /// let bytes = ring.to_bytes().unwrap();
+    /// let restored = HashRing::<String, _>::from_bytes(&bytes, RandomState::new()).unwrap();
+    /// assert_eq!(restored.to_bytes().unwrap(), bytes);
+    ///
+    /// let key_hash = ring.hash_key("Some key");
+    /// assert_eq!(restored.get_prehashed(key_hash), ring.get_prehashed(key_hash));
+    /// ```
+    pub fn to_bytes(&self) -> Result<Vec<u8>, HulahoopError>
+    where
+        N: Serialize,
+    {
+        let mut master_nodes = self.master_nodes();
+        master_nodes.sort_unstable_by_key(|master_node| master_node.id);
+        let indices = master_nodes
+            .iter()
+            .enumerate()
+            .map(|(index, master_node)| (Arc::as_ptr(master_node), index as u32))
+            .collect::<HashMap<_, _>>();
+        let snapshot = Snapshot {
+            format: FORMAT,
+            generation: self.generation,
+            version: self.version,
+            nodes: master_nodes
+                .iter()
+                .map(|master_node| SnapshotNode {
+                    node: &master_node.node,
+                    id: master_node.id,
+                    weight: master_node.weight.get(),
+                    requested_weight: master_node.requested_weight.get(),
+                    reassigned_positions: master_node.reassigned_positions.clone(),
+                })
+                .collect(),
+            positions: self
+                .virtual_nodes
+                .iter()
+                .map(|(position, master_node)| (*position, indices[&Arc::as_ptr(master_node)]))
+                .collect(),
+        };
+        let mut bytes = Vec::new();
+        ciborium::ser::into_writer(&snapshot, &mut bytes)
+            .map_err(|_| HulahoopError::InvalidSnapshot)?;
+        Ok(bytes)
+    }
+
+    /// Restores a ring from a snapshot encoded by [to_bytes](HashRing::to_bytes), using `hash_builder` to hash keys.
+    ///
+    /// All virtual nodes are restored at their exact positions, so every key hash is routed to the same node as on
+    /// the encoded ring. Keys are hashed with `hash_builder`, so to look up the same nodes for the same keys,
+    /// it has to hash like the hasher of the encoded ring. The same holds for looking up, updating and removing nodes,
+    /// whose positions are computed with `hash_builder`. Fails with [InvalidSnapshot](HulahoopError::InvalidSnapshot)
+    /// if the bytes are not a valid snapshot.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::{HashRing, HulahoopError};
+    ///
+    /// let mut ring: HashRing<String, _> = HashRing::default();
+    /// ring.insert("10.0.0.1:1234".to_string(), 10);
+    ///
+    /// let bytes = ring.to_bytes().unwrap();
+    /// let restored = HashRing::<String, _>::from_bytes(&bytes, ring.hasher().clone()).unwrap();
+    /// assert_eq!(restored.get("Some key"), Some(&"10.0.0.1:1234".to_string()));
+    ///
+    /// let invalid = HashRing::<String, _>::from_bytes(&bytes[1..], ring.hasher().clone());
+    /// assert_eq!(invalid.unwrap_err(), HulahoopError::InvalidSnapshot);
+    /// ```
+    pub fn from_bytes(bytes: &[u8], hash_builder: B) -> Result<Self, HulahoopError>
+    where
+        N: DeserializeOwned,
+    {
+        let snapshot: Snapshot<N> =
+            ciborium::de::from_reader(bytes).map_err(|_| HulahoopError::InvalidSnapshot)?;
+        if snapshot.format != FORMAT {
+            return Err(HulahoopError::InvalidSnapshot);
+        }
+        let master_nodes = snapshot
+            .nodes
+            .into_iter()
+            .map(|node| {
+                Some(Arc::new(MasterNode {
+                    node: node.node,
+                    id: node.id,
+                    weight: NonZeroU64::new(node.weight)?,
+                    requested_weight: NonZeroU64::new(node.requested_weight)?,
+                    reassigned_positions: node.reassigned_positions,
+                }))
+            })
+            .collect::<Option<Vec<_>>>()
+            .ok_or(HulahoopError::InvalidSnapshot)?;
+        let mut owned = vec![false; master_nodes.len()];
+        let virtual_nodes = snapshot
+            .positions
+            .into_iter()
+            .map(|(position, index)| {
+                let master_node = master_nodes.get(index as usize)?;
+                owned[index as usize] = true;
+                Some((position, master_node.clone()))
+            })
+            .collect::<Option<BTreeMap<_, _>>>()
+            .ok_or(HulahoopError::InvalidSnapshot)?;
+        // Every node owns at least one position
+        if owned.contains(&false) {
+            return Err(HulahoopError::InvalidSnapshot);
+        }
+
+        let mut ring = HashRing::with_hasher(hash_builder);
+        for master_node in &master_nodes {
+            ring.index_master_node(master_node);
+        }
+        ring.virtual_nodes = virtual_nodes;
+        ring.node_count = master_nodes.len();
+        ring.generation = snapshot.generation;
+        ring.version = snapshot.version;
+        Ok(ring)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{HashRing, HulahoopError};
+    use std::collections::hash_map::RandomState;
+
+    #[test]
+    fn snapshots_restore_the_exact_positions() {
+        let mut ring: HashRing<u32, _> = HashRing::with_hasher(RandomState::new());
+        for node in 0..20 {
+            ring.insert(node, u64::from(node % 4 + 1) * 10);
+        }
+        ring.remove(&7);
+        let ring = ring.with_bumped_generation();
+
+        let bytes = ring.to_bytes().unwrap();
+        let restored = HashRing::<u32, _>::from_bytes(&bytes, ring.hasher().clone()).unwrap();
+        assert_eq!(restored.virtual_nodes.len(), ring.virtual_nodes.len());
+        assert!(restored
+            .virtual_nodes
+            .iter()
+            .zip(&ring.virtual_nodes)
+            .all(|((x, x_node), (y, y_node))| x == y && x_node.node == y_node.node));
+        assert_eq!(restored.len(), ring.len());
+        assert_eq!(restored.generation(), ring.generation());
+        assert_eq!(restored.version(), ring.version());
+        assert_eq!(restored.to_weights_map(), ring.to_weights_map());
+
+        // With the same hasher, the restored ring can be changed like the original one
+        let mut restored = restored;
+        assert_eq!(restored.remove(&3), ring.virtual_node_count(&3));
+        assert!(!restored.contains_node(&3));
+
+        let other = HashRing::<u32, _>::from_bytes(&bytes, RandomState::new()).unwrap();
+        assert_eq!(other.to_bytes().unwrap(), bytes);
+    }
+
+    #[test]
+    fn invalid_snapshots_are_rejected() {
+        let mut ring: HashRing<u32, _> = HashRing::new();
+        ring.insert(1, 10);
+        let bytes = ring.to_bytes().unwrap();
+        for length in 0..bytes.len() {
+            assert_eq!(
+                HashRing::<u32, _>::from_bytes(&bytes[..length], ring.hasher().clone())
+                    .unwrap_err(),
+                HulahoopError::InvalidSnapshot
+            );
+        }
+        assert!(HashRing::<String, _>::from_bytes(&bytes, ring.hasher().clone()).is_err());
+    }
+}
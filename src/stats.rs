@@ -0,0 +1,105 @@
+use crate::{balance_factor_of, HashRing, KEY_SPACE};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Statistics about the distribution of the key space of a `HashRing`, computed in a single pass over the ring.
+///
+/// All metrics are derived from the same snapshot, so computing several of them only walks the ring once.
+/// The share of a node is the length of all arcs of the ring ending at its virtual nodes, as a fraction of the
+/// whole key space.
+///
+/// This `struct` is created by the [stats](HashRing::stats) method on [HashRing](HashRing). See its documentation for more.
+#[derive(Debug)]
+pub struct RingStats<'a, N> {
+    // Every node with its number of virtual nodes and the size of the key space routed to it
+    nodes: Vec<(&'a N, u64, u128)>,
+    total_weight: u64,
+}
+
+impl<'a, N> RingStats<'a, N> {
+    pub(crate) fn new<B>(ring: &'a HashRing<N, B>) -> Self {
+        let key_space_shares = ring.key_space_shares();
+        let mut virtual_nodes = HashMap::new();
+        for master_node in ring.virtual_nodes.values() {
+            virtual_nodes
+                .entry(Arc::as_ptr(master_node))
+                .or_insert((master_node, 0))
+                .1 += 1;
+        }
+        let total_weight = virtual_nodes
+            .values()
+            .map(|(master_node, _)| master_node.weight.get())
+            .sum();
+        let nodes = virtual_nodes
+            .into_iter()
+            .map(|(pointer, (master_node, count))| {
+                (&master_node.node, count, key_space_shares[&pointer])
+            })
+            .collect();
+        Self {
+            nodes,
+            total_weight,
+        }
+    }
+
+    /// Returns the number of nodes.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns `true` if the ring contained no nodes.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Returns the sum of the actual weights of all nodes, see [HashRing::total_weight](HashRing::total_weight).
+    pub fn total_weight(&self) -> u64 {
+        self.total_weight
+    }
+
+    /// Returns the balance factor of the ring, see [HashRing::balance_factor](HashRing::balance_factor).
+    pub fn balance_factor(&self) -> f64 {
+        balance_factor_of(
+            &self
+                .nodes
+                .iter()
+                .enumerate()
+                .map(|(index, (_, _, key_space))| (index, *key_space))
+                .collect(),
+        )
+    }
+
+    /// Returns the standard deviation of the key shares, see [HashRing::key_share_stddev](HashRing::key_share_stddev).
+    pub fn key_share_stddev(&self) -> f64 {
+        if self.nodes.is_empty() {
+            return 0.0;
+        }
+        let nodes = self.nodes.len() as f64;
+        let mean = 1.0 / nodes;
+        let variance = self
+            .nodes
+            .iter()
+            .map(|(_, _, key_space)| (*key_space as f64 / KEY_SPACE as f64 - mean).powi(2))
+            .sum::<f64>()
+            / nodes;
+        variance.sqrt()
+    }
+
+    /// Returns the share of the key space of the node, or `None` if the node was not part of the ring.
+    pub fn key_share(&self, node: &N) -> Option<f64>
+    where
+        N: PartialEq,
+    {
+        self.iter()
+            .find(|(other, _, _)| *other == node)
+            .map(|(_, _, key_share)| key_share)
+    }
+
+    /// Returns an iterator over all nodes with their number of virtual nodes and their share of the key space,
+    /// in arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item = (&'a N, u64, f64)> + '_ {
+        self.nodes.iter().map(|(node, virtual_nodes, key_space)| {
+            (*node, *virtual_nodes, *key_space as f64 / KEY_SPACE as f64)
+        })
+    }
+}
@@ -0,0 +1,40 @@
+/// Computes the positions of the virtual nodes of a [HashRing](crate::HashRing).
+///
+/// The strategy is implemented for all functions `Fn(&B, &N, u64) -> u64`,
+/// see [HashRing::with_virtual_node_fn](crate::HashRing::with_virtual_node_fn).
+///
+/// # Examples
+///
+/// ```
+/// use std::hash::{BuildHasher, Hash};
+/// use hulahoop::{HashRing, VirtualNodeStrategy};
+///
+/// struct DoubleHashing;
+///
+/// impl<N: Hash, B: BuildHasher> VirtualNodeStrategy<N, B> for DoubleHashing {
+///     fn position(&self, hash_builder: &B, node: &N, virtual_node_identifier: u64) -> u64 {
+///         let first = hash_builder.hash_one(node);
+///         let second = hash_builder.hash_one((node, u64::MAX)) | 1;
+///         first.wrapping_add(virtual_node_identifier.wrapping_mul(second))
+///     }
+/// }
+///
+/// let mut ring: HashRing<&str, _> = HashRing::default();
+/// ring.insert("10.0.0.1:1234", 10);
+///
+/// let ring = ring.rebuild_with(DoubleHashing);
+/// assert_eq!(ring.get("Some key"), Some(&"10.0.0.1:1234"));
+/// ```
+pub trait VirtualNodeStrategy<N, B> {
+    /// Returns the position of the virtual node with the given identifier (`0..weight`) of `node`.
+    fn position(&self, hash_builder: &B, node: &N, virtual_node_identifier: u64) -> u64;
+}
+
+impl<N, B, F> VirtualNodeStrategy<N, B> for F
+where
+    F: Fn(&B, &N, u64) -> u64,
+{
+    fn position(&self, hash_builder: &B, node: &N, virtual_node_identifier: u64) -> u64 {
+        self(hash_builder, node, virtual_node_identifier)
+    }
+}
@@ -0,0 +1,402 @@
+use crate::{HashRing, HulahoopError};
+use std::collections::BTreeMap;
+use std::hash::{BuildHasher, Hash};
+
+/// A set of changes applied to a ring at once.
+///
+/// This `struct` is passed to the closure of the [apply](HashRing::apply) method on [HashRing](HashRing).
+/// See its documentation for more.
+#[derive(Debug, Clone)]
+pub struct Transaction<N> {
+    changes: Vec<Change<N>>,
+}
+
+#[derive(Debug, Clone)]
+enum Change<N> {
+    Insert(N, u64),
+    Remove(N),
+    SetWeight(N, u64),
+}
+
+/// The state of a node after all changes of a transaction.
+enum Outcome<N> {
+    Inserted(N, u64),
+    Removed(N),
+    Reweighted(N, u64),
+}
+
+impl<N> Outcome<N> {
+    fn node(&self) -> &N {
+        match self {
+            Outcome::Inserted(node, _) | Outcome::Removed(node) | Outcome::Reweighted(node, _) => {
+                node
+            }
+        }
+    }
+}
+
+impl<N> Transaction<N> {
+    /// Inserts a node with the specified weight, replacing the node if it is already part of the ring.
+    pub fn insert(&mut self, node: N, weight: u64) -> &mut Self {
+        self.changes.push(Change::Insert(node, weight));
+        self
+    }
+
+    /// Removes a node.
+    pub fn remove(&mut self, node: N) -> &mut Self {
+        self.changes.push(Change::Remove(node));
+        self
+    }
+
+    /// Sets the weight of a node, keeping its positions like [set_weight](HashRing::set_weight).
+    /// A `weight` of 0 removes the node.
+    pub fn set_weight(&mut self, node: N, weight: u64) -> &mut Self {
+        self.changes.push(Change::SetWeight(node, weight));
+        self
+    }
+
+    /// Returns the number of recorded changes.
+    pub fn len(&self) -> usize {
+        self.changes.len()
+    }
+
+    /// Returns `true` if no changes were recorded.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+impl<N, B> HashRing<N, B>
+where
+    N: Hash,
+    B: BuildHasher,
+{
+    /// Applies all changes recorded by `f` at once, or none of them if any change is invalid.
+    ///
+    /// The changes are validated in the order they were recorded, taking the previous changes into account.
+    /// Inserting a node with a weight of 0 fails with [ZeroWeight](HulahoopError::ZeroWeight), inserting a node or
+    /// setting its weight beyond [MAX_WEIGHT](HashRing::MAX_WEIGHT) fails with
+    /// [WeightTooLarge](HulahoopError::WeightTooLarge), removing a node
+    /// or setting the weight of a node which is not part of the ring fails with
+    /// [NodeAbsent](HulahoopError::NodeAbsent). Changes of the same node are combined, so only the final state of
+    /// every node is applied, and all inserted nodes are placed in a single pass like [insert_many](HashRing::insert_many).
+    /// If the final weights of all changed nodes exceed the
+    /// [total virtual node budget](crate::HashRingBuilder::total_virtual_node_budget) left by the other nodes,
+    /// the transaction fails with [BudgetExceeded](HulahoopError::BudgetExceeded) instead of reducing any weight.
+    ///
+    /// The [version](HashRing::version) is increased by one if anything changed, and the
+    /// [event callback](crate::HashRingBuilder::event_callback) is called once with a [Batch](crate::RingEvent::Batch)
+    /// of all changes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::{HashRing, HulahoopError};
+    ///
+    /// let mut ring: HashRing<&str, _> = HashRing::default();
+    /// ring.insert("10.0.0.1:1234", 10);
+    ///
+    /// ring.apply(|transaction| {
+    ///     transaction
+    ///         .insert("10.0.0.2:1234", 10)
+    ///         .set_weight("10.0.0.1:1234", 5)
+    ///         .remove("10.0.0.2:1234")
+    ///         .insert("10.0.0.3:1234", 10);
+    /// })
+    /// .unwrap();
+    /// assert_eq!(ring.len(), 2);
+    /// assert_eq!(ring.total_weight(), 15);
+    /// assert_eq!(ring.version(), 2);
+    ///
+    /// let result = ring.apply(|transaction| {
+    ///     transaction.remove("10.0.0.3:1234").remove("10.0.0.4:1234");
+    /// });
+    /// assert_eq!(result, Err(HulahoopError::NodeAbsent));
+    /// assert!(ring.contains_node(&"10.0.0.3:1234"));
+    /// ```
+    pub fn apply<F>(&mut self, f: F) -> Result<(), HulahoopError>
+    where
+        N: Eq,
+        F: FnOnce(&mut Transaction<N>),
+    {
+        let mut transaction = Transaction {
+            changes: Vec::new(),
+        };
+        f(&mut transaction);
+        let outcomes = self.validate(transaction)?;
+
+        let version = self.version;
+        let event_callback = self.event_callback.take();
+        let changes = outcomes
+            .iter()
+            .map(|(id, outcome)| {
+                let node_hash = self.hash_builder.hash_one(outcome.node());
+                let old = self.master_node_with_id_and_hash(*id, node_hash).cloned();
+                let old_positions = match (&event_callback, &old) {
+                    (Some(_), Some(old)) => self.owned_positions(old),
+                    _ => Vec::new(),
+                };
+                (*id, node_hash, old, old_positions)
+            })
+            .collect::<Vec<_>>();
+        // Weights are released before any are claimed, so the validated budget suffices for every node
+        let mut increased = Vec::new();
+        let mut inserted = Vec::new();
+        for outcome in outcomes.into_values() {
+            match outcome {
+                Outcome::Inserted(node, weight) => {
+                    self.remove_inner(&node);
+                    inserted.push((node, weight));
+                }
+                Outcome::Removed(node) => {
+                    self.remove_inner(&node);
+                }
+                Outcome::Reweighted(node, weight) => {
+                    let current_weight = self
+                        .get_master_node(&node)
+                        .map_or(0, |master_node| master_node.weight.get());
+                    if weight > current_weight {
+                        increased.push((node, weight));
+                    } else {
+                        self.upsert(node, weight);
+                    }
+                }
+            }
+        }
+        for (node, weight) in increased {
+            self.upsert(node, weight);
+        }
+        self.insert_many(inserted);
+        self.event_callback = event_callback;
+
+        if self.version != version {
+            self.version = version + 1;
+        }
+        if self.event_callback.is_some() {
+            let changes = changes
+                .into_iter()
+                .map(|(id, node_hash, old, old_positions)| {
+                    let new = self.master_node_with_id_and_hash(id, node_hash).cloned();
+                    (old, new, old_positions)
+                })
+                .collect();
+            self.notify_batch(changes);
+        }
+        Ok(())
+    }
+
+    /// Combines the changes of every node into its final state, keyed by the id of the node.
+    fn validate(
+        &self,
+        transaction: Transaction<N>,
+    ) -> Result<BTreeMap<u64, Outcome<N>>, HulahoopError> {
+        let mut outcomes = BTreeMap::new();
+        for change in transaction.changes {
+            if let Change::Insert(_, weight) | Change::SetWeight(_, weight) = change {
+                if weight > Self::MAX_WEIGHT {
+                    return Err(HulahoopError::WeightTooLarge {
+                        requested: weight,
+                        max: Self::MAX_WEIGHT,
+                    });
+                }
+            }
+            let (node, weight) = match change {
+                Change::Insert(_, 0) => return Err(HulahoopError::ZeroWeight),
+                Change::Insert(node, weight) => {
+                    let id = self.virtual_node_hash(&node, 0);
+                    outcomes.insert(id, Outcome::Inserted(node, weight));
+                    continue;
+                }
+                Change::Remove(node) => (node, 0),
+                Change::SetWeight(node, weight) => (node, weight),
+            };
+            let id = self.virtual_node_hash(&node, 0);
+            let outcome = match outcomes.remove(&id) {
+                Some(Outcome::Removed(_)) => return Err(HulahoopError::NodeAbsent),
+                None if self.get_master_node(&node).is_none() => {
+                    return Err(HulahoopError::NodeAbsent)
+                }
+                _ if weight == 0 => Outcome::Removed(node),
+                Some(Outcome::Inserted(_, _)) => Outcome::Inserted(node, weight),
+                Some(Outcome::Reweighted(_, _)) | None => Outcome::Reweighted(node, weight),
+            };
+            outcomes.insert(id, outcome);
+        }
+        if let Some(budget) = self.total_virtual_node_budget {
+            let (current_weight, requested) =
+                outcomes
+                    .values()
+                    .fold((0u64, 0u64), |(current_weight, requested), outcome| {
+                        let weight = match outcome {
+                            Outcome::Inserted(_, weight) | Outcome::Reweighted(_, weight) => {
+                                *weight
+                            }
+                            Outcome::Removed(_) => 0,
+                        };
+                        let node_weight = self
+                            .get_master_node(outcome.node())
+                            .map_or(0, |master_node| master_node.weight.get());
+                        (
+                            current_weight + node_weight,
+                            requested.saturating_add(weight),
+                        )
+                    });
+            let remaining = budget.saturating_sub(self.total_weight() - current_weight);
+            if requested > remaining {
+                return Err(HulahoopError::BudgetExceeded {
+                    requested,
+                    remaining,
+                });
+            }
+        }
+        Ok(outcomes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{HashRing, HashRingBuilder, HulahoopError, RingEvent};
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn invalid_transactions_change_nothing() {
+        let mut ring: HashRing<u32, _> = HashRing::new();
+        ring.insert(1, 10);
+        let expected = ring.clone();
+
+        let result = ring.apply(|transaction| {
+            transaction.insert(2, 10).remove(2).set_weight(2, 5);
+        });
+        assert_eq!(result, Err(HulahoopError::NodeAbsent));
+        let result = ring.apply(|transaction| {
+            transaction.set_weight(1, 5).insert(3, 0);
+        });
+        assert_eq!(result, Err(HulahoopError::ZeroWeight));
+        let result = ring.apply(|transaction| {
+            transaction.set_weight(1, u64::MAX);
+        });
+        assert_eq!(
+            result,
+            Err(HulahoopError::WeightTooLarge {
+                requested: u64::MAX,
+                max: HashRing::<u32, ()>::MAX_WEIGHT
+            })
+        );
+        assert_eq!(ring.version(), expected.version());
+        assert_eq!(ring.to_weights_map(), expected.to_weights_map());
+        assert!(ring.agrees_with(&expected, &(0..1_000).collect::<Vec<_>>()));
+    }
+
+    #[test]
+    fn transactions_do_not_mistake_nodes_holding_the_first_position_of_another() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let recorded = events.clone();
+        let mut ring = HashRingBuilder::default()
+            .virtual_node_fn(|_, node: &&str, index| match (*node, index) {
+                ("a", _) => 10 + index,
+                ("b", 0) => 20,
+                (_, _) => 10,
+            })
+            .event_callback(move |event: &RingEvent<'_, &str>| {
+                if let RingEvent::Batch { events, .. } = event {
+                    let removed = events
+                        .iter()
+                        .filter(|event| matches!(event, RingEvent::NodeRemoved { .. }))
+                        .filter_map(|event| event.node().copied());
+                    recorded.lock().unwrap().extend(removed);
+                }
+            })
+            .build();
+        ring.insert("a", 2);
+        // "b" takes over the first position of "a"
+        ring.insert("b", 2);
+
+        let result = ring.apply(|transaction| {
+            transaction.remove("c");
+        });
+        assert_eq!(result, Err(HulahoopError::NodeAbsent));
+
+        ring.apply(|transaction| {
+            transaction.remove("a");
+        })
+        .unwrap();
+        assert_eq!(ring.to_weights_map(), HashMap::from([("b", 2)]));
+        assert_eq!(*events.lock().unwrap(), vec!["a"]);
+    }
+
+    #[test]
+    fn transactions_are_validated_against_the_budget() {
+        let mut ring = HashRingBuilder::default()
+            .total_virtual_node_budget(30)
+            .build();
+        ring.insert(1, 10);
+        ring.insert(2, 10);
+
+        let result = ring.apply(|transaction| {
+            transaction.set_weight(1, 15).insert(3, 10);
+        });
+        assert_eq!(
+            result,
+            Err(HulahoopError::BudgetExceeded {
+                requested: 25,
+                remaining: 20
+            })
+        );
+        assert_eq!(ring.total_weight(), 20);
+
+        // The weight released by one node is available to the others, regardless of their order
+        ring.apply(|transaction| {
+            transaction.set_weight(1, 18).insert(3, 10).set_weight(2, 2);
+        })
+        .unwrap();
+        assert_eq!(
+            ring.to_weights_map(),
+            HashMap::from([(1, 18), (2, 2), (3, 10)])
+        );
+    }
+
+    #[test]
+    fn transactions_match_individual_changes_and_report_one_event() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let recorded = events.clone();
+        let mut ring = HashRingBuilder::default()
+            .event_callback(move |event: &RingEvent<'_, u32>| {
+                recorded.lock().unwrap().push(event.ranges().to_vec());
+            })
+            .build();
+        for node in 0..10 {
+            ring.insert(node, 10);
+        }
+        let before = ring.clone();
+        let mut expected = ring.clone();
+        expected.remove(&0);
+        expected.set_weight(&1, 20);
+        expected.insert(10, 5);
+        events.lock().unwrap().clear();
+
+        ring.apply(|transaction| {
+            transaction
+                .remove(0)
+                .set_weight(1, 20)
+                .insert(10, 10)
+                .set_weight(10, 5)
+                .insert(11, 10)
+                .remove(11);
+        })
+        .unwrap();
+
+        assert_eq!(ring.to_weights_map(), expected.to_weights_map());
+        assert!(ring.agrees_with(&expected, &(0..1_000).collect::<Vec<_>>()));
+        assert_eq!(ring.version(), before.version() + 1);
+
+        let events = events.lock().unwrap();
+        assert_eq!(events.len(), 1);
+        for key in (0..1_000).filter(|key| ring.get(key) != before.get(key)) {
+            let key_hash = ring.hash_key(key).get();
+            assert!(events[0]
+                .iter()
+                .any(|(start, end)| (*start..=*end).contains(&key_hash)));
+        }
+    }
+}
@@ -0,0 +1,86 @@
+//! Rendering of a [HashRing](HashRing) as a Graphviz diagram.
+
+use crate::HashRing;
+use std::fmt::{Display, Write};
+use std::hash::{BuildHasher, Hash};
+
+impl<N, B> HashRing<N, B>
+where
+    N: Hash + Display,
+    B: BuildHasher,
+{
+    /// Returns a [Graphviz](https://graphviz.org) DOT representation of the ring.
+    ///
+    /// Every arc of the [ownership_map](HashRing::ownership_map) is drawn as a box labeled with its node,
+    /// its range of hashes and its share of the hash space. The boxes are connected clockwise to form a circle,
+    /// so `dot -Kcirco -Tpng` renders the ring as it routes keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hulahoop::HashRing;
+    ///
+    /// let mut ring: HashRing<&str, _> = HashRing::default();
+    ///
+    /// ring.insert("10.0.0.1:1234", 1);
+    /// assert!(ring.to_dot().starts_with("digraph HashRing {"));
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "viz")))]
+    pub fn to_dot(&self) -> String {
+        let ownership_map = self.ownership_map();
+        let mut dot =
+            String::from("digraph HashRing {\n    layout=circo;\n    node [shape=box];\n");
+        for (index, (start, end, node)) in ownership_map.iter().enumerate() {
+            let share = ((*end - *start) as f64 + 1.0) / (u64::MAX as f64 + 1.0);
+            let label = escape(&format!("{node}\n{start}..={end}\n{:.2}%", share * 100.0));
+            // Writing to a `String` never fails
+            writeln!(dot, "    arc{index} [label=\"{label}\"];").unwrap();
+        }
+        for index in 0..ownership_map.len() {
+            let next = (index + 1) % ownership_map.len();
+            writeln!(dot, "    arc{index} -> arc{next};").unwrap();
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+fn escape(label: &str) -> String {
+    label
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::HashRing;
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::BuildHasherDefault;
+
+    #[test]
+    fn dot_contains_every_arc_in_order() {
+        let mut ring = HashRing::with_virtual_node_fn(
+            BuildHasherDefault::<DefaultHasher>::default(),
+            |_, node: &&str, _| match *node {
+                "a" => u64::MAX / 2,
+                _ => u64::MAX,
+            },
+        );
+        assert_eq!(
+            ring.to_dot(),
+            "digraph HashRing {\n    layout=circo;\n    node [shape=box];\n}\n"
+        );
+
+        ring.insert("a", 1);
+        ring.insert("\"b\"", 1);
+        assert_eq!(
+            ring.to_dot(),
+            "digraph HashRing {\n    layout=circo;\n    node [shape=box];\n    \
+             arc0 [label=\"a\\n0..=9223372036854775807\\n50.00%\"];\n    \
+             arc1 [label=\"\\\"b\\\"\\n9223372036854775808..=18446744073709551615\\n50.00%\"];\n    \
+             arc0 -> arc1;\n    \
+             arc1 -> arc0;\n}\n"
+        );
+    }
+}
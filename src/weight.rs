@@ -0,0 +1,46 @@
+use std::num::NonZeroU64;
+
+/// The non-zero weight of a node, i.e. the number of its virtual nodes.
+///
+/// Contrary to a plain `u64`, a `Weight` can never be zero, so nodes inserted with it are never silently dropped.
+///
+/// # Examples
+///
+/// ```
+/// use hulahoop::Weight;
+///
+/// assert_eq!(Weight::new(10).map(Weight::get), Some(10));
+/// assert_eq!(Weight::new(0), None);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Weight(NonZeroU64);
+
+impl Weight {
+    /// Creates a `Weight` if the given value is not zero.
+    pub fn new(weight: u64) -> Option<Self> {
+        NonZeroU64::new(weight).map(Self)
+    }
+
+    /// Returns the weight as a primitive type.
+    pub fn get(self) -> u64 {
+        self.0.get()
+    }
+}
+
+impl From<NonZeroU64> for Weight {
+    fn from(weight: NonZeroU64) -> Self {
+        Self(weight)
+    }
+}
+
+impl From<Weight> for NonZeroU64 {
+    fn from(weight: Weight) -> Self {
+        weight.0
+    }
+}
+
+impl From<Weight> for u64 {
+    fn from(weight: Weight) -> Self {
+        weight.get()
+    }
+}